@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+pub type CoreResult<T> = Result<T, CoreError>;
+
+#[derive(Debug, Error)]
+pub enum CoreError {
+    #[error("Latitude {0} is out of range, expected a value between -90 and 90")]
+    LatitudeOutOfRange(f32),
+    #[error("Longitude {0} is out of range, expected a value between -180 and 180")]
+    LongitudeOutOfRange(f32),
+    #[error("Invalid quantity '{0}', expected a number")]
+    InvalidQuantity(String),
+    #[error("Missing unit in '{input}', expected one of {expected}")]
+    MissingUnit { input: String, expected: String },
+    #[error("Unknown unit '{unit}' in '{input}', expected one of {expected}")]
+    UnknownUnit {
+        input: String,
+        unit: String,
+        expected: String,
+    },
+    #[error("Invalid quantity '{0}', expected a value of at least 0")]
+    NegativeQuantity(String),
+}
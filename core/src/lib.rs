@@ -1 +1,4 @@
+pub mod error;
+pub mod parse;
+pub mod quantity;
 pub mod types;
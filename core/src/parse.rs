@@ -0,0 +1,308 @@
+//! Parsing of quantities like `"5 °C"`, `"41F"` or `"12 km/h"` for the `FromStr` impls of
+//! [`crate::types`].
+//!
+//! A quantity is a number followed by a unit, optionally separated by whitespace. Every type
+//! accepts the symbols its `format_*` methods write, so formatted values parse back up to the
+//! rounding of the formatting, as well as the aliases of [`UNIT_ALIASES`]. Units are matched
+//! exactly first, then ignoring ASCII case, e.g. `"hpa"` or `"KM/H"`.
+
+use crate::error::{CoreError, CoreResult};
+
+/// Other spellings of the units written by the `format_*` methods, alias first.
+pub const UNIT_ALIASES: &[(&str, &str)] = &[
+    // Temperature
+    ("C", "°C"),
+    ("℃", "°C"),
+    ("degC", "°C"),
+    ("celsius", "°C"),
+    ("F", "°F"),
+    ("℉", "°F"),
+    ("degF", "°F"),
+    ("fahrenheit", "°F"),
+    ("kelvin", "K"),
+    // Speed
+    ("mps", "m/s"),
+    ("kmh", "km/h"),
+    ("kph", "km/h"),
+    ("kmph", "km/h"),
+    ("mi/h", "mph"),
+    // Pressure
+    ("mbar", "hPa"),
+    ("millibar", "hPa"),
+    // Length
+    ("millimeters", "mm"),
+    ("centimeters", "cm"),
+    ("meters", "m"),
+    ("inch", "in"),
+    ("inches", "in"),
+    ("\"", "in"),
+    ("mile", "mi"),
+    ("miles", "mi"),
+    // Energy and power density
+    ("J/m2", "J/m^2"),
+    ("J/m²", "J/m^2"),
+    ("MJ/m2", "MJ/m^2"),
+    ("MJ/m²", "MJ/m^2"),
+    ("W/m2", "W/m²"),
+    ("W/m^2", "W/m²"),
+    ("kW/m2", "kW/m²"),
+    ("kW/m^2", "kW/m²"),
+    // Digital information
+    ("byte", "B"),
+    ("bytes", "B"),
+    // Angle and percentage
+    ("deg", "°"),
+    ("degrees", "°"),
+    ("radians", "rad"),
+    ("percent", "%"),
+];
+
+/// Splits `input` into its number and one of `units`, resolving aliases.
+pub(crate) fn parse_quantity(
+    input: &str,
+    units: &[&'static str],
+) -> CoreResult<(f64, &'static str)> {
+    let trimmed = input.trim();
+    let bytes = trimmed.as_bytes();
+    let mut end = 0;
+    while end < bytes.len() {
+        let is_number = match bytes[end] {
+            b'0'..=b'9' | b'.' => true,
+            b'+' | b'-' => end == 0 || matches!(bytes[end - 1], b'e' | b'E'),
+            b'e' | b'E' => bytes
+                .get(end + 1)
+                .is_some_and(|next| next.is_ascii_digit() || matches!(next, b'+' | b'-')),
+            _ => false,
+        };
+        if !is_number {
+            break;
+        }
+        end += 1;
+    }
+
+    let (number, unit) = trimmed.split_at(end);
+    let value = number
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| CoreError::InvalidQuantity(input.to_string()))?;
+
+    let unit = unit.trim();
+    if unit.is_empty() {
+        return Err(CoreError::MissingUnit {
+            input: input.to_string(),
+            expected: units.join(", "),
+        });
+    }
+    resolve_unit(unit, units)
+        .map(|unit| (value, unit))
+        .ok_or_else(|| CoreError::UnknownUnit {
+            input: input.to_string(),
+            unit: unit.to_string(),
+            expected: units.join(", "),
+        })
+}
+
+fn resolve_unit(unit: &str, units: &[&'static str]) -> Option<&'static str> {
+    let candidates = || {
+        units.iter().map(|symbol| (*symbol, *symbol)).chain(
+            UNIT_ALIASES
+                .iter()
+                .copied()
+                .filter(|(_, symbol)| units.contains(symbol)),
+        )
+    };
+    candidates()
+        .find(|(spelling, _)| *spelling == unit)
+        .or_else(|| candidates().find(|(spelling, _)| spelling.eq_ignore_ascii_case(unit)))
+        .map(|(_, symbol)| symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::angle::Angle;
+    use crate::types::area_energy_density::AreaEnergyDensity;
+    use crate::types::area_power_density::AreaPowerDensity;
+    use crate::types::digital_information::DigitalInformation;
+    use crate::types::length::Length;
+    use crate::types::percentage::Percentage;
+    use crate::types::pressure::Pressure;
+    use crate::types::speed::Speed;
+    use crate::types::temperature::Temperature;
+    use crate::types::temperature_difference::TemperatureDifference;
+    use crate::types::uv_index::UVIndex;
+    use std::str::FromStr;
+
+    /// Formatting what was parsed from a formatted value gives the same text.
+    fn round_trip<T: FromStr<Err = CoreError>>(
+        formatted: &[String],
+        format: impl Fn(&T) -> String,
+    ) {
+        for text in formatted {
+            let parsed = text.parse::<T>().unwrap_or_else(|e| panic!("{text}: {e}"));
+            assert_eq!(&format(&parsed), text);
+        }
+    }
+
+    #[test]
+    fn formatted_values_parse_back() {
+        let temperature = Temperature::from_celsius(-3.25);
+        round_trip(&[temperature.format_kelvin()], Temperature::format_kelvin);
+        round_trip(&[temperature.format_celsius()], Temperature::format_celsius);
+        round_trip(
+            &[temperature.format_fahrenheit()],
+            Temperature::format_fahrenheit,
+        );
+        let difference = TemperatureDifference::from_celsius(2.5);
+        round_trip(
+            &[difference.format_celsius()],
+            TemperatureDifference::format_celsius,
+        );
+        round_trip(
+            &[difference.format_fahrenheit()],
+            TemperatureDifference::format_fahrenheit,
+        );
+        round_trip(
+            &[difference.format_kelvin()],
+            TemperatureDifference::format_kelvin,
+        );
+
+        let speed = Speed::from_km_h(12.5);
+        round_trip(&[speed.format_m_s()], Speed::format_m_s);
+        round_trip(&[speed.format_km_h()], Speed::format_km_h);
+        round_trip(&[speed.format_mph()], Speed::format_mph);
+
+        let pressure = Pressure::from_hpa(1013.25);
+        round_trip(&[pressure.format_hpa()], Pressure::format_hpa);
+        round_trip(&[pressure.format_kpa()], Pressure::format_kpa);
+        round_trip(&[pressure.format_bar()], Pressure::format_bar);
+        round_trip(&[pressure.format_inhg()], Pressure::format_inhg);
+
+        let length = Length::from_millimeters(1234.5);
+        round_trip(&[length.format_millimeters()], Length::format_millimeters);
+        round_trip(&[length.format_centimeters()], Length::format_centimeters);
+        round_trip(&[length.format_meters()], Length::format_meters);
+        round_trip(&[length.format_inches()], Length::format_inches);
+        let miles = Length::from_meters(16093.44);
+        round_trip(&[miles.format_miles()], Length::format_miles);
+
+        let energy = AreaEnergyDensity::from_mj_m2(12.5);
+        round_trip(&[energy.format_j_m2()], AreaEnergyDensity::format_j_m2);
+        round_trip(&[energy.format_mj_m2()], AreaEnergyDensity::format_mj_m2);
+        let power = AreaPowerDensity::from_w_m2(812.5);
+        round_trip(&[power.format_w_m2()], AreaPowerDensity::format_w_m2);
+        round_trip(&[power.format_kw_m2()], AreaPowerDensity::format_kw_m2);
+
+        let angle = Angle::from_degrees(247.5);
+        round_trip(&[angle.format_degrees()], Angle::format_degrees);
+        round_trip(&[angle.format_radians()], Angle::format_radians);
+
+        let sizes = [512, 1_500, 16_000_000, 2_500_000_000, 3_000_000_000_000];
+        round_trip(
+            &sizes.map(|bytes| DigitalInformation::from_bytes(bytes).format_pretty()),
+            DigitalInformation::format_pretty,
+        );
+    }
+
+    #[test]
+    fn accepts_aliases_and_any_case() {
+        let celsius = |text: &str| text.parse::<Temperature>().unwrap().as_celsius();
+        assert!((celsius("41F") - 5.0).abs() < 1e-4);
+        assert!((celsius("5 c") - 5.0).abs() < 1e-4);
+        assert!((celsius(" 5degC ") - 5.0).abs() < 1e-4);
+        assert_eq!(
+            "12 kmh".parse::<Speed>().unwrap(),
+            "12 KM/H".parse().unwrap()
+        );
+        assert_eq!(
+            "1013 mbar".parse::<Pressure>().unwrap(),
+            "1013 hpa".parse().unwrap()
+        );
+        assert_eq!(
+            "2 inches".parse::<Length>().unwrap(),
+            "2 in".parse().unwrap()
+        );
+        assert_eq!(
+            "25 percent".parse::<Percentage>().unwrap(),
+            "25%".parse().unwrap()
+        );
+        assert_eq!(
+            "1e3 W/m2".parse::<AreaPowerDensity>().unwrap(),
+            "1 kW/m²".parse().unwrap()
+        );
+        assert_eq!("6.5".parse::<UVIndex>().unwrap(), UVIndex::new(6.5));
+    }
+
+    #[test]
+    fn bytes_take_decimal_and_binary_units() {
+        let bytes = |text: &str| text.parse::<DigitalInformation>().unwrap().as_bytes();
+        assert_eq!(bytes("512 B"), 512.0);
+        assert_eq!(bytes("16 bytes"), 16.0);
+        assert_eq!(bytes("1 kB"), 1_000.0);
+        assert_eq!(bytes("1 KB"), 1_000.0);
+        assert_eq!(bytes("16 MB"), 16_000_000.0);
+        assert_eq!(bytes("1 GB"), 1e9);
+        assert_eq!(bytes("1 TB"), 1e12);
+        assert_eq!(bytes("1.5 KiB"), 1_536.0);
+        assert_eq!(bytes("16 MiB"), 16_777_216.0);
+        assert_eq!(bytes("16 mib"), 16_777_216.0);
+        assert_eq!(bytes("1 GiB"), 1_073_741_824.0);
+        assert_eq!(bytes("1 TiB"), 1_099_511_627_776.0);
+        assert_eq!(bytes("0.4 B"), 0.0);
+    }
+
+    #[test]
+    fn rejects_bad_quantities() {
+        assert!(matches!(
+            "5 parsecs".parse::<Length>(),
+            Err(CoreError::UnknownUnit { unit, .. }) if unit == "parsecs"
+        ));
+        assert!(matches!(
+            "5 °C".parse::<Speed>(),
+            Err(CoreError::UnknownUnit { .. })
+        ));
+        assert!(matches!(
+            "16 Mb".parse::<Length>(),
+            Err(CoreError::UnknownUnit { .. })
+        ));
+        assert!(matches!(
+            "16 MiBs".parse::<DigitalInformation>(),
+            Err(CoreError::UnknownUnit { .. })
+        ));
+        assert!(matches!(
+            "16".parse::<DigitalInformation>(),
+            Err(CoreError::MissingUnit { .. })
+        ));
+        for input in [
+            "", "kB", "abc kB", "1..5 kB", "inf kB", "NaN kB", "1e400 kB",
+        ] {
+            assert!(
+                matches!(
+                    input.parse::<DigitalInformation>(),
+                    Err(CoreError::InvalidQuantity(_))
+                ),
+                "{input:?}"
+            );
+        }
+        assert!(matches!(
+            "-1 kB".parse::<DigitalInformation>(),
+            Err(CoreError::NegativeQuantity(_))
+        ));
+        assert!(matches!(
+            "high".parse::<UVIndex>(),
+            Err(CoreError::InvalidQuantity(_))
+        ));
+    }
+
+    #[test]
+    fn aliases_name_known_units() {
+        let formatted = [
+            "°C", "°F", "K", "m/s", "km/h", "mph", "hPa", "mm", "cm", "m", "in", "mi", "J/m^2",
+            "MJ/m^2", "W/m²", "kW/m²", "B", "°", "rad", "%",
+        ];
+        for (alias, unit) in UNIT_ALIASES {
+            assert!(formatted.contains(unit), "{alias} -> {unit}");
+        }
+    }
+}
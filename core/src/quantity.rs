@@ -0,0 +1,186 @@
+//! Arithmetic shared by the types of [`crate::types`].
+//!
+//! Linear quantities like [`Length`](crate::types::length::Length) add and subtract with
+//! themselves, scale by plain numbers and divide into a plain ratio, so mixing up units needs an
+//! explicit `from_*`/`as_*`. Products of different dimensions are implemented where they make
+//! sense, e.g. `Speed * Duration = Length`.
+//!
+//! [`Temperature`](crate::types::temperature::Temperature) is affine instead: absolute
+//! temperatures can't be added or scaled, `20 °C + 20 °C` isn't `40 °C`. Their difference is a
+//! linear [`TemperatureDifference`](crate::types::temperature_difference::TemperatureDifference)
+//! which can be added to or subtracted from a temperature.
+
+use std::iter::Sum;
+use std::ops::{Add, Div, Mul, Sub};
+
+/// A linear quantity, stored as a number of its base unit.
+pub trait Quantity:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Self::Value, Output = Self>
+    + Div<Self::Value, Output = Self>
+    + Div<Output = Self::Value>
+    + Sum
+    + Mean
+{
+    /// Number type of the base unit
+    type Value: Copy;
+
+    fn from_base(value: Self::Value) -> Self;
+
+    fn base(self) -> Self::Value;
+}
+
+/// Average of values of the same kind.
+pub trait Mean: Sized {
+    /// `None` without any values.
+    fn mean<I: IntoIterator<Item = Self>>(values: I) -> Option<Self>;
+}
+
+/// [`Mean::mean`] at the end of an iterator, like [`Iterator::sum`].
+pub trait MeanExt: Iterator<Item: Mean> + Sized {
+    fn mean(self) -> Option<Self::Item> {
+        Mean::mean(self)
+    }
+}
+
+impl<I: Iterator<Item: Mean>> MeanExt for I {}
+
+/// Implements [`Quantity`] and its operators for a newtype around a float of the base unit, as
+/// well as `total_cmp`, `min` and `max` behaving like the ones of the float.
+macro_rules! linear_quantity {
+    ($quantity:ident, $value:ty) => {
+        impl $crate::quantity::Quantity for $quantity {
+            type Value = $value;
+
+            fn from_base(value: $value) -> Self {
+                Self(value)
+            }
+
+            fn base(self) -> $value {
+                self.0
+            }
+        }
+
+        impl $quantity {
+            pub fn total_cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0.total_cmp(&other.0)
+            }
+
+            pub fn min(self, other: Self) -> Self {
+                Self(self.0.min(other.0))
+            }
+
+            pub fn max(self, other: Self) -> Self {
+                Self(self.0.max(other.0))
+            }
+        }
+
+        impl std::ops::Add for $quantity {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl std::ops::Sub for $quantity {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl std::ops::Neg for $quantity {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        impl std::ops::Mul<$value> for $quantity {
+            type Output = Self;
+
+            fn mul(self, rhs: $value) -> Self {
+                Self(self.0 * rhs)
+            }
+        }
+
+        impl std::ops::Mul<$quantity> for $value {
+            type Output = $quantity;
+
+            fn mul(self, rhs: $quantity) -> $quantity {
+                $quantity(self * rhs.0)
+            }
+        }
+
+        impl std::ops::Div<$value> for $quantity {
+            type Output = Self;
+
+            fn div(self, rhs: $value) -> Self {
+                Self(self.0 / rhs)
+            }
+        }
+
+        impl std::ops::Div for $quantity {
+            type Output = $value;
+
+            fn div(self, rhs: Self) -> $value {
+                self.0 / rhs.0
+            }
+        }
+
+        impl std::ops::AddAssign for $quantity {
+            fn add_assign(&mut self, rhs: Self) {
+                self.0 += rhs.0;
+            }
+        }
+
+        impl std::ops::SubAssign for $quantity {
+            fn sub_assign(&mut self, rhs: Self) {
+                self.0 -= rhs.0;
+            }
+        }
+
+        impl std::ops::MulAssign<$value> for $quantity {
+            fn mul_assign(&mut self, rhs: $value) {
+                self.0 *= rhs;
+            }
+        }
+
+        impl std::ops::DivAssign<$value> for $quantity {
+            fn div_assign(&mut self, rhs: $value) {
+                self.0 /= rhs;
+            }
+        }
+
+        impl std::iter::Sum for $quantity {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                Self(iter.map(|quantity| quantity.0).sum())
+            }
+        }
+
+        impl<'a> std::iter::Sum<&'a $quantity> for $quantity {
+            fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.copied().sum()
+            }
+        }
+
+        impl $crate::quantity::Mean for $quantity {
+            fn mean<I: IntoIterator<Item = Self>>(values: I) -> Option<Self> {
+                let (sum, count) = values
+                    .into_iter()
+                    .fold((0.0, 0usize), |(sum, count), quantity| {
+                        (sum + quantity.0, count + 1)
+                    });
+                (count > 0).then(|| Self(sum / count as $value))
+            }
+        }
+    };
+}
+
+pub(crate) use linear_quantity;
@@ -9,5 +9,155 @@ pub mod percentage;
 pub mod pressure;
 pub mod speed;
 pub mod temperature;
+pub mod temperature_difference;
 pub mod uv_index;
 pub mod wmo_code;
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use super::*;
+    use serde::Serialize;
+    use serde::de::DeserializeOwned;
+    use std::fmt::Debug;
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: T, json: &str) {
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        assert_eq!(serde_json::from_str::<T>(json).unwrap(), value);
+    }
+
+    #[test]
+    fn quantities_are_plain_numbers_of_their_base_unit() {
+        round_trip(angle::Angle::from_radians(1.5), "1.5");
+        round_trip(
+            area_energy_density::AreaEnergyDensity::from_j_m2(1500.0),
+            "1500.0",
+        );
+        round_trip(
+            area_power_density::AreaPowerDensity::from_w_m2(800.0),
+            "800.0",
+        );
+        round_trip(
+            digital_information::DigitalInformation::from_bytes(1024),
+            "1024",
+        );
+        round_trip(length::Length::from_meters(2.5), "2.5");
+        round_trip(percentage::Percentage::from_0_1(0.4), "0.4");
+        round_trip(pressure::Pressure::from_hpa(1013.0), "1013.0");
+        round_trip(speed::Speed::from_m_s(10.0), "10.0");
+        round_trip(temperature::Temperature::from_kelvin(293.15), "293.15");
+        round_trip(
+            temperature_difference::TemperatureDifference::from_kelvin(2.5),
+            "2.5",
+        );
+        round_trip(uv_index::UVIndex::new(6.0), "6.0");
+    }
+
+    #[test]
+    fn wmo_codes_are_numeric() {
+        round_trip(wmo_code::WMOCode::Rain, "63");
+        assert_eq!(
+            serde_json::from_str::<wmo_code::WMOCode>("255").unwrap(),
+            wmo_code::WMOCode::from(255)
+        );
+    }
+
+    #[test]
+    fn coordinates_round_trip_and_reject_out_of_range_values() {
+        let json = serde_json::to_string(&latitude::Latitude::new(52.5)).unwrap();
+        assert_eq!(json, "52.5");
+        let decoded = serde_json::from_str::<latitude::Latitude>(&json).unwrap();
+        assert_eq!(decoded.value(), 52.5);
+        let json = serde_json::to_string(&longitude::Longitude::new(-13.4)).unwrap();
+        let decoded = serde_json::from_str::<longitude::Longitude>(&json).unwrap();
+        assert_eq!(decoded.value(), -13.4);
+
+        for json in ["90.5", "-90.5", "1e9"] {
+            assert!(
+                serde_json::from_str::<latitude::Latitude>(json).is_err(),
+                "{json}"
+            );
+        }
+        for json in ["180.5", "-180.5", "1e9"] {
+            assert!(
+                serde_json::from_str::<longitude::Longitude>(json).is_err(),
+                "{json}"
+            );
+        }
+    }
+}
+
+#[cfg(all(test, feature = "bincode"))]
+mod bincode_tests {
+    use super::*;
+    use bincode::{Decode, Encode};
+    use std::fmt::Debug;
+
+    fn encode<T: Encode>(value: T) -> Vec<u8> {
+        bincode::encode_to_vec(value, bincode::config::standard()).unwrap()
+    }
+
+    fn decode<T: Decode<()>>(bytes: &[u8]) -> Result<T, bincode::error::DecodeError> {
+        bincode::decode_from_slice(bytes, bincode::config::standard()).map(|(value, _)| value)
+    }
+
+    fn round_trip<T: Encode + Decode<()> + PartialEq + Debug + Copy>(value: T) {
+        assert_eq!(decode::<T>(&encode(value)).unwrap(), value);
+    }
+
+    #[test]
+    fn quantities_round_trip() {
+        round_trip(angle::Angle::from_degrees(90.0));
+        round_trip(area_energy_density::AreaEnergyDensity::from_j_m2(1500.0));
+        round_trip(area_power_density::AreaPowerDensity::from_w_m2(800.0));
+        round_trip(digital_information::DigitalInformation::from_bytes(1024));
+        round_trip(length::Length::from_millimeters(2.5));
+        round_trip(percentage::Percentage::from_0_100(40.0));
+        round_trip(pressure::Pressure::from_hpa(1013.0));
+        round_trip(speed::Speed::from_km_h(36.0));
+        round_trip(temperature::Temperature::from_celsius(20.0));
+        round_trip(temperature_difference::TemperatureDifference::from_kelvin(
+            2.5,
+        ));
+        round_trip(uv_index::UVIndex::new(6.0));
+        round_trip(wmo_code::WMOCode::Rain);
+    }
+
+    #[test]
+    fn coordinates_are_encoded_as_their_degrees() {
+        let latitude = latitude::Latitude::new(52.5);
+        assert_eq!(encode(latitude), encode(52.5f32));
+        assert_eq!(
+            decode::<latitude::Latitude>(&encode(latitude))
+                .unwrap()
+                .value(),
+            52.5
+        );
+        let longitude = longitude::Longitude::new(-13.4);
+        assert_eq!(encode(longitude), encode(-13.4f32));
+        assert_eq!(
+            decode::<longitude::Longitude>(&encode(longitude))
+                .unwrap()
+                .value(),
+            -13.4
+        );
+    }
+
+    #[test]
+    fn out_of_range_coordinates_fail_to_decode() {
+        for degrees in [90.5f32, -90.5, f32::NAN, f32::INFINITY] {
+            assert!(
+                decode::<latitude::Latitude>(&encode(degrees)).is_err(),
+                "{degrees}"
+            );
+        }
+        for degrees in [180.5f32, -180.5, f32::NAN, f32::INFINITY] {
+            assert!(
+                decode::<longitude::Longitude>(&encode(degrees)).is_err(),
+                "{degrees}"
+            );
+        }
+        // Unchecked values still encode, the receiving side rejects them
+        let bytes = encode(latitude::Latitude::new(100.0));
+        assert!(decode::<latitude::Latitude>(&bytes).is_err());
+    }
+}
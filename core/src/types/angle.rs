@@ -1,10 +1,23 @@
+use crate::error::{CoreError, CoreResult};
+use crate::parse::parse_quantity;
+use crate::quantity::linear_quantity;
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
 #[repr(transparent)]
 /// Angle in radians
+///
+/// Serialized and encoded as a number of radians.
 pub struct Angle(f32);
 
+linear_quantity!(Angle, f32);
+
 impl Angle {
     pub fn from_radians(value: f32) -> Self {
         Self(value)
@@ -29,4 +42,31 @@ impl Angle {
     pub fn format_degrees(&self) -> String {
         format!("{:.2}°", self.as_degrees())
     }
+
+    /// Mean direction of angles like wind directions, 350° and 10° average to 0° instead of the
+    /// 180° of [`Mean::mean`](crate::quantity::Mean::mean). `None` without angles or if they
+    /// cancel out, e.g. 0° and 180°.
+    pub fn circular_mean<I: IntoIterator<Item = Self>>(angles: I) -> Option<Self> {
+        let (sin, cos, count) = angles
+            .into_iter()
+            .fold((0.0f32, 0.0f32, 0usize), |(sin, cos, count), angle| {
+                (sin + angle.0.sin(), cos + angle.0.cos(), count + 1)
+            });
+        let resultant = sin.hypot(cos) / count.max(1) as f32;
+        (resultant > 1e-6).then(|| Self(sin.atan2(cos)))
+    }
+}
+
+/// E.g. `"90°"` or `"1.57 rad"`, see [`crate::parse`].
+impl FromStr for Angle {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        let (value, unit) = parse_quantity(s, &["rad", "°"])?;
+        Ok(match unit {
+            "rad" => Self::from_radians(value as f32),
+            "°" => Self::from_degrees(value as f32),
+            _ => unreachable!("unit {unit} is not in the list"),
+        })
+    }
 }
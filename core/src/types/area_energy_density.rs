@@ -1,17 +1,33 @@
+use crate::error::{CoreError, CoreResult};
+use crate::parse::parse_quantity;
+use crate::quantity::linear_quantity;
+use crate::types::area_power_density::AreaPowerDensity;
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::ops::Mul;
+use std::str::FromStr;
+use std::time::Duration;
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
 #[repr(transparent)]
 /// Energy density in J/m^2
+///
+/// Serialized and encoded as a number of J/m^2.
 pub struct AreaEnergyDensity(f32);
 
+linear_quantity!(AreaEnergyDensity, f32);
+
 impl AreaEnergyDensity {
     pub fn from_j_m2(value: f32) -> Self {
         Self(value)
     }
 
     pub fn from_mj_m2(value: f32) -> Self {
-        Self(value / 1_000_000.0)
+        Self(value * 1_000_000.0)
     }
 
     pub fn as_j_m2(&self) -> f32 {
@@ -19,7 +35,7 @@ impl AreaEnergyDensity {
     }
 
     pub fn as_mj_m2(&self) -> f32 {
-        self.0 * 1_000_000.0
+        self.0 / 1_000_000.0
     }
 
     pub fn format_j_m2(&self) -> String {
@@ -30,3 +46,34 @@ impl AreaEnergyDensity {
         format!("{:.2} MJ/m^2", self.as_mj_m2())
     }
 }
+
+/// Energy received at a constant power, e.g. radiation over an hour.
+impl Mul<Duration> for AreaPowerDensity {
+    type Output = AreaEnergyDensity;
+
+    fn mul(self, rhs: Duration) -> AreaEnergyDensity {
+        AreaEnergyDensity::from_j_m2((self.as_w_m2() as f64 * rhs.as_secs_f64()) as f32)
+    }
+}
+
+impl Mul<AreaPowerDensity> for Duration {
+    type Output = AreaEnergyDensity;
+
+    fn mul(self, rhs: AreaPowerDensity) -> AreaEnergyDensity {
+        rhs * self
+    }
+}
+
+/// E.g. `"12.5 MJ/m^2"`, see [`crate::parse`].
+impl FromStr for AreaEnergyDensity {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        let (value, unit) = parse_quantity(s, &["J/m^2", "MJ/m^2"])?;
+        Ok(match unit {
+            "J/m^2" => Self::from_j_m2(value as f32),
+            "MJ/m^2" => Self::from_mj_m2(value as f32),
+            _ => unreachable!("unit {unit} is not in the list"),
+        })
+    }
+}
@@ -1,10 +1,26 @@
+use crate::error::{CoreError, CoreResult};
+use crate::parse::parse_quantity;
+use crate::quantity::linear_quantity;
+use crate::types::area_energy_density::AreaEnergyDensity;
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::ops::Div;
+use std::str::FromStr;
+use std::time::Duration;
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
 #[repr(transparent)]
 /// Power density in W/m^2
+///
+/// Serialized and encoded as a number of W/m^2.
 pub struct AreaPowerDensity(f32);
 
+linear_quantity!(AreaPowerDensity, f32);
+
 impl AreaPowerDensity {
     pub fn from_w_m2(value: f32) -> Self {
         Self(value)
@@ -30,3 +46,26 @@ impl AreaPowerDensity {
         format!("{:.2} kW/m²", self.as_kw_m2())
     }
 }
+
+/// Average power of an energy received over a duration.
+impl Div<Duration> for AreaEnergyDensity {
+    type Output = AreaPowerDensity;
+
+    fn div(self, rhs: Duration) -> AreaPowerDensity {
+        AreaPowerDensity::from_w_m2((self.as_j_m2() as f64 / rhs.as_secs_f64()) as f32)
+    }
+}
+
+/// E.g. `"800 W/m²"` or `"0.8 kW/m2"`, see [`crate::parse`].
+impl FromStr for AreaPowerDensity {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        let (value, unit) = parse_quantity(s, &["W/m²", "kW/m²"])?;
+        Ok(match unit {
+            "W/m²" => Self::from_w_m2(value as f32),
+            "kW/m²" => Self::from_kw_m2(value as f32),
+            _ => unreachable!("unit {unit} is not in the list"),
+        })
+    }
+}
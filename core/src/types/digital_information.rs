@@ -1,8 +1,21 @@
+use crate::error::{CoreError, CoreResult};
+use crate::parse::parse_quantity;
+use crate::quantity::{Mean, Quantity};
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+use std::str::FromStr;
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
 #[repr(transparent)]
 /// Digital information in bytes
+///
+/// Serialized and encoded as a number of bytes. Arithmetic saturates instead of overflowing.
 pub struct DigitalInformation(usize);
 
 impl DigitalInformation {
@@ -60,3 +73,133 @@ impl DigitalInformation {
         }
     }
 }
+
+impl Quantity for DigitalInformation {
+    type Value = usize;
+
+    fn from_base(value: usize) -> Self {
+        Self(value)
+    }
+
+    fn base(self) -> usize {
+        self.0
+    }
+}
+
+impl Add for DigitalInformation {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl Sub for DigitalInformation {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl Mul<usize> for DigitalInformation {
+    type Output = Self;
+
+    fn mul(self, rhs: usize) -> Self {
+        Self(self.0.saturating_mul(rhs))
+    }
+}
+
+impl Mul<DigitalInformation> for usize {
+    type Output = DigitalInformation;
+
+    fn mul(self, rhs: DigitalInformation) -> DigitalInformation {
+        rhs * self
+    }
+}
+
+/// Rounds down, panics when dividing by zero like `usize`.
+impl Div<usize> for DigitalInformation {
+    type Output = Self;
+
+    fn div(self, rhs: usize) -> Self {
+        Self(self.0 / rhs)
+    }
+}
+
+/// Rounds down, panics when dividing by zero like `usize`.
+impl Div for DigitalInformation {
+    type Output = usize;
+
+    fn div(self, rhs: Self) -> usize {
+        self.0 / rhs.0
+    }
+}
+
+impl AddAssign for DigitalInformation {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for DigitalInformation {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Sum for DigitalInformation {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self(0), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a DigitalInformation> for DigitalInformation {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+/// Rounds down to whole bytes.
+impl Mean for DigitalInformation {
+    fn mean<I: IntoIterator<Item = Self>>(values: I) -> Option<Self> {
+        let (sum, count) = values
+            .into_iter()
+            .fold((0u128, 0u128), |(sum, count), value| {
+                (sum + value.0 as u128, count + 1)
+            });
+        (count > 0).then(|| Self((sum / count) as usize))
+    }
+}
+
+/// E.g. `"512 B"`, `"16 MB"` or `"16 MiB"`, rounded to whole bytes, see [`crate::parse`].
+///
+/// `kB`, `MB`, `GB` and `TB` are decimal SI units, powers of 1000 like the ones of
+/// [`DigitalInformation::format_pretty`]. `KiB`, `MiB`, `GiB` and `TiB` are binary IEC units,
+/// powers of 1024.
+impl FromStr for DigitalInformation {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        let (value, unit) = parse_quantity(
+            s,
+            &["B", "kB", "MB", "GB", "TB", "KiB", "MiB", "GiB", "TiB"],
+        )?;
+        let factor = match unit {
+            "B" => 1.0,
+            "kB" => 1e3,
+            "MB" => 1e6,
+            "GB" => 1e9,
+            "TB" => 1e12,
+            "KiB" => 1024.0,
+            "MiB" => 1024.0 * 1024.0,
+            "GiB" => 1024.0 * 1024.0 * 1024.0,
+            "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+            _ => unreachable!("unit {unit} is not in the list"),
+        };
+        if value < 0.0 {
+            return Err(CoreError::NegativeQuantity(s.to_string()));
+        }
+        Ok(Self((value * factor).round() as usize))
+    }
+}
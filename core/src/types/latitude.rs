@@ -1,15 +1,98 @@
+use crate::error::{CoreError, CoreResult};
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "f32", into = "f32")
+)]
+#[cfg_attr(feature = "bincode", derive(Encode))]
 #[repr(transparent)]
+/// Latitude in degrees, -90 (south pole) to 90 (north pole)
+///
+/// Serialized and encoded as its degrees, values out of range are rejected when deserializing
+/// and decoding.
 pub struct Latitude(f32);
 
 impl Latitude {
+    pub const MIN: f32 = -90.0;
+    pub const MAX: f32 = 90.0;
+
+    /// Does not check the range, use [`Latitude::try_new`] for untrusted input.
     pub fn new(value: f32) -> Self {
         Self(value)
     }
 
+    pub fn try_new(value: f32) -> CoreResult<Self> {
+        if (Self::MIN..=Self::MAX).contains(&value) {
+            Ok(Self(value))
+        } else {
+            Err(CoreError::LatitudeOutOfRange(value))
+        }
+    }
+
     pub fn value(&self) -> f32 {
         self.0
     }
 }
+
+impl TryFrom<f32> for Latitude {
+    type Error = CoreError;
+
+    fn try_from(value: f32) -> Result<Self, Self::Error> {
+        Self::try_new(value)
+    }
+}
+
+impl From<Latitude> for f32 {
+    fn from(value: Latitude) -> Self {
+        value.0
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<Context> Decode<Context> for Latitude {
+    fn decode<D: bincode::de::Decoder<Context = Context>>(
+        decoder: &mut D,
+    ) -> Result<Self, bincode::error::DecodeError> {
+        Self::try_new(f32::decode(decoder)?)
+            .map_err(|e| bincode::error::DecodeError::OtherString(e.to_string()))
+    }
+}
+
+#[cfg(feature = "bincode")]
+bincode::impl_borrow_decode!(Latitude);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_the_poles() {
+        assert_eq!(Latitude::try_new(-90.0).unwrap().value(), -90.0);
+        assert_eq!(Latitude::try_new(90.0).unwrap().value(), 90.0);
+    }
+
+    #[test]
+    fn rejects_values_beyond_the_poles_and_nan() {
+        for value in [
+            (-90.0f32).next_down(),
+            90.0f32.next_up(),
+            f32::NEG_INFINITY,
+            f32::INFINITY,
+            f32::NAN,
+        ] {
+            assert!(
+                matches!(
+                    Latitude::try_new(value),
+                    Err(CoreError::LatitudeOutOfRange(_))
+                ),
+                "{value}"
+            );
+        }
+    }
+}
@@ -1,10 +1,26 @@
+use crate::error::{CoreError, CoreResult};
+use crate::parse::parse_quantity;
+use crate::quantity::linear_quantity;
+use crate::types::speed::Speed;
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::ops::Mul;
+use std::str::FromStr;
+use std::time::Duration;
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
 #[repr(transparent)]
 /// Length in meters
+///
+/// Serialized and encoded as a number of meters.
 pub struct Length(f64);
 
+linear_quantity!(Length, f64);
+
 impl Length {
     pub fn from_millimeters(value: f64) -> Self {
         Self(value / 1000.0)
@@ -18,6 +34,14 @@ impl Length {
         Self(value)
     }
 
+    pub fn from_inches(value: f64) -> Self {
+        Self(value * 0.0254)
+    }
+
+    pub fn from_miles(value: f64) -> Self {
+        Self(value * 1609.344)
+    }
+
     pub fn as_millimeters(&self) -> f64 {
         self.0 * 1000.0
     }
@@ -30,6 +54,14 @@ impl Length {
         self.0
     }
 
+    pub fn as_inches(&self) -> f64 {
+        self.0 / 0.0254
+    }
+
+    pub fn as_miles(&self) -> f64 {
+        self.0 / 1609.344
+    }
+
     pub fn format_millimeters(&self) -> String {
         format!("{:.2} mm", self.as_millimeters())
     }
@@ -41,4 +73,46 @@ impl Length {
     pub fn format_meters(&self) -> String {
         format!("{:.2} m", self.as_meters())
     }
+
+    pub fn format_inches(&self) -> String {
+        format!("{:.2} in", self.as_inches())
+    }
+
+    pub fn format_miles(&self) -> String {
+        format!("{:.2} mi", self.as_miles())
+    }
+}
+
+/// Distance covered at a constant speed.
+impl Mul<Duration> for Speed {
+    type Output = Length;
+
+    fn mul(self, rhs: Duration) -> Length {
+        Length::from_meters(self.as_m_s() as f64 * rhs.as_secs_f64())
+    }
+}
+
+impl Mul<Speed> for Duration {
+    type Output = Length;
+
+    fn mul(self, rhs: Speed) -> Length {
+        rhs * self
+    }
+}
+
+/// E.g. `"2 in"`, `"12.5 mm"` or `"10 m"`, see [`crate::parse`].
+impl FromStr for Length {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        let (value, unit) = parse_quantity(s, &["mm", "cm", "m", "in", "mi"])?;
+        Ok(match unit {
+            "mm" => Self::from_millimeters(value),
+            "cm" => Self::from_centimeters(value),
+            "m" => Self::from_meters(value),
+            "in" => Self::from_inches(value),
+            "mi" => Self::from_miles(value),
+            _ => unreachable!("unit {unit} is not in the list"),
+        })
+    }
 }
@@ -1,15 +1,106 @@
+use crate::error::{CoreError, CoreResult};
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "f32", into = "f32")
+)]
+#[cfg_attr(feature = "bincode", derive(Encode))]
 #[repr(transparent)]
+/// Longitude in degrees, -180 (west) to 180 (east)
+///
+/// Serialized and encoded as its degrees, values out of range are rejected when deserializing
+/// and decoding.
 pub struct Longitude(f32);
 
 impl Longitude {
+    pub const MIN: f32 = -180.0;
+    pub const MAX: f32 = 180.0;
+
+    /// Does not check the range, use [`Longitude::try_new`] for untrusted input.
     pub fn new(value: f32) -> Self {
         Self(value)
     }
 
+    pub fn try_new(value: f32) -> CoreResult<Self> {
+        if (Self::MIN..=Self::MAX).contains(&value) {
+            Ok(Self(value))
+        } else {
+            Err(CoreError::LongitudeOutOfRange(value))
+        }
+    }
+
+    /// Wraps any finite value around the antimeridian into -180..180, e.g. 190 becomes -170.
+    pub fn try_new_wrapping(value: f32) -> CoreResult<Self> {
+        if !value.is_finite() {
+            return Err(CoreError::LongitudeOutOfRange(value));
+        }
+        Ok(Self((value - Self::MIN).rem_euclid(360.0) + Self::MIN))
+    }
+
     pub fn value(&self) -> f32 {
         self.0
     }
 }
+
+impl TryFrom<f32> for Longitude {
+    type Error = CoreError;
+
+    fn try_from(value: f32) -> Result<Self, Self::Error> {
+        Self::try_new(value)
+    }
+}
+
+impl From<Longitude> for f32 {
+    fn from(value: Longitude) -> Self {
+        value.0
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<Context> Decode<Context> for Longitude {
+    fn decode<D: bincode::de::Decoder<Context = Context>>(
+        decoder: &mut D,
+    ) -> Result<Self, bincode::error::DecodeError> {
+        Self::try_new(f32::decode(decoder)?)
+            .map_err(|e| bincode::error::DecodeError::OtherString(e.to_string()))
+    }
+}
+
+#[cfg(feature = "bincode")]
+bincode::impl_borrow_decode!(Longitude);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_the_antimeridian() {
+        assert_eq!(Longitude::try_new(-180.0).unwrap().value(), -180.0);
+        assert_eq!(Longitude::try_new(180.0).unwrap().value(), 180.0);
+    }
+
+    #[test]
+    fn rejects_values_beyond_the_antimeridian_and_nan() {
+        for value in [
+            (-180.0f32).next_down(),
+            180.0f32.next_up(),
+            f32::NEG_INFINITY,
+            f32::INFINITY,
+            f32::NAN,
+        ] {
+            assert!(
+                matches!(
+                    Longitude::try_new(value),
+                    Err(CoreError::LongitudeOutOfRange(_))
+                ),
+                "{value}"
+            );
+        }
+    }
+}
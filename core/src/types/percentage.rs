@@ -1,10 +1,23 @@
+use crate::error::{CoreError, CoreResult};
+use crate::parse::parse_quantity;
+use crate::quantity::linear_quantity;
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
 #[repr(transparent)]
 /// Percentage in 0.0-1.0 range
+///
+/// Serialized and encoded as the 0.0-1.0 fraction.
 pub struct Percentage(f32);
 
+linear_quantity!(Percentage, f32);
+
 impl Percentage {
     pub fn from_0_1(value: f32) -> Self {
         Self(value)
@@ -22,3 +35,16 @@ impl Percentage {
         self.0 * 100.0
     }
 }
+
+/// E.g. `"25%"`, see [`crate::parse`].
+impl FromStr for Percentage {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        let (value, unit) = parse_quantity(s, &["%"])?;
+        Ok(match unit {
+            "%" => Self::from_0_100(value as f32),
+            _ => unreachable!("unit {unit} is not in the list"),
+        })
+    }
+}
@@ -1,10 +1,23 @@
+use crate::error::{CoreError, CoreResult};
+use crate::parse::parse_quantity;
+use crate::quantity::linear_quantity;
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
 #[repr(transparent)]
 /// Pressure in hPa
+///
+/// Serialized and encoded as a number of hPa.
 pub struct Pressure(f32);
 
+linear_quantity!(Pressure, f32);
+
 impl Pressure {
     pub fn from_hpa(value: f32) -> Self {
         Self(value)
@@ -18,6 +31,10 @@ impl Pressure {
         Self(value * 1000.0)
     }
 
+    pub fn from_inhg(value: f32) -> Self {
+        Self(value * 33.863_89)
+    }
+
     pub fn as_hpa(&self) -> f32 {
         self.0
     }
@@ -30,6 +47,10 @@ impl Pressure {
         self.0 / 1000.0
     }
 
+    pub fn as_inhg(&self) -> f32 {
+        self.0 / 33.863_89
+    }
+
     pub fn format_hpa(&self) -> String {
         format!("{:.2} hPa", self.as_hpa())
     }
@@ -41,4 +62,24 @@ impl Pressure {
     pub fn format_bar(&self) -> String {
         format!("{:.2} bar", self.as_bar())
     }
+
+    pub fn format_inhg(&self) -> String {
+        format!("{:.2} inHg", self.as_inhg())
+    }
+}
+
+/// E.g. `"1013 hPa"` or `"29.92 inHg"`, see [`crate::parse`].
+impl FromStr for Pressure {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        let (value, unit) = parse_quantity(s, &["hPa", "kPa", "bar", "inHg"])?;
+        Ok(match unit {
+            "hPa" => Self::from_hpa(value as f32),
+            "kPa" => Self::from_kpa(value as f32),
+            "bar" => Self::from_bar(value as f32),
+            "inHg" => Self::from_inhg(value as f32),
+            _ => unreachable!("unit {unit} is not in the list"),
+        })
+    }
 }
@@ -1,10 +1,26 @@
+use crate::error::{CoreError, CoreResult};
+use crate::parse::parse_quantity;
+use crate::quantity::linear_quantity;
+use crate::types::length::Length;
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::ops::Div;
+use std::str::FromStr;
+use std::time::Duration;
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
 #[repr(transparent)]
 /// Speed in m/s
+///
+/// Serialized and encoded as a number of m/s.
 pub struct Speed(f32);
 
+linear_quantity!(Speed, f32);
+
 impl Speed {
     pub fn from_m_s(value: f32) -> Self {
         Self(value)
@@ -14,6 +30,10 @@ impl Speed {
         Self(value / 3.6)
     }
 
+    pub fn from_mph(value: f32) -> Self {
+        Self(value * 0.44704)
+    }
+
     pub fn as_m_s(&self) -> f32 {
         self.0
     }
@@ -22,6 +42,10 @@ impl Speed {
         self.0 * 3.6
     }
 
+    pub fn as_mph(&self) -> f32 {
+        self.0 / 0.44704
+    }
+
     pub fn format_m_s(&self) -> String {
         format!("{:.2} m/s", self.as_m_s())
     }
@@ -29,4 +53,32 @@ impl Speed {
     pub fn format_km_h(&self) -> String {
         format!("{:.2} km/h", self.as_km_h())
     }
+
+    pub fn format_mph(&self) -> String {
+        format!("{:.2} mph", self.as_mph())
+    }
+}
+
+/// Average speed covering a distance.
+impl Div<Duration> for Length {
+    type Output = Speed;
+
+    fn div(self, rhs: Duration) -> Speed {
+        Speed::from_m_s((self.as_meters() / rhs.as_secs_f64()) as f32)
+    }
+}
+
+/// E.g. `"3.5 m/s"`, `"12 km/h"` or `"20 mph"`, see [`crate::parse`].
+impl FromStr for Speed {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        let (value, unit) = parse_quantity(s, &["m/s", "km/h", "mph"])?;
+        Ok(match unit {
+            "m/s" => Self::from_m_s(value as f32),
+            "km/h" => Self::from_km_h(value as f32),
+            "mph" => Self::from_mph(value as f32),
+            _ => unreachable!("unit {unit} is not in the list"),
+        })
+    }
 }
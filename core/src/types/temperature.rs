@@ -1,6 +1,25 @@
-#[derive(Debug, Copy, Clone)]
+use crate::error::{CoreError, CoreResult};
+use crate::parse::parse_quantity;
+use crate::quantity::Mean;
+use crate::types::temperature_difference::TemperatureDifference;
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
 #[repr(transparent)]
 /// Temperature in Kelvin
+///
+/// Temperatures are absolute, they can't be added or scaled. Subtracting two of them gives a
+/// [`TemperatureDifference`], which can be added to or subtracted from a temperature.
+///
+/// Serialized and encoded as a number of Kelvin.
 pub struct Temperature(f32);
 
 impl Temperature {
@@ -39,4 +58,79 @@ impl Temperature {
     pub fn format_fahrenheit(&self) -> String {
         format!("{:.2} °F", self.as_fahrenheit())
     }
+
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        Self(self.0.min(other.0))
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        Self(self.0.max(other.0))
+    }
+}
+
+impl Sub for Temperature {
+    type Output = TemperatureDifference;
+
+    fn sub(self, rhs: Self) -> TemperatureDifference {
+        TemperatureDifference::from_kelvin(self.0 - rhs.0)
+    }
+}
+
+impl Add<TemperatureDifference> for Temperature {
+    type Output = Self;
+
+    fn add(self, rhs: TemperatureDifference) -> Self {
+        Self(self.0 + rhs.as_kelvin())
+    }
+}
+
+impl Sub<TemperatureDifference> for Temperature {
+    type Output = Self;
+
+    fn sub(self, rhs: TemperatureDifference) -> Self {
+        Self(self.0 - rhs.as_kelvin())
+    }
+}
+
+impl AddAssign<TemperatureDifference> for Temperature {
+    fn add_assign(&mut self, rhs: TemperatureDifference) {
+        self.0 += rhs.as_kelvin();
+    }
+}
+
+impl SubAssign<TemperatureDifference> for Temperature {
+    fn sub_assign(&mut self, rhs: TemperatureDifference) {
+        self.0 -= rhs.as_kelvin();
+    }
+}
+
+/// The mean is the same in every scale, averaging Kelvin avoids the offsets of the others.
+impl Mean for Temperature {
+    fn mean<I: IntoIterator<Item = Self>>(values: I) -> Option<Self> {
+        let (sum, count) = values
+            .into_iter()
+            .fold((0.0f64, 0usize), |(sum, count), temperature| {
+                (sum + temperature.0 as f64, count + 1)
+            });
+        (count > 0).then(|| Self((sum / count as f64) as f32))
+    }
+}
+
+/// E.g. `"5 °C"`, `"41F"` or `"278.15 K"`, see [`crate::parse`].
+impl FromStr for Temperature {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        let (value, unit) = parse_quantity(s, &["K", "°C", "°F"])?;
+        Ok(match unit {
+            "K" => Self::from_kelvin(value as f32),
+            "°C" => Self::from_celsius(value as f32),
+            "°F" => Self::from_fahrenheit(value as f32),
+            _ => unreachable!("unit {unit} is not in the list"),
+        })
+    }
 }
@@ -0,0 +1,73 @@
+use crate::error::{CoreError, CoreResult};
+use crate::parse::parse_quantity;
+use crate::quantity::linear_quantity;
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
+#[repr(transparent)]
+/// Difference between two temperatures in Kelvin, see
+/// [`Temperature`](crate::types::temperature::Temperature)
+///
+/// Serialized and encoded as a number of Kelvin.
+pub struct TemperatureDifference(f32);
+
+linear_quantity!(TemperatureDifference, f32);
+
+impl TemperatureDifference {
+    pub fn from_kelvin(value: f32) -> Self {
+        Self(value)
+    }
+
+    pub fn from_celsius(value: f32) -> Self {
+        Self(value)
+    }
+
+    pub fn from_fahrenheit(value: f32) -> Self {
+        Self(value * 5.0 / 9.0)
+    }
+
+    pub fn as_kelvin(&self) -> f32 {
+        self.0
+    }
+
+    pub fn as_celsius(&self) -> f32 {
+        self.0
+    }
+
+    pub fn as_fahrenheit(&self) -> f32 {
+        self.0 * 9.0 / 5.0
+    }
+
+    pub fn format_kelvin(&self) -> String {
+        format!("{:.2} K", self.as_kelvin())
+    }
+
+    pub fn format_celsius(&self) -> String {
+        format!("{:.2} °C", self.as_celsius())
+    }
+
+    pub fn format_fahrenheit(&self) -> String {
+        format!("{:.2} °F", self.as_fahrenheit())
+    }
+}
+
+/// E.g. `"3 °C"` or `"5.4 °F"`, see [`crate::parse`].
+impl FromStr for TemperatureDifference {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        let (value, unit) = parse_quantity(s, &["K", "°C", "°F"])?;
+        Ok(match unit {
+            "K" => Self::from_kelvin(value as f32),
+            "°C" => Self::from_celsius(value as f32),
+            "°F" => Self::from_fahrenheit(value as f32),
+            _ => unreachable!("unit {unit} is not in the list"),
+        })
+    }
+}
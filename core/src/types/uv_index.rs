@@ -1,12 +1,42 @@
+use crate::error::{CoreError, CoreResult};
+use crate::quantity::linear_quantity;
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
 #[repr(transparent)]
 /// https://www.who.int/news-room/questions-and-answers/item/radiation-the-ultraviolet-(uv)-index
+///
+/// Serialized and encoded as the index.
 pub struct UVIndex(f32);
 
+linear_quantity!(UVIndex, f32);
+
 impl UVIndex {
     pub fn new(value: f32) -> Self {
         Self(value)
     }
+
+    pub fn value(&self) -> f32 {
+        self.0
+    }
+}
+
+/// The plain index, e.g. `"6.5"`.
+impl FromStr for UVIndex {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        s.trim()
+            .parse()
+            .ok()
+            .filter(|value: &f32| value.is_finite())
+            .map(Self)
+            .ok_or_else(|| CoreError::InvalidQuantity(s.to_string()))
+    }
 }
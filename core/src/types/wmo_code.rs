@@ -1,7 +1,20 @@
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// Sources:
 /// - https://www.nodc.noaa.gov/archive/arc0021/0002199/1.1/data/0-data/HTML/WMO-CODE/WMO4677.HTM
 /// - https://gist.github.com/stellasphere/9490c195ed2b53c707087c8c2db4ec0c
+///
+/// Serialized and encoded as its numeric code, codes without a variant become
+/// [`WMOCode::Unknown`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(from = "u8", into = "u8")
+)]
 pub enum WMOCode {
     Clear = 0,
     MainlyClear = 1,
@@ -34,6 +47,42 @@ pub enum WMOCode {
     Unknown = 100,
 }
 
+impl WMOCode {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Clear => "Clear sky",
+            Self::MainlyClear => "Mainly clear",
+            Self::PartlyCloudy => "Partly cloudy",
+            Self::Cloudy => "Overcast",
+            Self::Foggy => "Fog",
+            Self::RimeFog => "Depositing rime fog",
+            Self::LightDrizzle => "Light drizzle",
+            Self::Drizzle => "Drizzle",
+            Self::HeavyDrizzle => "Dense drizzle",
+            Self::LightFreezingDrizzle => "Light freezing drizzle",
+            Self::FreezingDrizzle => "Dense freezing drizzle",
+            Self::LightRain => "Slight rain",
+            Self::Rain => "Rain",
+            Self::HeavyRain => "Heavy rain",
+            Self::LightFreezingRain => "Light freezing rain",
+            Self::FreezingRain => "Heavy freezing rain",
+            Self::LightSnow => "Slight snowfall",
+            Self::Snow => "Snowfall",
+            Self::HeavySnow => "Heavy snowfall",
+            Self::SnowGrains => "Snow grains",
+            Self::LightShowers => "Slight rain showers",
+            Self::Showers => "Rain showers",
+            Self::HeavyShowers => "Violent rain showers",
+            Self::LightSnowShowers => "Slight snow showers",
+            Self::SnowShowers => "Heavy snow showers",
+            Self::Thunderstorm => "Thunderstorm",
+            Self::LightThunderstormHail => "Thunderstorm with slight hail",
+            Self::ThunderstormHail => "Thunderstorm with heavy hail",
+            Self::Unknown => "Unknown",
+        }
+    }
+}
+
 impl From<u8> for WMOCode {
     fn from(value: u8) -> Self {
         match value {
@@ -105,3 +154,25 @@ impl From<WMOCode> for u8 {
         }
     }
 }
+
+#[cfg(feature = "bincode")]
+impl Encode for WMOCode {
+    fn encode<E: bincode::enc::Encoder>(
+        &self,
+        encoder: &mut E,
+    ) -> Result<(), bincode::error::EncodeError> {
+        u8::from(*self).encode(encoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<Context> Decode<Context> for WMOCode {
+    fn decode<D: bincode::de::Decoder<Context = Context>>(
+        decoder: &mut D,
+    ) -> Result<Self, bincode::error::DecodeError> {
+        Ok(Self::from(u8::decode(decoder)?))
+    }
+}
+
+#[cfg(feature = "bincode")]
+bincode::impl_borrow_decode!(WMOCode);
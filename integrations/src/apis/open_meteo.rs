@@ -31,7 +31,29 @@ impl OpenMeteoApi {
         latitude: Latitude,
         longitude: Longitude,
     ) -> IntegrationResult<Vec<hourly_forecast::OpenMeteoHourly>> {
-        let request = self.client.request("https://api.open-meteo.com/v1/forecast?hourly=temperature_2m,relative_humidity_2m,dew_point_2m,apparent_temperature,precipitation_probability,precipitation,rain,showers,snowfall,snow_depth,weather_code,surface_pressure,cloud_cover,cloud_cover_low,cloud_cover_mid,cloud_cover_high,visibility,wind_speed_10m,wind_speed_80m,wind_speed_120m,wind_speed_180m,wind_direction_10m,wind_direction_80m,wind_direction_120m,wind_direction_180m,wind_gusts_10m,shortwave_radiation&forecast_days=7")?
+        self.hourly(latitude, longitude, 0, 7).await
+    }
+
+    /// Hourly data of the past days up until the end of today, Open-Meteo serves at most 92 past days.
+    pub async fn hourly_history(
+        &self,
+        latitude: Latitude,
+        longitude: Longitude,
+        past_days: u8,
+    ) -> IntegrationResult<Vec<hourly_forecast::OpenMeteoHourly>> {
+        self.hourly(latitude, longitude, past_days.min(92), 1).await
+    }
+
+    async fn hourly(
+        &self,
+        latitude: Latitude,
+        longitude: Longitude,
+        past_days: u8,
+        forecast_days: u8,
+    ) -> IntegrationResult<Vec<hourly_forecast::OpenMeteoHourly>> {
+        let request = self.client.request("https://api.open-meteo.com/v1/forecast?hourly=temperature_2m,relative_humidity_2m,dew_point_2m,apparent_temperature,precipitation_probability,precipitation,rain,showers,snowfall,snow_depth,weather_code,surface_pressure,cloud_cover,cloud_cover_low,cloud_cover_mid,cloud_cover_high,visibility,wind_speed_10m,wind_speed_80m,wind_speed_120m,wind_speed_180m,wind_direction_10m,wind_direction_80m,wind_direction_120m,wind_direction_180m,wind_gusts_10m,shortwave_radiation")?
+            .query("past_days", past_days.to_string())
+            .query("forecast_days", forecast_days.to_string())
             .query("latitude", latitude.value().to_string())
             .query("longitude", longitude.value().to_string())
             .query("timezone", "auto");
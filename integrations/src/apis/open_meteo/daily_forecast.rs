@@ -19,7 +19,9 @@ use std::time::Duration;
 /// Source: https://open-meteo.com/en/docs
 #[derive(Debug)]
 pub struct OpenMeteoDaily {
+    /// Local date of the location
     pub time: NaiveDate,
+    pub timezone: Tz,
     pub latitude: Latitude,
     pub longitude: Longitude,
     pub elevation: Length,
@@ -92,6 +94,7 @@ impl DailyForecastModel {
 
             let forecast = OpenMeteoDaily {
                 time,
+                timezone,
                 latitude,
                 longitude,
                 elevation,
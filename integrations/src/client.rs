@@ -5,7 +5,7 @@ use reqwest_retry::policies::ExponentialBackoff;
 use reqwest_retry::RetryTransientMiddleware;
 use std::time::Duration;
 
-mod request;
+pub mod request;
 
 pub struct ApiClient {
     client: ClientWithMiddleware,
@@ -13,15 +13,43 @@ pub struct ApiClient {
 
 impl ApiClient {
     pub fn new(tokens: usize, refill: usize, interval: Duration) -> Self {
+        Self::new_with_retries(tokens, refill, interval, 3)
+    }
+
+    /// Transient failures like timeouts or 5xx responses are retried `max_retries` times.
+    pub fn new_with_retries(
+        tokens: usize,
+        refill: usize,
+        interval: Duration,
+        max_retries: u32,
+    ) -> Self {
+        Self::with_client(
+            reqwest::Client::new(),
+            tokens,
+            refill,
+            interval,
+            max_retries,
+        )
+    }
+
+    /// Like [`ApiClient::new_with_retries`], sending through a configured client, e.g. one with
+    /// timeouts.
+    pub fn with_client(
+        http: reqwest::Client,
+        tokens: usize,
+        refill: usize,
+        interval: Duration,
+        max_retries: u32,
+    ) -> Self {
         let limiter = RateLimiter::builder()
             .max(tokens)
             .initial(tokens)
             .refill(refill)
             .interval(interval)
             .build();
-        let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+        let retry_policy = ExponentialBackoff::builder().build_with_max_retries(max_retries);
 
-        let client = ClientBuilder::new(reqwest::Client::new())
+        let client = ClientBuilder::new(http)
             .with(reqwest_leaky_bucket::rate_limit_all(limiter))
             .with(RetryTransientMiddleware::new_with_policy(retry_policy))
             .build();
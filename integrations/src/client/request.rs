@@ -44,6 +44,18 @@ impl<'a> RequestBuilder<'a> {
         self
     }
 
+    /// Sends `body` as is, any status other than 2xx is an error.
+    pub async fn post(self, body: impl Into<reqwest::Body>) -> IntegrationResult<()> {
+        self.client
+            .post(self.url)
+            .headers(self.headers)
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
     pub async fn get_json<T: DeserializeOwned>(self) -> IntegrationResult<T> {
         Ok(self
             .client
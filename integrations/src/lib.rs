@@ -1,3 +1,3 @@
 pub mod apis;
-mod client;
+pub mod client;
 pub mod error;
@@ -0,0 +1,130 @@
+//! Encoding and decoding frames with each codec, on responses as the server sends them.
+//!
+//! Run with `cargo bench -p omnistat-net`, the size of every frame is printed before measuring.
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use omnistat_net::Message;
+use omnistat_net::compression::Compression;
+use omnistat_net::handshake::Codec;
+use omnistat_net::protocol::HourlyWeather;
+use omnistat_net::rpc::{ServerFrame, encode_payload};
+use std::hint::black_box;
+
+const COMPRESSIONS: [(&str, Compression); 7] = [
+    ("none", Compression::NONE),
+    ("zstd-1", zstd(Codec::Zstd, 1)),
+    ("zstd-3", zstd(Codec::Zstd, 3)),
+    ("zstd-9", zstd(Codec::Zstd, 9)),
+    ("zstd-19", zstd(Codec::Zstd, 19)),
+    ("zstd-dictionary-3", zstd(Codec::ZstdDictionary, 3)),
+    ("lz4", zstd(Codec::Lz4, 0)),
+];
+
+/// Compresses every frame, the threshold would skip the small ones.
+const fn zstd(codec: Codec, level: u8) -> Compression {
+    Compression {
+        codec,
+        level,
+        threshold: 0,
+    }
+}
+
+/// Plausible weather, temperatures follow the sun and measurements are rounded like the upstream
+/// API rounds them.
+fn hours(count: usize) -> Vec<HourlyWeather> {
+    let mut seed = 0x2545_f491_u32;
+    let mut noise = move || {
+        // xorshift, deterministic so that runs compare
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed as f32 / u32::MAX as f32
+    };
+    let round = |value: f32| (value * 10.0).round() / 10.0;
+
+    (0..count)
+        .map(|hour| {
+            let daytime = ((hour % 24) as f32 / 24.0 * std::f32::consts::TAU).sin();
+            HourlyWeather {
+                time: 1_760_000_400 + hour as i64 * 3600,
+                wmo_code: [0, 1, 2, 3, 61][hour % 5],
+                temperature: round(12.0 + 6.0 * daytime + noise()),
+                apparent_temperature: round(10.5 + 6.5 * daytime + noise()),
+                relative_humidity: ((0.7 - 0.2 * daytime + noise() * 0.1) * 100.0).round() / 100.0,
+                dew_point: round(6.3 + 0.4 * daytime + noise()),
+                surface_pressure: round(1013.2 - (hour % 7) as f32 * 0.3 + noise()),
+                cloud_cover: (hour % 4) as f32 * 0.25,
+                cloud_cover_low: (hour % 3) as f32 * 0.2,
+                cloud_cover_mid: 0.1,
+                cloud_cover_high: 0.0,
+                wind_speed: round(8.0 + (hour % 6) as f32 + noise() * 3.0),
+                max_wind_speed: round(17.0 + (hour % 6) as f32 * 1.5 + noise() * 5.0),
+                wind_direction: (220.0 + (hour % 12) as f32 * 3.0 + noise() * 20.0).round(),
+                total_precipitation: if hour % 5 == 4 { 0.4 } else { 0.0 },
+                precipitation_probability: (hour % 5) as f32 * 0.2,
+                rain: if hour % 5 == 4 { 0.4 } else { 0.0 },
+                snowfall: 0.0,
+                snow_depth: 0.0,
+                showers: 0.0,
+                visibility: 24_140.0,
+                shortwave_radiation: (600.0 * daytime + noise() * 50.0).max(0.0).round(),
+            }
+        })
+        .collect()
+}
+
+fn payloads() -> Vec<(&'static str, ServerFrame)> {
+    let response = |id, hours: Vec<HourlyWeather>| ServerFrame::Response {
+        id,
+        result: Ok(encode_payload(&hours).unwrap()),
+    };
+    vec![
+        ("heartbeat", ServerFrame::Heartbeat),
+        ("hour", response(1, hours(1))),
+        ("day", response(2, hours(24))),
+        ("week", response(3, hours(24 * 7))),
+    ]
+}
+
+fn frame_size(message: &Message) -> usize {
+    let mut written = Vec::new();
+    futures::executor::block_on(message.write(&mut written)).unwrap();
+    written.len()
+}
+
+fn compression(c: &mut Criterion) {
+    for (payload_name, payload) in payloads() {
+        for (compression_name, compression) in COMPRESSIONS {
+            let message = Message::encode_with(&payload, &compression).unwrap();
+            println!(
+                "{payload_name}/{compression_name}: {} bytes",
+                frame_size(&message)
+            );
+        }
+    }
+
+    for (payload_name, payload) in payloads() {
+        let mut encode = c.benchmark_group(format!("encode/{payload_name}"));
+        for (compression_name, compression) in COMPRESSIONS {
+            encode.bench_function(compression_name, |b| {
+                b.iter(|| Message::encode_with(black_box(&payload), &compression).unwrap())
+            });
+        }
+        encode.finish();
+
+        let mut decode = c.benchmark_group(format!("decode/{payload_name}"));
+        for (compression_name, compression) in COMPRESSIONS {
+            decode.bench_function(compression_name, |b| {
+                b.iter_batched(
+                    || Message::encode_with(&payload, &compression).unwrap(),
+                    |message| message.decode::<ServerFrame>().unwrap(),
+                    BatchSize::SmallInput,
+                )
+            });
+        }
+        decode.finish();
+    }
+}
+
+criterion_group!(benches, compression);
+criterion_main!(benches);
@@ -0,0 +1,179 @@
+//! Integrity of frames. A frame with bit 2 of its flags set carries a CRC32C after the flags:
+//!
+//! ```text
+//! length u32 | flags u8 | checksum u32 | data [length]
+//! ```
+//!
+//! The checksum covers the length, the flags and the data, so a corrupted frame is detected
+//! before it is decompressed or decoded instead of failing there with a confusing error.
+
+use crate::error::{NetError, NetResult};
+
+pub(crate) const CHECKSUM_FLAG: u8 = 0b0000_0100;
+
+/// Bytes of the checksum following the flags.
+pub const CHECKSUM_SIZE: usize = 4;
+
+/// How frames of a connection are protected against corruption.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Checksums {
+    /// Sent frames carry a checksum
+    pub send: bool,
+    /// Received frames without a checksum are treated as corrupted
+    pub require: bool,
+    /// What happens to a received frame failing its checksum
+    pub on_mismatch: OnMismatch,
+}
+
+impl Default for Checksums {
+    fn default() -> Self {
+        Self {
+            send: true,
+            require: false,
+            on_mismatch: OnMismatch::Close,
+        }
+    }
+}
+
+impl Checksums {
+    /// Frames without checksums, received frames are still verified if they carry one.
+    pub const NONE: Checksums = Checksums {
+        send: false,
+        require: false,
+        on_mismatch: OnMismatch::Close,
+    };
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OnMismatch {
+    /// The frame fails with [`NetError::ChecksumMismatch`], which ends the connection
+    Close,
+    /// The frame is dropped and the connection carries on, the request it belonged to gets no
+    /// answer. Only safe while lengths aren't corrupted, a wrong length misaligns all frames
+    /// after it
+    Skip,
+}
+
+pub(crate) fn compute(length: u32, flags: u8, data: &[u8]) -> u32 {
+    let mut header = [0u8; 5];
+    header[..4].copy_from_slice(&length.to_be_bytes());
+    header[4] = flags;
+    crc32c::crc32c_append(crc32c::crc32c(&header), data)
+}
+
+/// Checks the checksum of a received frame, if it has one.
+pub(crate) fn verify(
+    length: u32,
+    flags: u8,
+    checksum: Option<u32>,
+    data: &[u8],
+    checksums: &Checksums,
+) -> NetResult<()> {
+    match checksum {
+        Some(expected) => {
+            let actual = compute(length, flags, data);
+            if actual != expected {
+                return Err(NetError::ChecksumMismatch { expected, actual });
+            }
+            Ok(())
+        }
+        None if checksums.require => Err(NetError::InvalidFrame(
+            "frame without the required checksum".to_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: &[u8] = b"hourly weather of a user";
+    const FLAGS: u8 = CHECKSUM_FLAG | 1;
+
+    fn flip(value: &mut [u8], bit: usize) {
+        value[bit / 8] ^= 1 << (bit % 8);
+    }
+
+    #[test]
+    fn accepts_intact_frames() {
+        let checksum = compute(DATA.len() as u32, FLAGS, DATA);
+        verify(
+            DATA.len() as u32,
+            FLAGS,
+            Some(checksum),
+            DATA,
+            &Checksums::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn detects_every_flipped_bit() {
+        let length = DATA.len() as u32;
+        let checksum = compute(length, FLAGS, DATA);
+        let checksums = Checksums::default();
+        let mismatch =
+            |result: NetResult<()>| matches!(result, Err(NetError::ChecksumMismatch { .. }));
+
+        for bit in 0..DATA.len() * 8 {
+            let mut data = DATA.to_vec();
+            flip(&mut data, bit);
+            assert!(
+                mismatch(verify(length, FLAGS, Some(checksum), &data, &checksums)),
+                "data bit {bit}"
+            );
+        }
+        for bit in 0..32 {
+            let mut bytes = length.to_be_bytes();
+            flip(&mut bytes, bit);
+            let corrupted_length = u32::from_be_bytes(bytes);
+            assert!(
+                mismatch(verify(
+                    corrupted_length,
+                    FLAGS,
+                    Some(checksum),
+                    DATA,
+                    &checksums
+                )),
+                "length bit {bit}"
+            );
+        }
+        for bit in 0..32 {
+            let mut bytes = checksum.to_be_bytes();
+            flip(&mut bytes, bit);
+            let corrupted_checksum = u32::from_be_bytes(bytes);
+            assert!(
+                mismatch(verify(
+                    length,
+                    FLAGS,
+                    Some(corrupted_checksum),
+                    DATA,
+                    &checksums
+                )),
+                "checksum bit {bit}"
+            );
+        }
+        for bit in 0..8 {
+            let flags = FLAGS ^ (1 << bit);
+            assert!(
+                mismatch(verify(length, flags, Some(checksum), DATA, &checksums)),
+                "flags bit {bit}"
+            );
+        }
+    }
+
+    #[test]
+    fn frames_without_checksum_pass_unless_required() {
+        let length = DATA.len() as u32;
+        verify(length, 1, None, DATA, &Checksums::default()).unwrap();
+        let required = Checksums {
+            require: true,
+            ..Checksums::default()
+        };
+        assert!(matches!(
+            verify(length, 1, None, DATA, &required),
+            Err(NetError::InvalidFrame(_))
+        ));
+    }
+}
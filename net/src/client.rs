@@ -0,0 +1,686 @@
+use crate::checksum::Checksums;
+use crate::codec::TypedCodec;
+use crate::compression::Compression;
+use crate::error::{NetError, NetResult};
+use crate::handshake::{self, Codec};
+use crate::limits::FrameLimits;
+use crate::protocol::{
+    DataKind, GetHourlyWeather, GetHourlyWeatherChunks, HourlyWeather, HourlyWeatherChunk,
+    ListUsers, Subscribe, SubscriptionUpdate,
+};
+use crate::rpc::{
+    ClientFrame, HEARTBEAT_INTERVAL, Method, RequestFrame, RpcError, ServerFrame, decode_payload,
+    encode_payload,
+};
+use crate::tls::ClientTls;
+use futures::{SinkExt, Stream, StreamExt};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadHalf, WriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, Sleep};
+use tokio_util::codec::{FramedRead, FramedWrite};
+use tracing::{debug, warn};
+
+/// First wait before a subscription tries to reconnect, doubled after every failed attempt.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+pub struct ClientOptions {
+    /// Required by servers that authenticate clients, either the token of the server or of a user
+    pub token: Option<String>,
+    /// Connects through TLS, for servers that require it
+    pub tls: Option<ClientTls>,
+    /// Limits for frames received from the server
+    pub limits: FrameLimits,
+    /// Codec offered to the server along with [`Codec::None`], and how requests are compressed
+    /// if the server picks it
+    pub compression: Compression,
+    /// Checksums of requests and how corrupted responses are dealt with
+    pub checksums: Checksums,
+    /// How long a request may take until [`NetError::Timeout`],
+    /// streams only wait for their first item
+    pub timeout: Duration,
+    /// Responses buffered per stream, a stream whose consumer falls further behind is closed
+    /// with [`NetError::StreamLagged`]
+    pub stream_capacity: usize,
+    /// Connections the server didn't send anything on for this long are considered dead,
+    /// servers send heartbeats every [`HEARTBEAT_INTERVAL`]
+    pub idle_timeout: Duration,
+    /// Longest wait between attempts of subscriptions to reconnect
+    pub max_reconnect_delay: Duration,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            token: None,
+            tls: None,
+            limits: FrameLimits::default(),
+            compression: Compression::default(),
+            checksums: Checksums::default(),
+            timeout: Duration::from_secs(30),
+            stream_capacity: 64,
+            idle_timeout: HEARTBEAT_INTERVAL * 3,
+            max_reconnect_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+enum Pending {
+    Single(oneshot::Sender<Result<Vec<u8>, RpcError>>),
+    Stream {
+        sender: mpsc::Sender<Result<Vec<u8>, RpcError>>,
+        /// Set when the stream is dropped for falling behind
+        lagged: Arc<AtomicBool>,
+    },
+}
+
+#[derive(Clone, Default)]
+struct PendingRequests(Arc<Mutex<HashMap<u64, Pending>>>);
+
+impl PendingRequests {
+    fn lock(&self) -> MutexGuard<'_, HashMap<u64, Pending>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A connection to an omnistat server, cheap to clone and usable from many tasks at once.
+///
+/// Requests after the connection was lost open a new one, [`Subscription`]s resubscribe on it.
+#[derive(Clone)]
+pub struct Client {
+    inner: Arc<Shared>,
+}
+
+struct Shared {
+    address: String,
+    options: ClientOptions,
+    connection: tokio::sync::Mutex<Arc<Connection>>,
+}
+
+type ClientCodec = TypedCodec<ServerFrame, ClientFrame>;
+
+type Writer = Arc<tokio::sync::Mutex<FramedWrite<WriteHalf<Box<dyn Transport>>, ClientCodec>>>;
+
+/// A TCP connection, with or without TLS.
+trait Transport: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Transport for T {}
+
+struct Connection {
+    writer: Writer,
+    pending: PendingRequests,
+    next_id: AtomicU64,
+    /// Ids of requests to cancel, see [`send_cancels`]
+    cancels: mpsc::UnboundedSender<u64>,
+    reader_task: JoinHandle<()>,
+    cancel_task: JoinHandle<()>,
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.reader_task.abort();
+        self.cancel_task.abort();
+    }
+}
+
+impl Connection {
+    async fn open(address: &str, options: &ClientOptions) -> NetResult<Self> {
+        let stream = TcpStream::connect(address).await?;
+        stream.set_nodelay(true)?;
+        let mut stream: Box<dyn Transport> = match &options.tls {
+            Some(tls) => Box::new(tls.connect(stream).await?),
+            None => Box::new(stream),
+        };
+        let codecs = [options.compression.codec, Codec::None];
+        let negotiated = handshake::connect(&mut stream, options.token.as_deref(), &codecs).await?;
+        let (reader, writer) = tokio::io::split(stream);
+
+        let compression = Compression {
+            codec: negotiated.codec,
+            ..options.compression
+        };
+        let codec = ClientCodec::new(options.limits, compression, options.checksums);
+
+        let writer = Arc::new(tokio::sync::Mutex::new(FramedWrite::new(
+            writer,
+            codec.clone(),
+        )));
+        let pending = PendingRequests::default();
+        let (cancels, cancelled) = mpsc::unbounded_channel();
+        let reader_task = tokio::spawn(read_responses(
+            FramedRead::new(reader, codec),
+            pending.clone(),
+            cancels.clone(),
+            options.idle_timeout,
+        ));
+        let cancel_task = tokio::spawn(send_cancels(writer.clone(), pending.clone(), cancelled));
+        Ok(Self {
+            writer,
+            pending,
+            next_id: AtomicU64::new(1),
+            cancels,
+            reader_task,
+            cancel_task,
+        })
+    }
+
+    fn is_closed(&self) -> bool {
+        self.reader_task.is_finished()
+    }
+
+    async fn send<M: Method>(&self, request: &M, pending: Pending) -> NetResult<u64> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let frame = ClientFrame::Request(RequestFrame {
+            id,
+            method: M::NAME.to_string(),
+            payload: encode_payload(request)?,
+        });
+
+        self.pending.lock().insert(id, pending);
+        let mut writer = self.writer.lock().await;
+        if let Err(e) = writer.send(frame).await {
+            self.pending.lock().remove(&id);
+            return Err(e);
+        }
+        Ok(id)
+    }
+}
+
+impl Client {
+    /// Connects and shakes hands with the server at `host:port`.
+    pub async fn connect(address: &str, options: ClientOptions) -> NetResult<Self> {
+        let connection = Connection::open(address, &options).await?;
+        Ok(Self {
+            inner: Arc::new(Shared {
+                address: address.to_string(),
+                options,
+                connection: tokio::sync::Mutex::new(Arc::new(connection)),
+            }),
+        })
+    }
+
+    pub async fn list_users(&self) -> NetResult<Vec<String>> {
+        self.call(ListUsers).await
+    }
+
+    /// Hours of a user ordered by time, `from` is inclusive and `to` exclusive, both unix seconds.
+    pub async fn hourly_weather(
+        &self,
+        user_id: &str,
+        from: Option<i64>,
+        to: Option<i64>,
+    ) -> NetResult<Vec<HourlyWeather>> {
+        self.call(GetHourlyWeather {
+            user_id: user_id.to_string(),
+            from,
+            to,
+        })
+        .await
+    }
+
+    /// Hours of several users like [`Client::hourly_weather`], streamed in chunks so that long
+    /// ranges aren't held in memory at once on either side. The server produces chunks only as
+    /// fast as the connection takes them, consumers must keep up within
+    /// [`ClientOptions::stream_capacity`] chunks.
+    pub async fn hourly_weather_chunks(
+        &self,
+        user_ids: &[String],
+        from: Option<i64>,
+        to: Option<i64>,
+    ) -> NetResult<ResponseStream<HourlyWeatherChunk>> {
+        self.call_stream(GetHourlyWeatherChunks {
+            user_ids: user_ids.to_vec(),
+            from,
+            to,
+        })
+        .await
+    }
+
+    /// Updates of a user about the given kinds of data, the connection stays usable for other
+    /// requests. The subscription survives lost connections by resubscribing.
+    pub async fn subscribe(&self, user_id: &str, kinds: &[DataKind]) -> NetResult<Subscription> {
+        let request = Subscribe {
+            user_id: user_id.to_string(),
+            kinds: kinds.to_vec(),
+        };
+        let updates = self.open_subscription(&request).await?;
+        Ok(Subscription {
+            client: self.clone(),
+            request,
+            updates: Some(updates),
+        })
+    }
+
+    /// Sends a request and waits for its response.
+    pub async fn call<M: Method>(&self, request: M) -> NetResult<M::Output> {
+        let connection = self.connection().await?;
+        let (sender, receiver) = oneshot::channel();
+        let id = connection.send(&request, Pending::Single(sender)).await?;
+
+        match tokio::time::timeout(self.inner.options.timeout, receiver).await {
+            Ok(Ok(result)) => decode_payload(&result?),
+            Ok(Err(_)) => Err(NetError::ConnectionClosed),
+            Err(_) => {
+                let _ = connection.cancels.send(id);
+                Err(NetError::Timeout)
+            }
+        }
+    }
+
+    /// Sends a request of a streaming method, its responses arrive through the returned stream.
+    /// The stream fails with [`NetError::Timeout`] if the first response doesn't arrive within
+    /// [`ClientOptions::timeout`].
+    pub async fn call_stream<M: Method>(&self, request: M) -> NetResult<ResponseStream<M::Output>> {
+        let options = &self.inner.options;
+        let connection = self.connection().await?;
+        let (sender, receiver) = mpsc::channel(options.stream_capacity.max(1));
+        let lagged = Arc::new(AtomicBool::new(false));
+        let pending = Pending::Stream {
+            sender,
+            lagged: lagged.clone(),
+        };
+        let id = connection.send(&request, pending).await?;
+        Ok(ResponseStream {
+            id,
+            cancels: connection.cancels.clone(),
+            ended: false,
+            receiver,
+            first_deadline: Some(Box::pin(tokio::time::sleep_until(
+                Instant::now() + options.timeout,
+            ))),
+            lagged,
+            output: PhantomData,
+        })
+    }
+
+    /// The current connection, replaced by a new one if it was lost.
+    async fn connection(&self) -> NetResult<Arc<Connection>> {
+        let mut connection = self.inner.connection.lock().await;
+        if connection.is_closed() {
+            debug!("Reconnecting to {}", self.inner.address);
+            *connection =
+                Arc::new(Connection::open(&self.inner.address, &self.inner.options).await?);
+        }
+        Ok(connection.clone())
+    }
+
+    /// Subscribes and waits for the confirmation of the server.
+    async fn open_subscription(
+        &self,
+        request: &Subscribe,
+    ) -> NetResult<ResponseStream<SubscriptionUpdate>> {
+        let mut updates = self.call_stream(request.clone()).await?;
+        match updates.next().await {
+            Some(Ok(SubscriptionUpdate::Subscribed)) => Ok(updates),
+            Some(Ok(_)) => Err(NetError::UnexpectedResponse("update before subscribing")),
+            Some(Err(e)) => Err(e),
+            None => Err(NetError::ConnectionClosed),
+        }
+    }
+}
+
+/// Hands responses to the requests waiting for them, until the connection closes or stays silent
+/// for longer than the idle timeout.
+async fn read_responses(
+    mut frames: FramedRead<ReadHalf<Box<dyn Transport>>, ClientCodec>,
+    pending: PendingRequests,
+    cancels: mpsc::UnboundedSender<u64>,
+    idle_timeout: Duration,
+) {
+    loop {
+        let frame = match tokio::time::timeout(idle_timeout, frames.next()).await {
+            Ok(Some(Ok(frame))) => frame,
+            Ok(Some(Err(e))) => {
+                debug!("Connection closed: {e}");
+                break;
+            }
+            Ok(None) => {
+                debug!("Connection closed by the server");
+                break;
+            }
+            Err(_) => {
+                warn!("Connection silent for {idle_timeout:?}, closing it");
+                break;
+            }
+        };
+
+        match frame {
+            ServerFrame::Response { id, result } => {
+                let mut pending = pending.lock();
+                match pending.remove(&id) {
+                    Some(Pending::Single(sender)) => {
+                        let _ = sender.send(result);
+                    }
+                    // Never waits for a consumer, that would hold up the responses of all other
+                    // requests on the connection.
+                    Some(Pending::Stream { sender, lagged }) => match sender.try_send(result) {
+                        Ok(()) => {
+                            pending.insert(id, Pending::Stream { sender, lagged });
+                        }
+                        Err(TrySendError::Full(_)) => {
+                            warn!("Closing stream {id}, its consumer fell behind");
+                            lagged.store(true, Ordering::Release);
+                            let _ = cancels.send(id);
+                        }
+                        // Dropped by the consumer
+                        Err(TrySendError::Closed(_)) => {}
+                    },
+                    // Timed out already
+                    None => {}
+                }
+            }
+            ServerFrame::End { id } => {
+                pending.lock().remove(&id);
+            }
+            ServerFrame::Heartbeat => {}
+        }
+    }
+
+    // Waiting requests see the closed connection through their dropped senders.
+    pending.lock().clear();
+}
+
+/// Forgets requests nobody waits for anymore and tells the server to stop them, so that it
+/// doesn't keep producing responses that would be dropped.
+async fn send_cancels(
+    writer: Writer,
+    pending: PendingRequests,
+    mut cancelled: mpsc::UnboundedReceiver<u64>,
+) {
+    while let Some(id) = cancelled.recv().await {
+        pending.lock().remove(&id);
+        if let Err(e) = writer.lock().await.send(ClientFrame::Cancel { id }).await {
+            debug!("Failed to cancel request {id}: {e}");
+            return;
+        }
+    }
+}
+
+/// Responses of a streaming method, ending once the server ended the stream or the connection
+/// closed.
+///
+/// Only [`ClientOptions::stream_capacity`] responses are buffered. Reading from the connection
+/// doesn't wait for the consumer, a stream falling further behind ends with
+/// [`NetError::StreamLagged`] after its buffered responses. The server is told to stop the
+/// stream then, as it is when the stream is dropped before it ended.
+pub struct ResponseStream<T> {
+    id: u64,
+    cancels: mpsc::UnboundedSender<u64>,
+    /// Once the server needn't be told to stop the stream
+    ended: bool,
+    receiver: mpsc::Receiver<Result<Vec<u8>, RpcError>>,
+    /// Until the first response arrived
+    first_deadline: Option<Pin<Box<Sleep>>>,
+    lagged: Arc<AtomicBool>,
+    output: PhantomData<fn() -> T>,
+}
+
+impl<T: bincode::Decode<()>> Stream for ResponseStream<T> {
+    type Item = NetResult<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let Poll::Ready(result) = this.receiver.poll_recv(cx) else {
+            let timed_out = this
+                .first_deadline
+                .as_mut()
+                .is_some_and(|deadline| deadline.as_mut().poll(cx).is_ready());
+            if !timed_out {
+                return Poll::Pending;
+            }
+            // Responses arriving later are dropped by the reader
+            this.first_deadline = None;
+            this.receiver.close();
+            while this.receiver.try_recv().is_ok() {}
+            this.cancel();
+            return Poll::Ready(Some(Err(NetError::Timeout)));
+        };
+        this.first_deadline = None;
+
+        Poll::Ready(match result {
+            Some(result) => Some(
+                result
+                    .map_err(NetError::from)
+                    .and_then(|payload| decode_payload(&payload)),
+            ),
+            None if this.lagged.swap(false, Ordering::Acquire) => {
+                this.ended = true;
+                Some(Err(NetError::StreamLagged))
+            }
+            None => {
+                this.ended = true;
+                None
+            }
+        })
+    }
+}
+
+impl<T> ResponseStream<T> {
+    fn cancel(&mut self) {
+        if !self.ended {
+            self.ended = true;
+            let _ = self.cancels.send(self.id);
+        }
+    }
+}
+
+impl<T> Drop for ResponseStream<T> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Updates pushed by the server after [`Client::subscribe`].
+pub struct Subscription {
+    client: Client,
+    request: Subscribe,
+    /// `None` once resubscribing failed for good
+    updates: Option<ResponseStream<SubscriptionUpdate>>,
+}
+
+impl Subscription {
+    /// Waits for the next update. After a lost connection this reconnects until it succeeds and
+    /// returns [`SubscriptionUpdate::Subscribed`] again. `None` after the server refused to
+    /// resubscribe, e.g. because the user was removed.
+    pub async fn next(&mut self) -> Option<NetResult<SubscriptionUpdate>> {
+        let updates = self.updates.as_mut()?;
+        if let Some(update) = updates.next().await {
+            return Some(update);
+        }
+
+        let mut delay = RECONNECT_DELAY;
+        loop {
+            tokio::time::sleep(delay).await;
+            match self.client.open_subscription(&self.request).await {
+                Ok(updates) => {
+                    self.updates = Some(updates);
+                    return Some(Ok(SubscriptionUpdate::Subscribed));
+                }
+                Err(e) if is_retryable(&e) => {
+                    debug!("Resubscribing to '{}' failed: {e}", self.request.user_id);
+                    delay = (delay * 2).min(self.client.inner.options.max_reconnect_delay);
+                }
+                Err(e) => {
+                    self.updates = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Failures that may go away by trying again later.
+fn is_retryable(e: &NetError) -> bool {
+    matches!(
+        e,
+        NetError::ConnectionClosed | NetError::IO(_) | NetError::Timeout
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::{self, Handlers};
+    use tokio::net::TcpListener;
+    use tokio::sync::Notify;
+
+    /// Serves the handlers on a local port without a token, returns its address.
+    async fn server(handlers: Handlers) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let handlers = Arc::new(handlers);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let handlers = handlers.clone();
+                tokio::spawn(async move {
+                    handshake::accept(&mut stream, Codec::None, |_| true)
+                        .await
+                        .unwrap();
+                    let (reader, writer) = tokio::io::split(stream);
+                    let _ = rpc::serve(
+                        reader,
+                        writer,
+                        handlers,
+                        FrameLimits::default(),
+                        Compression::NONE,
+                        Checksums::default(),
+                    )
+                    .await;
+                });
+            }
+        });
+        address
+    }
+
+    fn chunk(user_id: &str) -> HourlyWeatherChunk {
+        HourlyWeatherChunk {
+            user_id: user_id.to_string(),
+            hours: Vec::new(),
+        }
+    }
+
+    /// Answers chunk requests with `count` chunks right away, and with none at all for `count` 0.
+    fn handlers(count: usize) -> Handlers {
+        let mut handlers = Handlers::default();
+        handlers.unary(|_: ListUsers| async { Ok(vec!["user".to_string()]) });
+        handlers.stream(move |_: GetHourlyWeatherChunks| async move {
+            let (sender, receiver) = mpsc::channel(count.max(1));
+            if count == 0 {
+                // Keeps the stream open without ever answering
+                tokio::spawn(async move {
+                    let _sender = sender;
+                    std::future::pending::<()>().await
+                });
+                return Ok(receiver);
+            }
+            for _ in 0..count {
+                sender.try_send(Ok(chunk("user"))).unwrap();
+            }
+            Ok(receiver)
+        });
+        handlers
+    }
+
+    /// Streams chunks for as long as the stream is wanted, then notifies `stopped`.
+    fn endless_handlers(stopped: Arc<Notify>) -> Handlers {
+        let mut handlers = Handlers::default();
+        handlers.stream(move |_: GetHourlyWeatherChunks| {
+            let stopped = stopped.clone();
+            async move {
+                let (sender, receiver) = mpsc::channel(1);
+                tokio::spawn(async move {
+                    while sender.send(Ok(chunk("user"))).await.is_ok() {}
+                    stopped.notify_one();
+                });
+                Ok(receiver)
+            }
+        });
+        handlers
+    }
+
+    async fn wait_until_stopped(stopped: &Notify) {
+        tokio::time::timeout(Duration::from_secs(5), stopped.notified())
+            .await
+            .expect("The server kept producing the stream");
+    }
+
+    fn options() -> ClientOptions {
+        ClientOptions {
+            compression: Compression::NONE,
+            timeout: Duration::from_millis(500),
+            stream_capacity: 4,
+            ..ClientOptions::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn lagging_stream_does_not_hold_up_other_requests() {
+        let client = Client::connect(&server(handlers(32)).await, options())
+            .await
+            .unwrap();
+        let mut chunks = client.hourly_weather_chunks(&[], None, None).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(client.list_users().await.unwrap(), vec!["user"]);
+
+        for _ in 0..4 {
+            assert_eq!(chunks.next().await.unwrap().unwrap(), chunk("user"));
+        }
+        assert!(matches!(
+            chunks.next().await,
+            Some(Err(NetError::StreamLagged))
+        ));
+        assert!(chunks.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn stream_times_out_without_a_first_item() {
+        let client = Client::connect(&server(handlers(0)).await, options())
+            .await
+            .unwrap();
+        let mut chunks = client.hourly_weather_chunks(&[], None, None).await.unwrap();
+
+        assert!(matches!(chunks.next().await, Some(Err(NetError::Timeout))));
+        assert!(chunks.next().await.is_none());
+        assert_eq!(client.list_users().await.unwrap(), vec!["user"]);
+    }
+
+    #[tokio::test]
+    async fn dropped_stream_stops_the_server() {
+        let stopped = Arc::new(Notify::new());
+        let address = server(endless_handlers(stopped.clone())).await;
+        let client = Client::connect(&address, options()).await.unwrap();
+        let mut chunks = client.hourly_weather_chunks(&[], None, None).await.unwrap();
+        assert_eq!(chunks.next().await.unwrap().unwrap(), chunk("user"));
+
+        drop(chunks);
+        wait_until_stopped(&stopped).await;
+    }
+
+    #[tokio::test]
+    async fn lagging_stream_stops_the_server() {
+        let stopped = Arc::new(Notify::new());
+        let address = server(endless_handlers(stopped.clone())).await;
+        let client = Client::connect(&address, options()).await.unwrap();
+        let mut chunks = client.hourly_weather_chunks(&[], None, None).await.unwrap();
+
+        // Not reading lets the stream fall behind
+        wait_until_stopped(&stopped).await;
+        for _ in 0..4 {
+            assert_eq!(chunks.next().await.unwrap().unwrap(), chunk("user"));
+        }
+        assert!(matches!(
+            chunks.next().await,
+            Some(Err(NetError::StreamLagged))
+        ));
+    }
+}
@@ -0,0 +1,410 @@
+//! [`tokio_util::codec`] implementations of the framing, for use with `Framed`, `FramedRead` and
+//! `FramedWrite`.
+//!
+//! Frames are buffered until they are complete, so unlike [`crate::Message::read`] reading is
+//! cancel-safe and can be raced in `select!` or put under a timeout.
+
+use crate::Message;
+use crate::checksum::{self, CHECKSUM_FLAG, CHECKSUM_SIZE, Checksums, OnMismatch};
+use crate::compression::Compression;
+use crate::error::{NetError, NetResult};
+use crate::limits::FrameLimits;
+use bincode::{Decode, Encode};
+use bytes::{Buf, BufMut, BytesMut};
+use std::marker::PhantomData;
+use tokio_util::codec::{Decoder, Encoder};
+use tracing::warn;
+
+/// Length prefix and flags.
+const HEADER_SIZE: usize = 5;
+
+/// Frames as [`Message`]s, received within limits and checked against their checksums.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MessageCodec {
+    limits: FrameLimits,
+    checksums: Checksums,
+}
+
+impl MessageCodec {
+    pub fn new(limits: FrameLimits, checksums: Checksums) -> Self {
+        Self { limits, checksums }
+    }
+}
+
+impl Decoder for MessageCodec {
+    type Item = Message;
+    type Error = NetError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> NetResult<Option<Message>> {
+        loop {
+            let Some(header) = src.get(..HEADER_SIZE) else {
+                return Ok(None);
+            };
+            let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+            let flags = header[4];
+
+            // Checked before buffering the frame, the prefix alone could claim 4 GiB.
+            if length as usize > self.limits.max_frame_size {
+                return Err(NetError::FrameTooLarge {
+                    kind: "compressed",
+                    max: self.limits.max_frame_size,
+                });
+            }
+
+            let header_size = match flags & CHECKSUM_FLAG {
+                0 => HEADER_SIZE,
+                _ => HEADER_SIZE + CHECKSUM_SIZE,
+            };
+            let frame_size = header_size + length as usize;
+            if src.len() < frame_size {
+                src.reserve(frame_size - src.len());
+                return Ok(None);
+            }
+
+            src.advance(HEADER_SIZE);
+            let checksum = (header_size > HEADER_SIZE).then(|| src.get_u32());
+            let data = src.split_to(length as usize).freeze();
+            match checksum::verify(length, flags, checksum, &data, &self.checksums) {
+                Ok(()) => {
+                    return Ok(Some(Message {
+                        length,
+                        flags,
+                        checksum,
+                        data,
+                    }));
+                }
+                Err(e) if self.checksums.on_mismatch == OnMismatch::Skip => {
+                    warn!("Skipping corrupted frame: {e}");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Encoder<Message> for MessageCodec {
+    type Error = NetError;
+
+    /// Adds a checksum to messages without one if checksums are sent.
+    fn encode(&mut self, mut message: Message, dst: &mut BytesMut) -> NetResult<()> {
+        if self.checksums.send && message.checksum.is_none() {
+            message = message.with_checksum();
+        }
+        dst.reserve(HEADER_SIZE + CHECKSUM_SIZE + message.data.len());
+        dst.put_u32(message.length);
+        dst.put_u8(message.flags);
+        if let Some(checksum) = message.checksum {
+            dst.put_u32(checksum);
+        }
+        dst.extend_from_slice(&message.data);
+        Ok(())
+    }
+}
+
+/// Frames as the types they carry, `In` is received and `Out` sent with the given compression.
+pub struct TypedCodec<In, Out> {
+    messages: MessageCodec,
+    compression: Compression,
+    types: PhantomData<fn(Out) -> In>,
+}
+
+impl<In, Out> TypedCodec<In, Out> {
+    pub fn new(limits: FrameLimits, compression: Compression, checksums: Checksums) -> Self {
+        Self {
+            messages: MessageCodec::new(limits, checksums),
+            compression,
+            types: PhantomData,
+        }
+    }
+}
+
+impl<In, Out> Default for TypedCodec<In, Out> {
+    fn default() -> Self {
+        Self::new(
+            FrameLimits::default(),
+            Compression::default(),
+            Checksums::default(),
+        )
+    }
+}
+
+impl<In, Out> Clone for TypedCodec<In, Out> {
+    fn clone(&self) -> Self {
+        Self {
+            messages: self.messages,
+            compression: self.compression,
+            types: PhantomData,
+        }
+    }
+}
+
+impl<In, Out> Decoder for TypedCodec<In, Out>
+where
+    In: Decode<()>,
+{
+    type Item = In;
+    type Error = NetError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> NetResult<Option<In>> {
+        self.messages
+            .decode(src)?
+            .map(|message| message.decode_limited(&self.messages.limits))
+            .transpose()
+    }
+}
+
+impl<In, Out> Encoder<Out> for TypedCodec<In, Out>
+where
+    Out: Encode,
+{
+    type Error = NetError;
+
+    fn encode(&mut self, item: Out, dst: &mut BytesMut) -> NetResult<()> {
+        let message = Message::encode_with(&item, &self.compression)?;
+        self.messages.encode(message, dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handshake::Codec;
+    use crate::rpc::{ClientFrame, ServerFrame};
+    use proptest::prelude::*;
+
+    const LIMITS: FrameLimits = FrameLimits {
+        max_frame_size: 64 * 1024,
+        max_decoded_size: 4096,
+    };
+
+    fn compression(codec: Codec) -> Compression {
+        Compression {
+            codec,
+            level: 3,
+            threshold: 0,
+        }
+    }
+
+    fn encoded(payload: &[u8], codec: Codec) -> BytesMut {
+        let mut codec = TypedCodec::<Vec<u8>, Vec<u8>>::new(
+            FrameLimits::default(),
+            compression(codec),
+            Checksums::default(),
+        );
+        let mut buffer = BytesMut::new();
+        codec.encode(payload.to_vec(), &mut buffer).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn round_trips_with_every_codec() {
+        let payload: Vec<u8> = (0..2000).map(|i| (i % 7) as u8).collect();
+        for codec in [Codec::None, Codec::Zstd, Codec::Lz4, Codec::ZstdDictionary] {
+            let mut buffer = encoded(&payload, codec);
+            let decoded = TypedCodec::<Vec<u8>, Vec<u8>>::new(
+                LIMITS,
+                Compression::NONE,
+                Checksums::default(),
+            )
+            .decode(&mut buffer)
+            .unwrap();
+            assert_eq!(decoded, Some(payload.clone()), "{codec:?}");
+            assert!(buffer.is_empty());
+        }
+    }
+
+    #[test]
+    fn waits_for_truncated_frames() {
+        let payload = vec![1u8; 100];
+        let frame = encoded(&payload, Codec::None);
+        let mut codec = TypedCodec::<Vec<u8>, Vec<u8>>::default();
+        let mut buffer = BytesMut::new();
+        for byte in &frame[..frame.len() - 1] {
+            buffer.put_u8(*byte);
+            assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+        }
+        // Nothing is consumed until the frame is complete
+        assert_eq!(buffer.len(), frame.len() - 1);
+        buffer.put_u8(frame[frame.len() - 1]);
+        assert_eq!(codec.decode(&mut buffer).unwrap(), Some(payload));
+    }
+
+    #[test]
+    fn rejects_oversized_length_before_buffering() {
+        let mut buffer = BytesMut::new();
+        buffer.put_u32(u32::MAX);
+        buffer.put_u8(0);
+        let Err(error) = MessageCodec::new(LIMITS, Checksums::default()).decode(&mut buffer) else {
+            panic!("frame was accepted");
+        };
+        assert!(matches!(
+            error,
+            NetError::FrameTooLarge {
+                kind: "compressed",
+                max: 65536
+            }
+        ));
+        assert!(buffer.capacity() < LIMITS.max_frame_size);
+    }
+
+    #[test]
+    fn rejects_zip_bombs() {
+        let payload = vec![0u8; 1024 * 1024];
+        for codec in [Codec::Zstd, Codec::Lz4, Codec::ZstdDictionary] {
+            let mut buffer = encoded(&payload, codec);
+            assert!(buffer.len() < LIMITS.max_frame_size, "{codec:?}");
+            let error = TypedCodec::<Vec<u8>, Vec<u8>>::new(
+                LIMITS,
+                Compression::NONE,
+                Checksums::default(),
+            )
+            .decode(&mut buffer)
+            .unwrap_err();
+            assert!(
+                matches!(
+                    error,
+                    NetError::FrameTooLarge {
+                        kind: "decoded",
+                        max: 4096
+                    }
+                ),
+                "{codec:?}: {error}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_payloads() {
+        let frame = |flags: u8, data: &[u8]| {
+            let mut buffer = BytesMut::new();
+            buffer.put_u32(data.len() as u32);
+            buffer.put_u8(flags);
+            buffer.extend_from_slice(data);
+            buffer
+        };
+        let decode =
+            |mut buffer: BytesMut| TypedCodec::<Vec<u8>, Vec<u8>>::default().decode(&mut buffer);
+
+        // A vector claiming more elements than the frame holds
+        assert!(matches!(
+            decode(frame(0, &[0xfc, 0xff, 0xff, 0xff, 0x7f])),
+            Err(NetError::Decode(_))
+        ));
+        // Announced as lz4, zstd and zstd with the dictionary
+        for flags in [2, 1, 3] {
+            assert!(
+                decode(frame(flags, &[0xde, 0xad, 0xbe, 0xef, 0x00, 0x01])).is_err(),
+                "flags {flags}"
+            );
+        }
+    }
+
+    /// Three frames with checksums, the data of the second one corrupted.
+    fn corrupted_stream() -> BytesMut {
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+        for payload in [b"first", b"other", b"third"] {
+            let message = Message::encode_with(&payload.to_vec(), &Compression::NONE).unwrap();
+            codec.encode(message, &mut buffer).unwrap();
+        }
+        let second_data = 2 * (HEADER_SIZE + CHECKSUM_SIZE + 6) - 1;
+        buffer[second_data] ^= 0b0001_0000;
+        buffer
+    }
+
+    fn decode_all(checksums: Checksums) -> Vec<NetResult<Vec<u8>>> {
+        let mut codec = TypedCodec::<Vec<u8>, Vec<u8>>::new(
+            FrameLimits::default(),
+            Compression::NONE,
+            checksums,
+        );
+        let mut buffer = corrupted_stream();
+        let mut decoded = Vec::new();
+        loop {
+            match codec.decode(&mut buffer) {
+                Ok(Some(payload)) => decoded.push(Ok(payload)),
+                Ok(None) => return decoded,
+                Err(e) => {
+                    decoded.push(Err(e));
+                    return decoded;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn skips_corrupted_frames_if_asked() {
+        let decoded = decode_all(Checksums {
+            on_mismatch: OnMismatch::Skip,
+            ..Checksums::default()
+        });
+        let decoded: Vec<Vec<u8>> = decoded.into_iter().map(Result::unwrap).collect();
+        assert_eq!(decoded, [b"first".to_vec(), b"third".to_vec()]);
+    }
+
+    #[test]
+    fn fails_on_corrupted_frames_by_default() {
+        let decoded = decode_all(Checksums::default());
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].as_ref().unwrap(), b"first");
+        assert!(matches!(decoded[1], Err(NetError::ChecksumMismatch { .. })));
+    }
+
+    /// Decodes frames until the buffer runs out or decoding fails.
+    fn decode_frames<T: Decode<()>>(mut buffer: BytesMut) {
+        let mut codec = TypedCodec::<T, ()>::new(LIMITS, Compression::NONE, Checksums::default());
+        while let Ok(Some(_)) = codec.decode(&mut buffer) {}
+    }
+
+    fn decode_both_directions(buffer: &BytesMut) {
+        decode_frames::<ClientFrame>(buffer.clone());
+        decode_frames::<ServerFrame>(buffer.clone());
+    }
+
+    fn frame(flags: u8, data: &[u8], with_checksum: bool) -> BytesMut {
+        let length = data.len() as u32;
+        let mut buffer = BytesMut::new();
+        buffer.put_u32(length);
+        if with_checksum {
+            let flags = flags | CHECKSUM_FLAG;
+            buffer.put_u8(flags);
+            buffer.put_u32(checksum::compute(length, flags, data));
+        } else {
+            buffer.put_u8(flags & !CHECKSUM_FLAG);
+        }
+        buffer.extend_from_slice(data);
+        buffer
+    }
+
+    fn any_codec() -> impl Strategy<Value = Codec> {
+        prop::sample::select(Codec::SUPPORTED.to_vec())
+    }
+
+    proptest! {
+        #[test]
+        fn arbitrary_bytes_fail_without_panicking(
+            bytes in prop::collection::vec(any::<u8>(), 0..512),
+        ) {
+            decode_both_directions(&BytesMut::from(&bytes[..]));
+        }
+
+        #[test]
+        fn arbitrary_frames_fail_without_panicking(
+            flags in any::<u8>(),
+            data in prop::collection::vec(any::<u8>(), 0..2048),
+            with_checksum in any::<bool>(),
+        ) {
+            decode_both_directions(&frame(flags, &data, with_checksum));
+        }
+
+        #[test]
+        fn arbitrary_payloads_fail_without_panicking_with_every_codec(
+            codec in any_codec(),
+            payload in prop::collection::vec(any::<u8>(), 0..2048),
+            with_checksum in any::<bool>(),
+        ) {
+            let (flags, data) = compression(codec).compress(payload).unwrap();
+            decode_both_directions(&frame(flags, &data, with_checksum));
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Compression of frame payloads, announced by the flags byte of every frame:
+//!
+//! ```text
+//! bits 0-1    codec, 0 none | 1 zstd | 2 lz4 | 3 zstd with the bundled weather dictionary
+//! bit  2      a checksum follows the flags, see [`crate::checksum`]
+//! bits 3-7    level the payload was compressed with
+//! ```
+//!
+//! Receivers decompress whatever a frame announces, the codec negotiated in the handshake only
+//! decides what is sent.
+
+use crate::error::{NetError, NetResult};
+use crate::handshake::Codec;
+use std::borrow::Cow;
+use std::io::Read;
+use std::sync::{LazyLock, OnceLock};
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
+
+/// Dictionary trained on hourly weather responses with `omnistat-server net-dictionary`.
+///
+/// Peers need the same dictionary to read each other's frames, replacing it requires a new
+/// [`crate::handshake::PROTOCOL_VERSION`].
+pub const WEATHER_DICTIONARY: &[u8] = include_bytes!("../dictionaries/weather.zdict");
+
+pub const MAX_ZSTD_LEVEL: u8 = 22;
+
+const CODEC_MASK: u8 = 0b0000_0011;
+const CODEC_NONE: u8 = 0;
+const CODEC_ZSTD: u8 = 1;
+const CODEC_LZ4: u8 = 2;
+const CODEC_ZSTD_DICTIONARY: u8 = 3;
+const LEVEL_SHIFT: u32 = 3;
+
+static DECODER_DICTIONARY: LazyLock<DecoderDictionary<'static>> =
+    LazyLock::new(|| DecoderDictionary::copy(WEATHER_DICTIONARY));
+
+/// Encoder dictionaries are prepared for a level, on first use of that level.
+static ENCODER_DICTIONARIES: [OnceLock<EncoderDictionary<'static>>; MAX_ZSTD_LEVEL as usize + 1] =
+    [const { OnceLock::new() }; MAX_ZSTD_LEVEL as usize + 1];
+
+/// How frames are compressed before they are sent.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Compression {
+    pub codec: Codec,
+    /// zstd level from 1 to [`MAX_ZSTD_LEVEL`], lz4 has no levels
+    pub level: u8,
+    /// Payloads smaller than this many bytes are sent uncompressed,
+    /// compressing them costs more than it saves
+    pub threshold: usize,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self {
+            codec: Codec::Zstd,
+            level: 3,
+            threshold: 256,
+        }
+    }
+}
+
+impl Compression {
+    /// Uncompressed frames only.
+    pub const NONE: Compression = Compression {
+        codec: Codec::None,
+        level: 0,
+        threshold: 0,
+    };
+
+    /// Compresses a payload, returns the flags of the frame along with the data. Payloads that
+    /// don't get smaller are sent as they are.
+    pub(crate) fn compress(&self, payload: Vec<u8>) -> NetResult<(u8, Vec<u8>)> {
+        if payload.len() < self.threshold {
+            return Ok((CODEC_NONE, payload));
+        }
+
+        let level = self.level.min(MAX_ZSTD_LEVEL);
+        let (flags, compressed) = match self.codec {
+            Codec::None => return Ok((CODEC_NONE, payload)),
+            Codec::Zstd => (
+                CODEC_ZSTD | level << LEVEL_SHIFT,
+                zstd::bulk::compress(&payload, level as i32)?,
+            ),
+            Codec::ZstdDictionary => {
+                let dictionary = ENCODER_DICTIONARIES[level as usize]
+                    .get_or_init(|| EncoderDictionary::copy(WEATHER_DICTIONARY, level as i32));
+                let compressed = zstd::bulk::Compressor::with_prepared_dictionary(dictionary)?
+                    .compress(&payload)?;
+                (CODEC_ZSTD_DICTIONARY | level << LEVEL_SHIFT, compressed)
+            }
+            Codec::Lz4 => (CODEC_LZ4, lz4_flex::compress_prepend_size(&payload)),
+        };
+
+        if compressed.len() >= payload.len() {
+            return Ok((CODEC_NONE, payload));
+        }
+        Ok((flags, compressed))
+    }
+}
+
+/// Name of the codec a frame announces, for logging.
+pub(crate) fn codec_name(flags: u8) -> &'static str {
+    match flags & CODEC_MASK {
+        CODEC_NONE => "none",
+        CODEC_ZSTD => "zstd",
+        CODEC_LZ4 => "lz4",
+        // Two bits hold no other value
+        _ => "zstd+dictionary",
+    }
+}
+
+/// Decompresses the data of a frame, without producing more than `max_size` bytes.
+pub(crate) fn decompress(flags: u8, data: &[u8], max_size: usize) -> NetResult<Cow<'_, [u8]>> {
+    let too_large = || NetError::FrameTooLarge {
+        kind: "decoded",
+        max: max_size,
+    };
+
+    let decompressed = match flags & CODEC_MASK {
+        CODEC_NONE => Cow::Borrowed(data),
+        CODEC_LZ4 => {
+            let (size, block) = lz4_flex::block::uncompressed_size(data)
+                .map_err(|e| NetError::InvalidFrame(e.to_string()))?;
+            if size > max_size {
+                return Err(too_large());
+            }
+            let mut decompressed = vec![0u8; size];
+            let written = lz4_flex::block::decompress_into(block, &mut decompressed)
+                .map_err(|e| NetError::InvalidFrame(e.to_string()))?;
+            decompressed.truncate(written);
+            Cow::Owned(decompressed)
+        }
+        // zstd, with or without the dictionary
+        codec => {
+            let mut decompressed = Vec::new();
+            let limit = max_size as u64 + 1;
+            if codec == CODEC_ZSTD_DICTIONARY {
+                zstd::stream::read::Decoder::with_prepared_dictionary(data, &DECODER_DICTIONARY)?
+                    .take(limit)
+                    .read_to_end(&mut decompressed)?;
+            } else {
+                zstd::stream::read::Decoder::with_buffer(data)?
+                    .take(limit)
+                    .read_to_end(&mut decompressed)?;
+            }
+            Cow::Owned(decompressed)
+        }
+    };
+
+    if decompressed.len() > max_size {
+        return Err(too_large());
+    }
+    Ok(decompressed)
+}
+
+/// Trains a zstd dictionary of at most `max_size` bytes on sample payloads, see
+/// [`WEATHER_DICTIONARY`].
+pub fn train_dictionary<S: AsRef<[u8]>>(samples: &[S], max_size: usize) -> NetResult<Vec<u8>> {
+    Ok(zstd::dict::from_samples(samples, max_size)?)
+}
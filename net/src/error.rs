@@ -1,13 +1,44 @@
+use crate::rpc::RpcError;
 use thiserror::Error;
 
 pub type NetResult<T> = Result<T, NetError>;
 
 #[derive(Debug, Error)]
 pub enum NetError {
+    #[error("Checksum mismatch: frame announces {expected:#010x}, contents have {actual:#010x}")]
+    ChecksumMismatch { expected: u32, actual: u32 },
+    #[error("Connection closed")]
+    ConnectionClosed,
     #[error("Decode error: {0}")]
     Decode(#[from] bincode::error::DecodeError),
     #[error("Encode error: {0}")]
     Encode(#[from] bincode::error::EncodeError),
+    #[error("Frame too large: {kind} size exceeds the maximum of {max} bytes")]
+    FrameTooLarge { kind: &'static str, max: usize },
+    #[error("Handshake failed: {0}")]
+    Handshake(String),
+    #[error("Invalid frame: {0}")]
+    InvalidFrame(String),
     #[error("IO error: {0}")]
     IO(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("No compression codec supported by both sides")]
+    NoCommonCodec,
+    #[error("Request failed: {0}")]
+    Rpc(#[from] RpcError),
+    #[error("Stream closed, its responses weren't consumed fast enough")]
+    StreamLagged,
+    #[error("Request timed out")]
+    Timeout,
+    #[error("TLS error: {0}")]
+    Tls(String),
+    #[error("Unauthorized, the server rejected the token")]
+    Unauthorized,
+    #[error("Unexpected response: {0}")]
+    UnexpectedResponse(&'static str),
+    #[error(
+        "Protocol version mismatch: this side speaks version {local}, the peer version {remote}"
+    )]
+    VersionMismatch { local: u16, remote: u16 },
 }
@@ -0,0 +1,268 @@
+//! The first bytes of every connection, exchanged before any [`crate::Message`].
+//!
+//! The layout is fixed instead of bincode so that every version can read the version of its peer:
+//!
+//! ```text
+//! hello    magic [4] | version u16 | codecs u8 | token length u16 | token [token length]
+//! reply    magic [4] | version u16 | status u8 | codec u8
+//! ```
+//!
+//! Integers are big-endian, codecs are a bit set of [`Codec`]s and an empty token means none.
+
+use crate::error::{NetError, NetResult};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::debug;
+
+pub const MAGIC: [u8; 4] = *b"OMNI";
+
+/// Incremented whenever the types of [`crate::protocol`] or the framing change incompatibly.
+pub const PROTOCOL_VERSION: u16 = 7;
+
+/// Compression of message payloads, see [`crate::compression`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Codec {
+    None,
+    Zstd,
+    /// zstd with [`crate::compression::WEATHER_DICTIONARY`]
+    ZstdDictionary,
+    Lz4,
+}
+
+impl Codec {
+    /// Supported codecs, chosen in this order when the preference of the server isn't offered.
+    pub const SUPPORTED: [Codec; 4] = [Codec::Zstd, Codec::ZstdDictionary, Codec::Lz4, Codec::None];
+
+    fn bit(self) -> u8 {
+        match self {
+            Codec::Zstd => 0b0000_0001,
+            Codec::Lz4 => 0b0000_0010,
+            Codec::ZstdDictionary => 0b0000_0100,
+            Codec::None => 0b0000_1000,
+        }
+    }
+
+    fn from_bit(bit: u8) -> Option<Self> {
+        Self::SUPPORTED.into_iter().find(|codec| codec.bit() == bit)
+    }
+
+    fn set(codecs: &[Codec]) -> u8 {
+        codecs.iter().fold(0, |set, codec| set | codec.bit())
+    }
+}
+
+/// Why a server turned a connection down.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Rejection {
+    VersionMismatch,
+    NoCommonCodec,
+    Unauthorized,
+}
+
+impl Rejection {
+    const ALL: [Rejection; 3] = [
+        Rejection::VersionMismatch,
+        Rejection::NoCommonCodec,
+        Rejection::Unauthorized,
+    ];
+
+    fn status(self) -> u8 {
+        match self {
+            Rejection::VersionMismatch => 1,
+            Rejection::NoCommonCodec => 2,
+            Rejection::Unauthorized => 3,
+        }
+    }
+
+    fn into_error(self, remote_version: u16) -> NetError {
+        match self {
+            Rejection::VersionMismatch => NetError::VersionMismatch {
+                local: PROTOCOL_VERSION,
+                remote: remote_version,
+            },
+            Rejection::NoCommonCodec => NetError::NoCommonCodec,
+            Rejection::Unauthorized => NetError::Unauthorized,
+        }
+    }
+}
+
+const STATUS_ACCEPTED: u8 = 0;
+
+/// What both sides agreed on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Negotiated {
+    pub version: u16,
+    pub codec: Codec,
+    /// Token the client authenticated with, only known to the server
+    pub token: Option<String>,
+}
+
+/// Opens a connection as a client offering `codecs`, fails with the reason if the server
+/// rejects it.
+pub async fn connect<S>(
+    stream: &mut S,
+    token: Option<&str>,
+    codecs: &[Codec],
+) -> NetResult<Negotiated>
+where
+    S: AsyncReadExt + AsyncWriteExt + Unpin,
+{
+    let token = token.unwrap_or_default();
+    let token_length = u16::try_from(token.len()).map_err(|_| NetError::FrameTooLarge {
+        kind: "token",
+        max: u16::MAX as usize,
+    })?;
+
+    let mut hello = Vec::with_capacity(9 + token.len());
+    hello.extend_from_slice(&MAGIC);
+    hello.extend_from_slice(&PROTOCOL_VERSION.to_be_bytes());
+    hello.push(Codec::set(codecs));
+    hello.extend_from_slice(&token_length.to_be_bytes());
+    hello.extend_from_slice(token.as_bytes());
+    stream.write_all(&hello).await?;
+    stream.flush().await?;
+
+    let remote_version = read_preamble(stream).await?;
+    let status = stream.read_u8().await?;
+    let codec = stream.read_u8().await?;
+
+    if status != STATUS_ACCEPTED {
+        let rejection = Rejection::ALL
+            .into_iter()
+            .find(|rejection| rejection.status() == status)
+            .ok_or_else(|| NetError::Handshake(format!("unknown status {status}")))?;
+        return Err(rejection.into_error(remote_version));
+    }
+
+    let codec = Codec::from_bit(codec).ok_or(NetError::NoCommonCodec)?;
+    debug!("Handshake accepted, protocol version {remote_version}, codec {codec:?}");
+    Ok(Negotiated {
+        version: remote_version,
+        codec,
+        token: None,
+    })
+}
+
+/// Answers the handshake of a client, `authorize` decides about its token. The `preferred` codec
+/// is chosen if the client offers it.
+///
+/// Rejections are sent to the client before they are returned as errors.
+pub async fn accept<S, F>(stream: &mut S, preferred: Codec, authorize: F) -> NetResult<Negotiated>
+where
+    S: AsyncReadExt + AsyncWriteExt + Unpin,
+    F: FnOnce(Option<&str>) -> bool,
+{
+    let remote_version = read_preamble(stream).await?;
+    let codecs = stream.read_u8().await?;
+    let token_length = stream.read_u16().await?;
+    let mut token = vec![0u8; token_length as usize];
+    stream.read_exact(&mut token).await?;
+    let token = String::from_utf8(token)
+        .map_err(|_| NetError::Handshake("token is not UTF-8".to_string()))?;
+    let token = (!token.is_empty()).then_some(token);
+
+    match negotiate(
+        remote_version,
+        codecs,
+        preferred,
+        token.as_deref(),
+        authorize,
+    ) {
+        Ok(codec) => {
+            write_reply(stream, STATUS_ACCEPTED, codec.bit()).await?;
+            Ok(Negotiated {
+                version: remote_version,
+                codec,
+                token,
+            })
+        }
+        Err(rejection) => {
+            write_reply(stream, rejection.status(), 0).await?;
+            Err(rejection.into_error(remote_version))
+        }
+    }
+}
+
+fn negotiate<F>(
+    remote_version: u16,
+    codecs: u8,
+    preferred: Codec,
+    token: Option<&str>,
+    authorize: F,
+) -> Result<Codec, Rejection>
+where
+    F: FnOnce(Option<&str>) -> bool,
+{
+    if remote_version != PROTOCOL_VERSION {
+        return Err(Rejection::VersionMismatch);
+    }
+    let codec = std::iter::once(preferred)
+        .chain(Codec::SUPPORTED)
+        .find(|codec| codecs & codec.bit() != 0)
+        .ok_or(Rejection::NoCommonCodec)?;
+    if !authorize(token) {
+        return Err(Rejection::Unauthorized);
+    }
+    Ok(codec)
+}
+
+/// Reads the magic and the protocol version of the peer.
+async fn read_preamble<S>(stream: &mut S) -> NetResult<u16>
+where
+    S: AsyncReadExt + Unpin,
+{
+    let mut magic = [0u8; 4];
+    stream.read_exact(&mut magic).await?;
+    if magic != MAGIC {
+        return Err(NetError::Handshake(
+            "peer does not speak the omnistat protocol".to_string(),
+        ));
+    }
+    Ok(stream.read_u16().await?)
+}
+
+async fn write_reply<S>(stream: &mut S, status: u8, codec: u8) -> NetResult<()>
+where
+    S: AsyncWriteExt + Unpin,
+{
+    let mut reply = Vec::with_capacity(8);
+    reply.extend_from_slice(&MAGIC);
+    reply.extend_from_slice(&PROTOCOL_VERSION.to_be_bytes());
+    reply.push(status);
+    reply.push(codec);
+    stream.write_all(&reply).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs both sides of a handshake, the server only accepts `expected`.
+    async fn handshake(
+        token: Option<&str>,
+        expected: &str,
+    ) -> (NetResult<Negotiated>, NetResult<Negotiated>) {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        tokio::join!(
+            connect(&mut client, token, &[Codec::Zstd, Codec::None]),
+            accept(&mut server, Codec::Zstd, |token| token == Some(expected)),
+        )
+    }
+
+    #[tokio::test]
+    async fn accepts_the_expected_token() {
+        let (client, server) = handshake(Some("secret"), "secret").await;
+        assert_eq!(client.unwrap().codec, Codec::Zstd);
+        assert_eq!(server.unwrap().token.as_deref(), Some("secret"));
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_and_missing_tokens() {
+        for token in [Some("wrong"), Some("secre"), Some("secret "), None] {
+            let (client, server) = handshake(token, "secret").await;
+            assert!(matches!(client, Err(NetError::Unauthorized)), "{token:?}");
+            assert!(matches!(server, Err(NetError::Unauthorized)), "{token:?}");
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! JSON form of the protocol, for clients that can't decode bincode like browsers.
+//!
+//! Every message is a JSON object of its own, which needs a transport that keeps messages apart
+//! such as WebSocket text messages. The client opens with a [`JsonHello`], the server answers
+//! with a [`JsonWelcome`], then the client sends [`JsonClientFrame`]s and the server
+//! [`JsonServerFrame`]s:
+//!
+//! ```text
+//! → {"version": 7, "token": "secret"}
+//! ← {"version": 7}
+//! → {"id": 1, "method": "hourly_weather", "payload": {"user_id": "home", "from": null}}
+//! ← {"type": "response", "id": 1, "result": [{"time": 1760868000, ...}]}
+//! → {"cancel": 2}
+//! ```
+//!
+//! Payloads are the [`crate::protocol`] types in their serde form, methods without fields take
+//! `null` or no payload at all. There is no compression, transports compress on their own.
+
+use crate::error::{NetError, NetResult};
+use crate::handshake::PROTOCOL_VERSION;
+use crate::rpc::{ClientFrame, ErrorCode, RequestFrame, ServerFrame};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+
+/// The first message of a client.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonHello {
+    /// [`PROTOCOL_VERSION`] of the client
+    pub version: u16,
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// The answer to a [`JsonHello`], the server closes the connection after an error.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonWelcome {
+    pub version: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<&NetResult<()>> for JsonWelcome {
+    fn from(accepted: &NetResult<()>) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            error: accepted.as_ref().err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// A [`RequestFrame`] with a JSON payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRequest {
+    pub id: u64,
+    pub method: String,
+    #[serde(default)]
+    pub payload: Value,
+}
+
+impl JsonRequest {
+    pub fn into_frame(self) -> NetResult<RequestFrame> {
+        Ok(RequestFrame {
+            id: self.id,
+            method: self.method,
+            payload: serde_json::to_vec(&self.payload)?,
+        })
+    }
+}
+
+/// A [`ClientFrame`] with a JSON payload, a [`JsonRequest`] or `{"cancel": id}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum JsonClientFrame {
+    Request(JsonRequest),
+    Cancel { cancel: u64 },
+}
+
+impl JsonClientFrame {
+    pub fn into_frame(self) -> NetResult<ClientFrame> {
+        Ok(match self {
+            JsonClientFrame::Request(request) => ClientFrame::Request(request.into_frame()?),
+            JsonClientFrame::Cancel { cancel } => ClientFrame::Cancel { id: cancel },
+        })
+    }
+}
+
+/// A [`ServerFrame`] with a JSON payload, errors get a frame of their own.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonServerFrame {
+    Response {
+        id: u64,
+        result: Value,
+    },
+    Error {
+        id: u64,
+        code: ErrorCode,
+        message: String,
+    },
+    End {
+        id: u64,
+    },
+    Heartbeat,
+}
+
+impl TryFrom<ServerFrame> for JsonServerFrame {
+    type Error = NetError;
+
+    /// Payloads of the frame have to be JSON, see [`crate::rpc::PayloadFormat::Json`].
+    fn try_from(frame: ServerFrame) -> NetResult<Self> {
+        Ok(match frame {
+            ServerFrame::Response {
+                id,
+                result: Ok(payload),
+            } => JsonServerFrame::Response {
+                id,
+                result: serde_json::from_slice(&payload)?,
+            },
+            ServerFrame::Response {
+                id,
+                result: Err(error),
+            } => JsonServerFrame::Error {
+                id,
+                code: error.code,
+                message: error.message,
+            },
+            ServerFrame::End { id } => JsonServerFrame::End { id },
+            ServerFrame::Heartbeat => JsonServerFrame::Heartbeat,
+        })
+    }
+}
+
+/// Checks the hello of a client, `authorize` decides about its token. The [`JsonWelcome`] of
+/// the result is sent back either way.
+pub fn accept<F>(hello: &str, authorize: F) -> NetResult<()>
+where
+    F: FnOnce(Option<&str>) -> bool,
+{
+    let hello: JsonHello = serde_json::from_str(hello)
+        .map_err(|e| NetError::Handshake(format!("invalid hello: {e}")))?;
+    if hello.version != PROTOCOL_VERSION {
+        return Err(NetError::VersionMismatch {
+            local: PROTOCOL_VERSION,
+            remote: hello.version,
+        });
+    }
+    let token = hello.token.as_deref().filter(|token| !token.is_empty());
+    if !authorize(token) {
+        return Err(NetError::Unauthorized);
+    }
+    debug!(
+        "JSON handshake accepted, protocol version {}",
+        hello.version
+    );
+    Ok(())
+}
@@ -1,46 +1,120 @@
-use crate::error::NetResult;
+use crate::checksum::{CHECKSUM_FLAG, Checksums};
+use crate::compression::Compression;
+use crate::error::{NetError, NetResult};
+use crate::limits::FrameLimits;
 use bincode::{Decode, Encode};
+use bytes::Bytes;
 use omnistat_core::types::digital_information::DigitalInformation;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tracing::debug;
 
-mod error;
+pub mod checksum;
+pub mod client;
+pub mod codec;
+pub mod compression;
+pub mod error;
+pub mod handshake;
+pub mod json;
+pub mod limits;
+pub mod protocol;
+pub mod rpc;
+pub mod tls;
 
+/// Bytes bincode may claim per byte it decodes. Claims are the in-memory size of what is
+/// decoded, the largest being a one-byte varint that decodes to a `u128`.
+const DECODE_ALLOCATION_FACTOR: usize = 16;
+
+/// Decodes bincode, allocating at most [`DECODE_ALLOCATION_FACTOR`] bytes per byte of `bytes`.
+/// Containers are allocated by their length prefix, which a few bytes can claim to be anything.
+pub(crate) fn decode_bounded<T: Decode<()>>(bytes: &[u8]) -> NetResult<T> {
+    let limit = bytes.len().saturating_mul(DECODE_ALLOCATION_FACTOR);
+    // Bincode only takes the limit as a const, the next power of four above it is used
+    macro_rules! decode_within {
+        ($($shift:literal)*) => {$(
+            if limit <= 1 << $shift {
+                let config = bincode::config::standard().with_limit::<{ 1 << $shift }>();
+                return Ok(bincode::decode_from_slice(bytes, config)?.0);
+            }
+        )*};
+    }
+    decode_within!(12 14 16 18 20 22 24 26 28 30);
+    let config = bincode::config::standard().with_limit::<{ 1 << 31 }>();
+    Ok(bincode::decode_from_slice(bytes, config)?.0)
+}
+
+/// A frame: `length u32 | flags u8 | checksum u32 | data [length]`, the flags are described in
+/// [`compression`] and the checksum in [`checksum`], frames without one leave it out.
 pub struct Message {
     length: u32,
-    data: Vec<u8>,
+    flags: u8,
+    checksum: Option<u32>,
+    data: Bytes,
 }
 
 impl Message {
     pub fn encode<T>(encodable: &T) -> NetResult<Self>
+    where
+        T: Encode,
+    {
+        Self::encode_with(encodable, &Compression::default())
+    }
+
+    pub fn encode_with<T>(encodable: &T, compression: &Compression) -> NetResult<Self>
     where
         T: Encode,
     {
         let uncompressed = bincode::encode_to_vec(encodable, bincode::config::standard())?;
-        let compressed = zstd::encode_all(uncompressed.as_slice(), 0)?;
+        let uncompressed_size = uncompressed.len();
+        let (flags, compressed) = compression.compress(uncompressed)?;
+        let length = u32::try_from(compressed.len()).map_err(|_| NetError::FrameTooLarge {
+            kind: "compressed",
+            max: u32::MAX as usize,
+        })?;
 
         debug!(
-            "Message encoded ({}) =zstd=> ({})",
-            DigitalInformation::from_bytes(uncompressed.len()).format_pretty(),
+            "Message encoded ({}) ={}=> ({})",
+            DigitalInformation::from_bytes(uncompressed_size).format_pretty(),
+            compression::codec_name(flags),
             DigitalInformation::from_bytes(compressed.len()).format_pretty()
         );
 
         Ok(Self {
-            length: compressed.len() as u32,
-            data: compressed,
+            length,
+            flags,
+            checksum: None,
+            data: Bytes::from(compressed),
         })
     }
 
+    /// Adds a checksum to the frame, see [`checksum`].
+    pub fn with_checksum(mut self) -> Self {
+        self.flags |= CHECKSUM_FLAG;
+        self.checksum = Some(checksum::compute(self.length, self.flags, &self.data));
+        self
+    }
+
     pub fn decode<T>(self) -> NetResult<T>
     where
         T: Decode<()>,
     {
-        let decompressed = zstd::decode_all(self.data.as_slice())?;
-        let (decoded, _) = bincode::decode_from_slice(&decompressed, bincode::config::standard())?;
+        self.decode_limited(&FrameLimits::default())
+    }
+
+    /// Decodes without decompressing more than the limit, a small frame can expand enormously.
+    /// Decoding allocates no more than [`decode_bounded`] allows for the decompressed size.
+    pub fn decode_limited<T>(self, limits: &FrameLimits) -> NetResult<T>
+    where
+        T: Decode<()>,
+    {
+        let compressed_size = self.data.len();
+        let decompressed =
+            compression::decompress(self.flags, &self.data, limits.max_decoded_size)?;
+        let decoded = decode_bounded(&decompressed)?;
 
         debug!(
-            "Message decoded ({}) =zstd=> ({})",
-            DigitalInformation::from_bytes(self.data.len()).format_pretty(),
+            "Message decoded ({}) ={}=> ({})",
+            DigitalInformation::from_bytes(compressed_size).format_pretty(),
+            compression::codec_name(self.flags),
             DigitalInformation::from_bytes(decompressed.len()).format_pretty()
         );
 
@@ -48,6 +122,18 @@ impl Message {
     }
 
     pub async fn read<R>(reader: &mut R) -> NetResult<Self>
+    where
+        R: AsyncReadExt + Unpin,
+    {
+        Self::read_limited(reader, &FrameLimits::default()).await
+    }
+
+    /// Reads a frame, rejecting it before allocating if its length prefix exceeds the limit.
+    /// A checksum of the frame is verified.
+    ///
+    /// Not cancel-safe, a frame read partially is lost. [`codec::MessageCodec`] buffers frames
+    /// until they are complete.
+    pub async fn read_limited<R>(reader: &mut R, limits: &FrameLimits) -> NetResult<Self>
     where
         R: AsyncReadExt + Unpin,
     {
@@ -60,15 +146,33 @@ impl Message {
             DigitalInformation::from_bytes(length as usize).format_pretty()
         );
 
+        if length as usize > limits.max_frame_size {
+            return Err(NetError::FrameTooLarge {
+                kind: "compressed",
+                max: limits.max_frame_size,
+            });
+        }
+
+        let flags = reader.read_u8().await?;
+        let checksum = match flags & CHECKSUM_FLAG {
+            0 => None,
+            _ => Some(reader.read_u32().await?),
+        };
         let mut data = vec![0u8; length as usize];
         reader.read_exact(&mut data).await?;
+        checksum::verify(length, flags, checksum, &data, &Checksums::default())?;
 
         debug!(
             "Read message data: {}",
             DigitalInformation::from_bytes(data.len()).format_pretty()
         );
 
-        Ok(Self { length, data })
+        Ok(Self {
+            length,
+            flags,
+            checksum,
+            data: Bytes::from(data),
+        })
     }
 
     pub async fn write<W>(&self, writer: &mut W) -> NetResult<()>
@@ -77,6 +181,10 @@ impl Message {
     {
         let len_bytes: [u8; 4] = self.length.to_be_bytes();
         writer.write_all(&len_bytes).await?;
+        writer.write_u8(self.flags).await?;
+        if let Some(checksum) = self.checksum {
+            writer.write_u32(checksum).await?;
+        }
 
         debug!(
             "Wrote message length: {}",
@@ -94,13 +202,37 @@ impl Message {
 }
 
 pub trait MessageSend {
+    /// Sends a message with the default [`Compression`].
     fn send<W>(&self, writer: &mut W) -> impl Future<Output = NetResult<()>>
+    where
+        W: AsyncWriteExt + Unpin,
+    {
+        self.send_with(writer, Compression::default())
+    }
+
+    fn send_with<W>(
+        &self,
+        writer: &mut W,
+        compression: Compression,
+    ) -> impl Future<Output = NetResult<()>>
     where
         W: AsyncWriteExt + Unpin;
 }
 
 pub trait MessageRecv {
+    /// Receives a message within the default [`FrameLimits`].
     fn recv<R>(reader: &mut R) -> impl Future<Output = NetResult<Self>>
+    where
+        R: AsyncReadExt + Unpin,
+        Self: Sized,
+    {
+        Self::recv_limited(reader, FrameLimits::default())
+    }
+
+    fn recv_limited<R>(
+        reader: &mut R,
+        limits: FrameLimits,
+    ) -> impl Future<Output = NetResult<Self>>
     where
         R: AsyncReadExt + Unpin,
         Self: Sized;
@@ -110,11 +242,11 @@ impl<T> MessageSend for T
 where
     T: Encode,
 {
-    async fn send<W>(&self, writer: &mut W) -> NetResult<()>
+    async fn send_with<W>(&self, writer: &mut W, compression: Compression) -> NetResult<()>
     where
         W: AsyncWriteExt + Unpin,
     {
-        let message = Message::encode(self)?;
+        let message = Message::encode_with(self, &compression)?;
         message.write(writer).await
     }
 }
@@ -123,11 +255,97 @@ impl<T> MessageRecv for T
 where
     T: Decode<()>,
 {
-    async fn recv<R>(reader: &mut R) -> NetResult<Self>
+    async fn recv_limited<R>(reader: &mut R, limits: FrameLimits) -> NetResult<Self>
     where
         R: AsyncReadExt + Unpin,
     {
-        let message = Message::read(reader).await?;
-        message.decode()
+        let message = Message::read_limited(reader, &limits).await?;
+        message.decode_limited(&limits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: FrameLimits = FrameLimits {
+        max_frame_size: 64 * 1024,
+        max_decoded_size: 4096,
+    };
+
+    async fn sent(payload: &Vec<u8>) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        payload
+            .send_with(&mut buffer, Compression::default())
+            .await
+            .unwrap();
+        buffer
+    }
+
+    #[tokio::test]
+    async fn receives_within_limits() {
+        let payload = vec![7u8; 4000];
+        let buffer = sent(&payload).await;
+        let received = Vec::<u8>::recv_limited(&mut &buffer[..], LIMITS).await;
+        assert_eq!(received.unwrap(), payload);
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_length_without_reading_the_frame() {
+        // Only the length prefix is there, the frame would be read if it was accepted
+        let buffer = u32::MAX.to_be_bytes();
+        let error = Message::read_limited(&mut &buffer[..], &LIMITS).await.err();
+        assert!(matches!(
+            error,
+            Some(NetError::FrameTooLarge {
+                kind: "compressed",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn fails_on_truncated_frames() {
+        let buffer = sent(&vec![7u8; 4000]).await;
+        for end in [2, 4, 5, 7, buffer.len() - 1] {
+            let error = Message::read_limited(&mut &buffer[..end], &LIMITS)
+                .await
+                .err();
+            let Some(NetError::IO(e)) = error else {
+                panic!("truncated at {end}: {error:?}");
+            };
+            assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof);
+        }
+    }
+
+    #[test]
+    fn rejects_containers_larger_than_the_frame() {
+        // A vector of 32 TiB in a frame of 9 bytes
+        let mut data = vec![0xfd];
+        data.extend_from_slice(&(1u64 << 45).to_le_bytes());
+        let message = Message {
+            length: data.len() as u32,
+            flags: 0,
+            checksum: None,
+            data: Bytes::from(data),
+        };
+        let error = message.decode_limited::<Vec<u8>>(&LIMITS).err();
+        assert!(matches!(error, Some(NetError::Decode(_))), "{error:?}");
+    }
+
+    #[tokio::test]
+    async fn rejects_zip_bombs() {
+        let buffer = sent(&vec![0u8; 16 * 1024 * 1024]).await;
+        assert!(buffer.len() < LIMITS.max_frame_size);
+        let error = Vec::<u8>::recv_limited(&mut &buffer[..], LIMITS)
+            .await
+            .err();
+        assert!(matches!(
+            error,
+            Some(NetError::FrameTooLarge {
+                kind: "decoded",
+                max: 4096
+            })
+        ));
     }
 }
@@ -0,0 +1,17 @@
+/// Upper bounds for received frames, a peer can't make us allocate more than this.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FrameLimits {
+    /// Bytes of a frame as sent, checked against the length prefix before reading
+    pub max_frame_size: usize,
+    /// Bytes of a frame after decompression
+    pub max_decoded_size: usize,
+}
+
+impl Default for FrameLimits {
+    fn default() -> Self {
+        Self {
+            max_frame_size: 16 * 1024 * 1024,
+            max_decoded_size: 64 * 1024 * 1024,
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::rpc::Method;
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// Names of all users.
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Serialize, Deserialize)]
+pub struct ListUsers;
+
+impl Method for ListUsers {
+    const NAME: &'static str = "list_users";
+    type Output = Vec<String>;
+}
+
+/// Hours of a user ordered by time, `from` is inclusive and `to` exclusive, both unix seconds.
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Serialize, Deserialize)]
+pub struct GetHourlyWeather {
+    pub user_id: String,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl Method for GetHourlyWeather {
+    const NAME: &'static str = "hourly_weather";
+    type Output = Vec<HourlyWeather>;
+}
+
+/// Hours of several users in [`HourlyWeatherChunk`]s, user after user and ordered by time, for
+/// ranges too large for a single response. `from` is inclusive and `to` exclusive, both unix
+/// seconds.
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Serialize, Deserialize)]
+pub struct GetHourlyWeatherChunks {
+    pub user_ids: Vec<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl Method for GetHourlyWeatherChunks {
+    const NAME: &'static str = "hourly_weather_chunks";
+    type Output = HourlyWeatherChunk;
+}
+
+/// Consecutive hours of a user, a user's hours may span several chunks.
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Serialize, Deserialize)]
+pub struct HourlyWeatherChunk {
+    pub user_id: String,
+    pub hours: Vec<HourlyWeather>,
+}
+
+/// Streams [`SubscriptionUpdate`]s of a user for as long as the connection lasts.
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Serialize, Deserialize)]
+pub struct Subscribe {
+    pub user_id: String,
+    pub kinds: Vec<DataKind>,
+}
+
+impl Method for Subscribe {
+    const NAME: &'static str = "subscribe";
+    type Output = SubscriptionUpdate;
+}
+
+/// What a subscription is about.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataKind {
+    HourlyWeather,
+    Alerts,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionUpdate {
+    /// The first update, the subscription is active from here on. Clients that resubscribe after
+    /// a reconnect receive it again, updates in between were missed.
+    Subscribed,
+    /// The forecast of the user was synced, these are its hours from the current one on
+    HourlyWeather {
+        user_id: String,
+        hours: Vec<HourlyWeather>,
+    },
+    Alert(Alert),
+}
+
+/// An alert rule of a user fired.
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Serialize, Deserialize)]
+pub struct Alert {
+    pub user_id: String,
+    pub rule_id: String,
+    /// The rule as written, e.g. `temperature_apparent < -5 °C within 12h`
+    pub rule: String,
+    pub message: String,
+    /// First forecast hour matching the rule, unix seconds
+    pub first_match: i64,
+    /// Unix seconds
+    pub fired_at: i64,
+}
+
+/// A stored hour of weather, values in the units of the database.
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Serialize, Deserialize)]
+pub struct HourlyWeather {
+    /// Unix seconds
+    pub time: i64,
+    pub wmo_code: u8,
+    /// °C
+    pub temperature: f32,
+    /// °C
+    pub apparent_temperature: f32,
+    /// 0-1
+    pub relative_humidity: f32,
+    /// °C
+    pub dew_point: f32,
+    /// hPa
+    pub surface_pressure: f32,
+    /// 0-1
+    pub cloud_cover: f32,
+    /// 0-1
+    pub cloud_cover_low: f32,
+    /// 0-1
+    pub cloud_cover_mid: f32,
+    /// 0-1
+    pub cloud_cover_high: f32,
+    /// km/h
+    pub wind_speed: f32,
+    /// km/h
+    pub max_wind_speed: f32,
+    /// Degrees
+    pub wind_direction: f32,
+    /// mm
+    pub total_precipitation: f64,
+    /// 0-1
+    pub precipitation_probability: f32,
+    /// mm
+    pub rain: f64,
+    /// mm
+    pub snowfall: f64,
+    /// cm
+    pub snow_depth: f64,
+    /// mm
+    pub showers: f64,
+    /// m
+    pub visibility: f64,
+    /// W/m²
+    pub shortwave_radiation: f32,
+}
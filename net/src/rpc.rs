@@ -0,0 +1,409 @@
+//! Requests and responses over a connection, correlated by id so that many can be in flight.
+//!
+//! Clients send [`RequestFrame`]s, servers answer each with one [`ServerFrame::Response`], or with
+//! any number of them followed by [`ServerFrame::End`] for streaming methods. Clients that lose
+//! interest in a request send [`ClientFrame::Cancel`], which stops its handler.
+//!
+//! Payloads are bincode, or JSON for clients that can't use bincode, see [`crate::json`].
+
+use crate::checksum::Checksums;
+use crate::codec::TypedCodec;
+use crate::compression::Compression;
+use crate::error::{NetError, NetResult};
+use crate::limits::FrameLimits;
+use bincode::{Decode, Encode};
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{Semaphore, mpsc};
+use tokio::task::{AbortHandle, JoinSet};
+use tokio_util::codec::{FramedRead, FramedWrite};
+use tracing::debug;
+
+/// Requests of a connection that are handled at the same time, further requests wait.
+const MAX_IN_FLIGHT: usize = 64;
+
+/// Frames queued for writing before handlers have to wait.
+const OUTGOING_CAPACITY: usize = 64;
+
+/// How often servers send [`ServerFrame::Heartbeat`].
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A request type of the protocol, the payload of a [`RequestFrame`].
+pub trait Method: Encode + Decode<()> + Serialize + DeserializeOwned + Send + 'static {
+    /// Name handlers are registered under
+    const NAME: &'static str;
+    /// Answer to the request, or a single item of the stream of a streaming method
+    type Output: Encode + Decode<()> + Serialize + DeserializeOwned + Send + 'static;
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct RequestFrame {
+    /// Chosen by the client, answers carry the id of their request
+    pub id: u64,
+    pub method: String,
+    /// The encoded [`Method`]
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub enum ClientFrame {
+    Request(RequestFrame),
+    /// Stops the handler of a request, e.g. a stream the client dropped. Nothing is answered,
+    /// responses already sent may still arrive.
+    Cancel {
+        id: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub enum ServerFrame {
+    /// The encoded [`Method::Output`] or why there is none
+    Response {
+        id: u64,
+        result: Result<Vec<u8>, RpcError>,
+    },
+    /// No further responses follow for a streaming request
+    End { id: u64 },
+    /// Sent every [`HEARTBEAT_INTERVAL`], clients consider a silent connection dead
+    Heartbeat,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    BadRequest,
+    UnknownMethod,
+    NotFound,
+    Internal,
+    /// The token of the client doesn't grant access to the data
+    Forbidden,
+}
+
+/// A request failed on the server.
+#[derive(Debug, Clone, PartialEq, Eq, Error, Encode, Decode, Serialize, Deserialize)]
+#[error("{code:?}: {message}")]
+pub struct RpcError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+pub fn encode_payload<T: Encode>(value: &T) -> NetResult<Vec<u8>> {
+    Ok(bincode::encode_to_vec(value, bincode::config::standard())?)
+}
+
+/// Decodes a payload, allocating no more than a few times its size whatever it claims.
+pub fn decode_payload<T: Decode<()>>(payload: &[u8]) -> NetResult<T> {
+    crate::decode_bounded(payload)
+}
+
+/// How the payloads of a connection are encoded, the frames around them are always bincode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PayloadFormat {
+    Bincode,
+    Json,
+}
+
+impl PayloadFormat {
+    pub fn encode<T: Encode + Serialize>(self, value: &T) -> NetResult<Vec<u8>> {
+        match self {
+            PayloadFormat::Bincode => encode_payload(value),
+            PayloadFormat::Json => Ok(serde_json::to_vec(value)?),
+        }
+    }
+
+    pub fn decode<T: Decode<()> + DeserializeOwned>(self, payload: &[u8]) -> NetResult<T> {
+        match self {
+            PayloadFormat::Bincode => decode_payload(payload),
+            PayloadFormat::Json => Ok(serde_json::from_slice(payload)?),
+        }
+    }
+}
+
+enum Reply {
+    Single(Vec<u8>),
+    Stream(BoxStream<'static, Result<Vec<u8>, RpcError>>),
+}
+
+type Handler = Box<
+    dyn Fn(PayloadFormat, Vec<u8>) -> BoxFuture<'static, Result<Reply, RpcError>> + Send + Sync,
+>;
+
+/// Handlers of the methods a server supports, by [`Method::NAME`].
+#[derive(Default)]
+pub struct Handlers {
+    handlers: HashMap<&'static str, Handler>,
+}
+
+impl Handlers {
+    /// Registers a method answered with a single response.
+    pub fn unary<M, F, Fut>(&mut self, handler: F)
+    where
+        M: Method,
+        F: Fn(M) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<M::Output, RpcError>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        self.insert::<M>(Box::new(move |format, payload| {
+            let handler = handler.clone();
+            Box::pin(async move {
+                let output = handler(decode_request(format, &payload)?).await?;
+                let encoded = format
+                    .encode(&output)
+                    .map_err(|e| RpcError::new(ErrorCode::Internal, e.to_string()))?;
+                Ok(Reply::Single(encoded))
+            })
+        }));
+    }
+
+    /// Registers a method answered with a response per item the handler sends, until it drops
+    /// the sender. The receiver is dropped when the connection closes.
+    ///
+    /// Items are taken from the receiver as fast as the client reads them, a bounded channel
+    /// makes the handler wait for a slow client instead of buffering its items.
+    pub fn stream<M, F, Fut>(&mut self, handler: F)
+    where
+        M: Method,
+        F: Fn(M) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<mpsc::Receiver<Result<M::Output, RpcError>>, RpcError>>
+            + Send
+            + 'static,
+    {
+        let handler = Arc::new(handler);
+        self.insert::<M>(Box::new(move |format, payload| {
+            let handler = handler.clone();
+            Box::pin(async move {
+                let receiver = handler(decode_request(format, &payload)?).await?;
+                let items = futures::stream::unfold(receiver, |mut receiver| async move {
+                    let item = receiver.recv().await?;
+                    Some((item, receiver))
+                })
+                .map(move |item| {
+                    item.and_then(|item| {
+                        format
+                            .encode(&item)
+                            .map_err(|e| RpcError::new(ErrorCode::Internal, e.to_string()))
+                    })
+                });
+                Ok(Reply::Stream(items.boxed()))
+            })
+        }));
+    }
+
+    fn insert<M: Method>(&mut self, handler: Handler) {
+        if self.handlers.insert(M::NAME, handler).is_some() {
+            panic!("Handler for method '{}' registered twice", M::NAME);
+        }
+    }
+
+    async fn dispatch(
+        &self,
+        method: &str,
+        format: PayloadFormat,
+        payload: Vec<u8>,
+    ) -> Result<Reply, RpcError> {
+        match self.handlers.get(method) {
+            Some(handler) => handler(format, payload).await,
+            None => Err(RpcError::new(
+                ErrorCode::UnknownMethod,
+                format!("Unknown method '{method}'"),
+            )),
+        }
+    }
+}
+
+fn decode_request<M: Method>(format: PayloadFormat, payload: &[u8]) -> Result<M, RpcError> {
+    format
+        .decode(payload)
+        .map_err(|e| RpcError::new(ErrorCode::BadRequest, e.to_string()))
+}
+
+/// Answers the requests of a connection until the client disconnects, frames are sent with
+/// `compression` and checked with `checksums`.
+pub async fn serve<R, W>(
+    reader: R,
+    writer: W,
+    handlers: Arc<Handlers>,
+    limits: FrameLimits,
+    compression: Compression,
+    checksums: Checksums,
+) -> NetResult<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let codec = TypedCodec::<ClientFrame, ServerFrame>::new(limits, compression, checksums);
+    let incoming = FramedRead::new(reader, codec.clone());
+    let outgoing = FramedWrite::new(writer, codec);
+    serve_frames(incoming, outgoing, handlers, PayloadFormat::Bincode).await
+}
+
+/// Answers the requests of a connection whose frames are carried by something other than a
+/// byte stream, like the messages of a WebSocket.
+pub async fn serve_frames<I, O>(
+    incoming: I,
+    writer: O,
+    handlers: Arc<Handlers>,
+    format: PayloadFormat,
+) -> NetResult<()>
+where
+    I: Stream<Item = NetResult<ClientFrame>>,
+    O: Sink<ServerFrame, Error = NetError> + Send + 'static,
+{
+    let mut incoming = std::pin::pin!(incoming);
+
+    let (outgoing, mut queued) = mpsc::channel::<ServerFrame>(OUTGOING_CAPACITY);
+    let mut writer_task = tokio::spawn(async move {
+        let mut writer = Box::pin(writer);
+        let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+        loop {
+            let frame = tokio::select! {
+                frame = queued.recv() => match frame {
+                    Some(frame) => frame,
+                    None => return NetResult::Ok(()),
+                },
+                _ = heartbeat.tick() => ServerFrame::Heartbeat,
+            };
+            writer.send(frame).await?;
+        }
+    });
+
+    // Requests are aborted when the client leaves or cancels them, streams included.
+    let mut requests = JoinSet::new();
+    let mut running = HashMap::<u64, AbortHandle>::new();
+    let in_flight = Arc::new(Semaphore::new(MAX_IN_FLIGHT));
+
+    let result = loop {
+        let frame = tokio::select! {
+            frame = incoming.next() => match frame {
+                Some(frame) => frame,
+                None => break Ok(()),
+            },
+            written = &mut writer_task => match written {
+                Ok(Err(e)) if !is_disconnect(&e) => break Err(e),
+                _ => break Ok(()),
+            },
+        };
+        let frame = match frame {
+            Ok(ClientFrame::Request(frame)) => frame,
+            Ok(ClientFrame::Cancel { id }) => {
+                if let Some(request) = running.remove(&id) {
+                    debug!("Request {id} cancelled");
+                    request.abort();
+                }
+                continue;
+            }
+            Err(e) if is_disconnect(&e) => break Ok(()),
+            Err(e) => break Err(e),
+        };
+        debug!("Request {} for '{}'", frame.id, frame.method);
+
+        let Ok(permit) = in_flight.clone().acquire_owned().await else {
+            break Ok(());
+        };
+        let handlers = handlers.clone();
+        let outgoing = outgoing.clone();
+        let id = frame.id;
+        let request = requests.spawn(async move {
+            let reply = handlers
+                .dispatch(&frame.method, format, frame.payload)
+                .await;
+            drop(permit);
+            match reply {
+                Ok(Reply::Single(payload)) => {
+                    let result = Ok(payload);
+                    let _ = outgoing.send(ServerFrame::Response { id, result }).await;
+                }
+                Ok(Reply::Stream(mut items)) => {
+                    while let Some(result) = items.next().await {
+                        if outgoing
+                            .send(ServerFrame::Response { id, result })
+                            .await
+                            .is_err()
+                        {
+                            return;
+                        }
+                    }
+                    let _ = outgoing.send(ServerFrame::End { id }).await;
+                }
+                Err(error) => {
+                    let result = Err(error);
+                    let _ = outgoing.send(ServerFrame::Response { id, result }).await;
+                }
+            }
+        });
+
+        running.insert(id, request);
+
+        // Finished requests are collected so the set doesn't grow with the connection.
+        while requests.try_join_next().is_some() {}
+        running.retain(|_, request| !request.is_finished());
+    };
+
+    requests.shutdown().await;
+    drop(outgoing);
+    if !writer_task.is_finished()
+        && let Ok(Err(e)) = writer_task.await
+        && !is_disconnect(&e)
+    {
+        return Err(e);
+    }
+    result
+}
+
+/// The peer went away, which ends a connection as usual.
+fn is_disconnect(e: &NetError) -> bool {
+    matches!(
+        e,
+        NetError::IO(e) if matches!(
+            e.kind(),
+            ErrorKind::UnexpectedEof | ErrorKind::BrokenPipe | ErrorKind::ConnectionReset
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::GetHourlyWeather;
+
+    #[test]
+    fn rejects_payloads_claiming_huge_allocations() {
+        // A user id of 32 TiB, which would abort the process if it was allocated
+        let mut payload = vec![0xfd];
+        payload.extend_from_slice(&(1u64 << 45).to_le_bytes());
+        let error = decode_payload::<GetHourlyWeather>(&payload).err();
+        assert!(matches!(error, Some(NetError::Decode(_))), "{error:?}");
+    }
+
+    #[test]
+    fn decodes_payloads_within_the_limit() {
+        let request = GetHourlyWeather {
+            user_id: "x".repeat(100_000),
+            from: Some(0),
+            to: None,
+        };
+        let payload = encode_payload(&request).unwrap();
+        assert_eq!(
+            decode_payload::<GetHourlyWeather>(&payload).unwrap(),
+            request
+        );
+    }
+}
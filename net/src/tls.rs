@@ -0,0 +1,198 @@
+//! Optional TLS around connections, the handshake and every frame go through it.
+//!
+//! Certificates and keys are read from PEM files, servers with a self-signed certificate are
+//! trusted by handing clients that certificate.
+
+use crate::error::{NetError, NetResult};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+pub use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use tokio_rustls::rustls::{ClientConfig, RootCertStore, ServerConfig, crypto};
+use tokio_rustls::{TlsConnector, client};
+
+/// Accepts TLS connections with the certificate chain and private key of PEM files.
+pub fn acceptor(certificate_path: &Path, private_key_path: &Path) -> NetResult<TlsAcceptor> {
+    let certificates = read_certificates(certificate_path)?;
+    let private_key = PrivateKeyDer::from_pem_file(private_key_path).map_err(|e| {
+        NetError::Tls(format!(
+            "failed to read private key '{}': {e}",
+            private_key_path.display()
+        ))
+    })?;
+
+    let config = ServerConfig::builder_with_provider(Arc::new(crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(tls_error)?
+        .with_no_client_auth()
+        .with_single_cert(certificates, private_key)
+        .map_err(tls_error)?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// How clients verify the server they connect to.
+#[derive(Clone)]
+pub struct ClientTls {
+    connector: TlsConnector,
+    server_name: ServerName<'static>,
+}
+
+impl ClientTls {
+    /// Trusts servers with a certificate for `server_name` issued by one of `roots`, or being one
+    /// of them.
+    pub fn new(server_name: &str, roots: Vec<CertificateDer<'static>>) -> NetResult<Self> {
+        let server_name = ServerName::try_from(server_name.to_string())
+            .map_err(|e| NetError::Tls(format!("invalid server name '{server_name}': {e}")))?;
+        let mut root_store = RootCertStore::empty();
+        for root in roots {
+            root_store.add(root).map_err(tls_error)?;
+        }
+
+        let config =
+            ClientConfig::builder_with_provider(Arc::new(crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .map_err(tls_error)?
+                .with_root_certificates(root_store)
+                .with_no_client_auth();
+        Ok(Self {
+            connector: TlsConnector::from(Arc::new(config)),
+            server_name,
+        })
+    }
+
+    /// Trusts the certificates of a PEM file, e.g. the self-signed certificate of the server.
+    pub fn from_pem_file(server_name: &str, path: &Path) -> NetResult<Self> {
+        Self::new(server_name, read_certificates(path)?)
+    }
+
+    pub(crate) async fn connect(
+        &self,
+        stream: TcpStream,
+    ) -> NetResult<client::TlsStream<TcpStream>> {
+        Ok(self
+            .connector
+            .connect(self.server_name.clone(), stream)
+            .await?)
+    }
+}
+
+fn read_certificates(path: &Path) -> NetResult<Vec<CertificateDer<'static>>> {
+    let read_error = |e| {
+        NetError::Tls(format!(
+            "failed to read certificates '{}': {e}",
+            path.display()
+        ))
+    };
+    let certificates = CertificateDer::pem_file_iter(path)
+        .map_err(read_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(read_error)?;
+    if certificates.is_empty() {
+        return Err(NetError::Tls(format!(
+            "no certificates in '{}'",
+            path.display()
+        )));
+    }
+    Ok(certificates)
+}
+
+fn tls_error(e: tokio_rustls::rustls::Error) -> NetError {
+    NetError::Tls(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum::Checksums;
+    use crate::client::{Client, ClientOptions};
+    use crate::compression::Compression;
+    use crate::handshake::{self, Codec};
+    use crate::limits::FrameLimits;
+    use crate::protocol::ListUsers;
+    use crate::rpc::{self, Handlers};
+    use std::path::PathBuf;
+    use tokio::net::TcpListener;
+
+    /// A self-signed certificate for `localhost` and its key, as PEM files in a new directory.
+    fn self_signed(name: &str) -> (PathBuf, PathBuf) {
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let directory =
+            std::env::temp_dir().join(format!("omnistat-net-tls-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let certificate = directory.join("certificate.pem");
+        let private_key = directory.join("private-key.pem");
+        std::fs::write(&certificate, certified.cert.pem()).unwrap();
+        std::fs::write(&private_key, certified.signing_key.serialize_pem()).unwrap();
+        (certificate, private_key)
+    }
+
+    /// Serves `list_users` through TLS on a local port, returns its address.
+    async fn server(acceptor: TlsAcceptor) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut handlers = Handlers::default();
+        handlers.unary(|_: ListUsers| async { Ok(vec!["user".to_string()]) });
+        let handlers = Arc::new(handlers);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                let handlers = handlers.clone();
+                tokio::spawn(async move {
+                    let Ok(mut stream) = acceptor.accept(stream).await else {
+                        return;
+                    };
+                    handshake::accept(&mut stream, Codec::Zstd, |token| token == Some("secret"))
+                        .await
+                        .unwrap();
+                    let (reader, writer) = tokio::io::split(stream);
+                    let _ = rpc::serve(
+                        reader,
+                        writer,
+                        handlers,
+                        FrameLimits::default(),
+                        Compression::default(),
+                        Checksums::default(),
+                    )
+                    .await;
+                });
+            }
+        });
+        address
+    }
+
+    fn options(tls: ClientTls) -> ClientOptions {
+        ClientOptions {
+            token: Some("secret".to_string()),
+            tls: Some(tls),
+            ..ClientOptions::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trips_with_a_self_signed_certificate() {
+        let (certificate, private_key) = self_signed("round-trip");
+        let address = server(acceptor(&certificate, &private_key).unwrap()).await;
+        let tls = ClientTls::from_pem_file("localhost", &certificate).unwrap();
+
+        let client = Client::connect(&address, options(tls)).await.unwrap();
+        assert_eq!(client.list_users().await.unwrap(), vec!["user"]);
+    }
+
+    #[tokio::test]
+    async fn rejects_untrusted_certificates() {
+        let (certificate, private_key) = self_signed("server");
+        let (other, _) = self_signed("other");
+        let address = server(acceptor(&certificate, &private_key).unwrap()).await;
+
+        let untrusted = ClientTls::from_pem_file("localhost", &other).unwrap();
+        assert!(Client::connect(&address, options(untrusted)).await.is_err());
+        let wrong_name = ClientTls::from_pem_file("example.com", &certificate).unwrap();
+        assert!(
+            Client::connect(&address, options(wrong_name))
+                .await
+                .is_err()
+        );
+    }
+}
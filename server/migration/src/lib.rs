@@ -1,12 +1,26 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20251102_104720_initial_weather;
+mod m20251108_153012_daily_weather;
+mod m20251116_091544_alert_event;
+mod m20251122_140210_webhook_delivery;
+mod m20251129_083406_hourly_weather_rollup;
+mod m20251206_101530_climate;
+mod m20251213_164205_data_quality;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20251102_104720_initial_weather::Migration)]
+        vec![
+            Box::new(m20251102_104720_initial_weather::Migration),
+            Box::new(m20251108_153012_daily_weather::Migration),
+            Box::new(m20251116_091544_alert_event::Migration),
+            Box::new(m20251122_140210_webhook_delivery::Migration),
+            Box::new(m20251129_083406_hourly_weather_rollup::Migration),
+            Box::new(m20251206_101530_climate::Migration),
+            Box::new(m20251213_164205_data_quality::Migration),
+        ]
     }
 }
@@ -0,0 +1,86 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(DailyWeather::Table)
+                    .if_not_exists()
+                    .col(string(DailyWeather::UserId))
+                    .col(date(DailyWeather::Date))
+                    .col(string(DailyWeather::Timezone))
+                    .col(integer(DailyWeather::WmoCode))
+                    .col(float(DailyWeather::TemperatureMax))
+                    .col(float(DailyWeather::TemperatureMean))
+                    .col(float(DailyWeather::TemperatureMin))
+                    .col(float(DailyWeather::TemperatureApparentMax))
+                    .col(float(DailyWeather::TemperatureApparentMean))
+                    .col(float(DailyWeather::TemperatureApparentMin))
+                    .col(double(DailyWeather::PrecipitationSum))
+                    .col(double(DailyWeather::RainSum))
+                    .col(double(DailyWeather::ShowersSum))
+                    .col(double(DailyWeather::SnowfallSum))
+                    .col(float(DailyWeather::PrecipitationHours))
+                    .col(float(DailyWeather::PrecipitationProbabilityMax))
+                    .col(timestamp(DailyWeather::SunriseUtc))
+                    .col(timestamp(DailyWeather::SunsetUtc))
+                    .col(float(DailyWeather::SunshineDuration))
+                    .col(float(DailyWeather::DaylightDuration))
+                    .col(float(DailyWeather::WindSpeedMax))
+                    .col(float(DailyWeather::WindGustsMax))
+                    .col(float(DailyWeather::WindDirectionDominant))
+                    .col(float(DailyWeather::UvIndexMax))
+                    .col(float(DailyWeather::UvIndexClearSkyMax))
+                    .col(float(DailyWeather::ShortwaveRadiationSum))
+                    .primary_key(
+                        Index::create()
+                            .col(DailyWeather::UserId)
+                            .col(DailyWeather::Date),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DailyWeather::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum DailyWeather {
+    Table,
+    UserId,
+    Date,
+    Timezone,
+    WmoCode,
+    TemperatureMax,
+    TemperatureMean,
+    TemperatureMin,
+    TemperatureApparentMax,
+    TemperatureApparentMean,
+    TemperatureApparentMin,
+    PrecipitationSum,
+    RainSum,
+    ShowersSum,
+    SnowfallSum,
+    PrecipitationHours,
+    PrecipitationProbabilityMax,
+    SunriseUtc,
+    SunsetUtc,
+    SunshineDuration,
+    DaylightDuration,
+    WindSpeedMax,
+    WindGustsMax,
+    WindDirectionDominant,
+    UvIndexMax,
+    UvIndexClearSkyMax,
+    ShortwaveRadiationSum,
+}
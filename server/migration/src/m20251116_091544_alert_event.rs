@@ -0,0 +1,53 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AlertEvent::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AlertEvent::Id))
+                    .col(string(AlertEvent::UserId))
+                    .col(string(AlertEvent::RuleId))
+                    .col(timestamp(AlertEvent::FirstMatchUtc))
+                    .col(string(AlertEvent::Message))
+                    .col(timestamp(AlertEvent::FiredAtUtc))
+                    .col(timestamp_null(AlertEvent::ResolvedAtUtc))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_alert_event_user_rule")
+                    .table(AlertEvent::Table)
+                    .col(AlertEvent::UserId)
+                    .col(AlertEvent::RuleId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AlertEvent::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AlertEvent {
+    Table,
+    Id,
+    UserId,
+    RuleId,
+    FirstMatchUtc,
+    Message,
+    FiredAtUtc,
+    ResolvedAtUtc,
+}
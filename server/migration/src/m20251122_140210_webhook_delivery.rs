@@ -0,0 +1,59 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookDelivery::Table)
+                    .if_not_exists()
+                    .col(pk_auto(WebhookDelivery::Id))
+                    .col(string(WebhookDelivery::Webhook))
+                    .col(string(WebhookDelivery::Event))
+                    .col(text(WebhookDelivery::Payload))
+                    .col(string(WebhookDelivery::Status))
+                    .col(integer(WebhookDelivery::Attempts))
+                    .col(timestamp(WebhookDelivery::NextAttemptAtUtc))
+                    .col(text_null(WebhookDelivery::LastError))
+                    .col(timestamp(WebhookDelivery::CreatedAtUtc))
+                    .col(timestamp_null(WebhookDelivery::DeliveredAtUtc))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_delivery_status_next_attempt")
+                    .table(WebhookDelivery::Table)
+                    .col(WebhookDelivery::Status)
+                    .col(WebhookDelivery::NextAttemptAtUtc)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebhookDelivery::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WebhookDelivery {
+    Table,
+    Id,
+    Webhook,
+    Event,
+    Payload,
+    Status,
+    Attempts,
+    NextAttemptAtUtc,
+    LastError,
+    CreatedAtUtc,
+    DeliveredAtUtc,
+}
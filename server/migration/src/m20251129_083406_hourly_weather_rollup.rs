@@ -0,0 +1,112 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(HourlyWeatherRollup::Table)
+                    .if_not_exists()
+                    .col(string(HourlyWeatherRollup::UserId))
+                    .col(date(HourlyWeatherRollup::Date))
+                    .col(string(HourlyWeatherRollup::Timezone))
+                    .col(integer(HourlyWeatherRollup::Hours))
+                    .col(integer(HourlyWeatherRollup::WmoCode))
+                    .col(float(HourlyWeatherRollup::TemperatureMin))
+                    .col(float(HourlyWeatherRollup::TemperatureMean))
+                    .col(float(HourlyWeatherRollup::TemperatureMax))
+                    .col(float(HourlyWeatherRollup::TemperatureApparentMin))
+                    .col(float(HourlyWeatherRollup::TemperatureApparentMean))
+                    .col(float(HourlyWeatherRollup::TemperatureApparentMax))
+                    .col(float(HourlyWeatherRollup::RelativeHumidityMin))
+                    .col(float(HourlyWeatherRollup::RelativeHumidityMean))
+                    .col(float(HourlyWeatherRollup::RelativeHumidityMax))
+                    .col(float(HourlyWeatherRollup::DewPointMin))
+                    .col(float(HourlyWeatherRollup::DewPointMean))
+                    .col(float(HourlyWeatherRollup::DewPointMax))
+                    .col(float(HourlyWeatherRollup::SurfacePressureMin))
+                    .col(float(HourlyWeatherRollup::SurfacePressureMean))
+                    .col(float(HourlyWeatherRollup::SurfacePressureMax))
+                    .col(float(HourlyWeatherRollup::CloudCoverMin))
+                    .col(float(HourlyWeatherRollup::CloudCoverMean))
+                    .col(float(HourlyWeatherRollup::CloudCoverMax))
+                    .col(float(HourlyWeatherRollup::WindSpeedMin))
+                    .col(float(HourlyWeatherRollup::WindSpeedMean))
+                    .col(float(HourlyWeatherRollup::WindSpeedMax))
+                    .col(float(HourlyWeatherRollup::MaxWindSpeedMax))
+                    .col(float(HourlyWeatherRollup::WindDirectionMean))
+                    .col(double(HourlyWeatherRollup::TotalPrecipitationSum))
+                    .col(double(HourlyWeatherRollup::RainSum))
+                    .col(double(HourlyWeatherRollup::ShowersSum))
+                    .col(double(HourlyWeatherRollup::SnowfallSum))
+                    .col(float(HourlyWeatherRollup::PrecipitationProbabilityMax))
+                    .col(double(HourlyWeatherRollup::SnowDepthMax))
+                    .col(double(HourlyWeatherRollup::VisibilityMin))
+                    .col(double(HourlyWeatherRollup::VisibilityMean))
+                    .col(double(HourlyWeatherRollup::VisibilityMax))
+                    .col(float(HourlyWeatherRollup::ShortwaveRadiationMean))
+                    .col(float(HourlyWeatherRollup::ShortwaveRadiationMax))
+                    .primary_key(
+                        Index::create()
+                            .col(HourlyWeatherRollup::UserId)
+                            .col(HourlyWeatherRollup::Date),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(HourlyWeatherRollup::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum HourlyWeatherRollup {
+    Table,
+    UserId,
+    Date,
+    Timezone,
+    Hours,
+    WmoCode,
+    TemperatureMin,
+    TemperatureMean,
+    TemperatureMax,
+    TemperatureApparentMin,
+    TemperatureApparentMean,
+    TemperatureApparentMax,
+    RelativeHumidityMin,
+    RelativeHumidityMean,
+    RelativeHumidityMax,
+    DewPointMin,
+    DewPointMean,
+    DewPointMax,
+    SurfacePressureMin,
+    SurfacePressureMean,
+    SurfacePressureMax,
+    CloudCoverMin,
+    CloudCoverMean,
+    CloudCoverMax,
+    WindSpeedMin,
+    WindSpeedMean,
+    WindSpeedMax,
+    MaxWindSpeedMax,
+    WindDirectionMean,
+    TotalPrecipitationSum,
+    RainSum,
+    ShowersSum,
+    SnowfallSum,
+    PrecipitationProbabilityMax,
+    SnowDepthMax,
+    VisibilityMin,
+    VisibilityMean,
+    VisibilityMax,
+    ShortwaveRadiationMean,
+    ShortwaveRadiationMax,
+}
@@ -0,0 +1,167 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClimateMonth::Table)
+                    .if_not_exists()
+                    .col(string(ClimateMonth::UserId))
+                    .col(integer(ClimateMonth::Year))
+                    .col(integer(ClimateMonth::Month))
+                    .col(integer(ClimateMonth::Days))
+                    .col(double(ClimateMonth::TemperatureMinSum))
+                    .col(double(ClimateMonth::TemperatureMeanSum))
+                    .col(double(ClimateMonth::TemperatureMaxSum))
+                    .col(double(ClimateMonth::PrecipitationSum))
+                    .col(double(ClimateMonth::HeatingDegreeDays))
+                    .col(double(ClimateMonth::CoolingDegreeDays))
+                    .col(integer(ClimateMonth::FrostDays))
+                    .col(integer(ClimateMonth::DryDays))
+                    .primary_key(
+                        Index::create()
+                            .col(ClimateMonth::UserId)
+                            .col(ClimateMonth::Year)
+                            .col(ClimateMonth::Month),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClimateDayNormal::Table)
+                    .if_not_exists()
+                    .col(string(ClimateDayNormal::UserId))
+                    .col(integer(ClimateDayNormal::Month))
+                    .col(integer(ClimateDayNormal::Day))
+                    .col(integer(ClimateDayNormal::Samples))
+                    .col(double(ClimateDayNormal::TemperatureMinSum))
+                    .col(double(ClimateDayNormal::TemperatureMeanSum))
+                    .col(double(ClimateDayNormal::TemperatureMaxSum))
+                    .col(double(ClimateDayNormal::PrecipitationSum))
+                    .primary_key(
+                        Index::create()
+                            .col(ClimateDayNormal::UserId)
+                            .col(ClimateDayNormal::Month)
+                            .col(ClimateDayNormal::Day),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClimateRecord::Table)
+                    .if_not_exists()
+                    .col(string(ClimateRecord::UserId))
+                    .col(integer(ClimateRecord::Month))
+                    .col(string(ClimateRecord::Kind))
+                    .col(double(ClimateRecord::Value))
+                    .col(date(ClimateRecord::Date))
+                    .col(timestamp_null(ClimateRecord::TimeUtc))
+                    .primary_key(
+                        Index::create()
+                            .col(ClimateRecord::UserId)
+                            .col(ClimateRecord::Month)
+                            .col(ClimateRecord::Kind),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClimateState::Table)
+                    .if_not_exists()
+                    .col(string(ClimateState::UserId).primary_key())
+                    .col(date(ClimateState::ProcessedThrough))
+                    .col(integer(ClimateState::DryStreakDays))
+                    .col(date_null(ClimateState::DryStreakStart))
+                    .col(integer(ClimateState::LongestDryStreakDays))
+                    .col(date_null(ClimateState::LongestDryStreakStart))
+                    .col(integer(ClimateState::FrostStreakDays))
+                    .col(date_null(ClimateState::FrostStreakStart))
+                    .col(integer(ClimateState::LongestFrostStreakDays))
+                    .col(date_null(ClimateState::LongestFrostStreakStart))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ClimateMonth::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ClimateDayNormal::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ClimateRecord::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ClimateState::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ClimateMonth {
+    Table,
+    UserId,
+    Year,
+    Month,
+    Days,
+    TemperatureMinSum,
+    TemperatureMeanSum,
+    TemperatureMaxSum,
+    PrecipitationSum,
+    HeatingDegreeDays,
+    CoolingDegreeDays,
+    FrostDays,
+    DryDays,
+}
+
+#[derive(DeriveIden)]
+enum ClimateDayNormal {
+    Table,
+    UserId,
+    Month,
+    Day,
+    Samples,
+    TemperatureMinSum,
+    TemperatureMeanSum,
+    TemperatureMaxSum,
+    PrecipitationSum,
+}
+
+#[derive(DeriveIden)]
+enum ClimateRecord {
+    Table,
+    UserId,
+    Month,
+    Kind,
+    Value,
+    Date,
+    TimeUtc,
+}
+
+#[derive(DeriveIden)]
+enum ClimateState {
+    Table,
+    UserId,
+    ProcessedThrough,
+    DryStreakDays,
+    DryStreakStart,
+    LongestDryStreakDays,
+    LongestDryStreakStart,
+    FrostStreakDays,
+    FrostStreakStart,
+    LongestFrostStreakDays,
+    LongestFrostStreakStart,
+}
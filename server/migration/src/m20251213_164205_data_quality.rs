@@ -0,0 +1,50 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(DataQuality::Table)
+                    .if_not_exists()
+                    .col(string(DataQuality::UserId))
+                    .col(timestamp(DataQuality::TimeUtc))
+                    .col(string(DataQuality::Checks))
+                    .col(text(DataQuality::Message))
+                    .col(text(DataQuality::Payload))
+                    .col(integer(DataQuality::Occurrences))
+                    .col(timestamp(DataQuality::FirstFlaggedAtUtc))
+                    .col(timestamp(DataQuality::LastFlaggedAtUtc))
+                    .primary_key(
+                        Index::create()
+                            .col(DataQuality::UserId)
+                            .col(DataQuality::TimeUtc),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DataQuality::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum DataQuality {
+    Table,
+    UserId,
+    TimeUtc,
+    Checks,
+    Message,
+    Payload,
+    Occurrences,
+    FirstFlaggedAtUtc,
+    LastFlaggedAtUtc,
+}
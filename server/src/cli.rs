@@ -0,0 +1,86 @@
+use crate::config::check::run_check_config;
+use clap::{Parser, Subcommand};
+
+mod alerts;
+mod climate;
+mod digest;
+mod export;
+mod migrate;
+mod net_dictionary;
+mod retention;
+mod sync;
+mod users;
+
+#[derive(Parser)]
+#[command(
+    name = "omnistat-server",
+    version,
+    about = "Omnistat server and maintenance tools"
+)]
+pub struct Cli {
+    /// Defaults to `serve`
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run the server and its scheduled jobs
+    Serve,
+    /// Apply, roll back or inspect database migrations
+    Migrate {
+        #[command(subcommand)]
+        command: migrate::MigrateCommand,
+    },
+    /// Sync data from the upstream APIs right away
+    Sync {
+        #[command(subcommand)]
+        command: sync::SyncCommand,
+    },
+    /// Fill in hourly weather of the past days
+    Backfill(sync::BackfillArgs),
+    /// Evaluate alert rules or try out notifiers
+    Alerts {
+        #[command(subcommand)]
+        command: alerts::AlertsCommand,
+    },
+    /// Update or rebuild the climate statistics
+    Climate {
+        #[command(subcommand)]
+        command: climate::ClimateCommand,
+    },
+    /// Render the daily weather digest of a user
+    Digest(digest::DigestArgs),
+    /// Export stored hourly weather of a user
+    Export(export::ExportArgs),
+    /// Roll up and remove hourly weather older than the retention period
+    Retention(retention::RetentionArgs),
+    /// Train the compression dictionary of the net protocol on stored hourly weather
+    NetDictionary(net_dictionary::NetDictionaryArgs),
+    /// List, add or remove users in the config file
+    Users {
+        #[command(subcommand)]
+        command: users::UsersCommand,
+    },
+    /// Validate the config file and report all problems
+    CheckConfig,
+}
+
+impl Command {
+    pub async fn run(self) -> anyhow::Result<()> {
+        match self {
+            Command::Serve => crate::serve().await,
+            Command::Migrate { command } => command.run().await,
+            Command::Sync { command } => command.run().await,
+            Command::Backfill(args) => args.run().await,
+            Command::Alerts { command } => command.run().await,
+            Command::Climate { command } => command.run().await,
+            Command::Digest(args) => args.run().await,
+            Command::Export(args) => args.run().await,
+            Command::Retention(args) => args.run().await,
+            Command::NetDictionary(args) => args.run().await,
+            Command::Users { command } => command.run(),
+            Command::CheckConfig => std::process::exit(run_check_config()),
+        }
+    }
+}
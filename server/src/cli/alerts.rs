@@ -0,0 +1,39 @@
+use crate::state::ServerState;
+use clap::Subcommand;
+use tracing::info;
+
+#[derive(Subcommand)]
+pub enum AlertsCommand {
+    /// Evaluate alert rules against the stored forecast and deliver what fires
+    Evaluate {
+        /// Only evaluate this user, all users if omitted
+        #[arg(long)]
+        user: Option<String>,
+    },
+    /// Send a test notification through a notifier of the config
+    TestNotifier { name: String },
+}
+
+impl AlertsCommand {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let state = ServerState::initialize().await?;
+
+        let alert = &state.services.alert;
+        match self {
+            AlertsCommand::Evaluate { user: Some(user) } => {
+                let fired = alert.evaluate_user(&user).await?;
+                alert.wait_for_deliveries().await;
+                info!("Fired {fired} alert(s) for user '{user}'");
+            }
+            AlertsCommand::Evaluate { user: None } => {
+                alert.evaluate_all().await;
+                alert.wait_for_deliveries().await;
+            }
+            AlertsCommand::TestNotifier { name } => {
+                alert.test_notifier(&name).await?;
+                info!("Sent test notification through '{name}'");
+            }
+        }
+        Ok(())
+    }
+}
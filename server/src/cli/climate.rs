@@ -0,0 +1,40 @@
+use crate::state::ServerState;
+use clap::Subcommand;
+use tracing::info;
+
+#[derive(Subcommand)]
+pub enum ClimateCommand {
+    /// Add the local days completed since the last update
+    Update {
+        /// Only update this user, all users if omitted
+        #[arg(long)]
+        user: Option<String>,
+    },
+    /// Recompute the statistics from all stored weather, e.g. after a backfill
+    Rebuild {
+        #[arg(long)]
+        user: String,
+    },
+}
+
+impl ClimateCommand {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let state = ServerState::initialize().await?;
+
+        let climate = &state.services.climate;
+        match self {
+            ClimateCommand::Update { user: Some(user) } => {
+                state.config.load().get_user_or_err(&user)?;
+                let days = climate.update_user(&user).await?;
+                info!("Added {days} day(s) to the climate of user '{user}'");
+            }
+            ClimateCommand::Update { user: None } => climate.update_all().await,
+            ClimateCommand::Rebuild { user } => {
+                state.config.load().get_user_or_err(&user)?;
+                let days = climate.rebuild_user(&user).await?;
+                info!("Rebuilt the climate of user '{user}' from {days} day(s)");
+            }
+        }
+        Ok(())
+    }
+}
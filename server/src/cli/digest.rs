@@ -0,0 +1,34 @@
+use crate::services::digest::DigestFormat;
+use crate::services::export::column::UnitSystem;
+use crate::state::ServerState;
+use chrono::NaiveDate;
+use clap::Args;
+
+#[derive(Args)]
+pub struct DigestArgs {
+    #[arg(long)]
+    user: String,
+    /// Local day of the user, today if omitted
+    #[arg(long)]
+    date: Option<NaiveDate>,
+    #[arg(long, value_enum, default_value_t)]
+    format: DigestFormat,
+    #[arg(long, value_enum, default_value_t)]
+    units: UnitSystem,
+}
+
+impl DigestArgs {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let state = ServerState::initialize().await?;
+        state.config.load().get_user_or_err(&self.user)?;
+
+        let digest = state
+            .services
+            .digest
+            .digest(&self.user, self.date, self.units)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("No weather stored for user '{}'", self.user))?;
+        print!("{}", state.services.digest.render(&digest, self.format)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use crate::services::export::column::UnitSystem;
+use crate::services::export::format::ExportFormat;
+use crate::services::export::{ExportRequest, day_bounds};
+use crate::state::ServerState;
+use chrono::NaiveDate;
+use clap::Args;
+use futures::StreamExt;
+use std::path::PathBuf;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tracing::info;
+
+#[derive(Args)]
+pub struct ExportArgs {
+    #[arg(long)]
+    user: String,
+    /// First day to export (UTC), from the beginning if omitted
+    #[arg(long)]
+    from: Option<NaiveDate>,
+    /// Last day to export (UTC, inclusive), until the end if omitted
+    #[arg(long)]
+    to: Option<NaiveDate>,
+    #[arg(long, value_enum, default_value_t)]
+    format: ExportFormat,
+    #[arg(long, value_enum, default_value_t)]
+    units: UnitSystem,
+    /// File to write to, stdout if omitted
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+impl ExportArgs {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let state = ServerState::initialize().await?;
+        state.config.load().get_user_or_err(&self.user)?;
+
+        let (from, to) = day_bounds(self.from, self.to);
+        let request = ExportRequest {
+            user_id: self.user.clone(),
+            from,
+            to,
+            format: self.format,
+            units: self.units,
+        };
+        let stream = state.services.export.export_hourly_weather(request)?;
+
+        let bytes = match &self.output {
+            Some(path) => write_all(stream, tokio::fs::File::create(path).await?).await?,
+            None => write_all(stream, tokio::io::stdout()).await?,
+        };
+
+        info!("Exported {bytes} bytes of user '{}'", self.user);
+        Ok(())
+    }
+}
+
+async fn write_all<S, W>(stream: S, mut writer: W) -> anyhow::Result<usize>
+where
+    S: futures::Stream<Item = anyhow::Result<Vec<u8>>>,
+    W: AsyncWrite + Unpin,
+{
+    let mut stream = std::pin::pin!(stream);
+    let mut bytes = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        writer.write_all(&chunk).await?;
+        bytes += chunk.len();
+    }
+    writer.flush().await?;
+    Ok(bytes)
+}
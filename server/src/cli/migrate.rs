@@ -0,0 +1,37 @@
+use crate::config::Config;
+use crate::state::ServerState;
+use clap::Subcommand;
+use migration::{Migrator, MigratorTrait};
+
+#[derive(Subcommand)]
+pub enum MigrateCommand {
+    /// Apply pending migrations
+    Up {
+        /// Amount of migrations to apply, all pending ones if omitted
+        #[arg(short = 'n', long)]
+        steps: Option<u32>,
+    },
+    /// Roll back applied migrations
+    Down {
+        /// Amount of migrations to roll back
+        #[arg(short = 'n', long, default_value_t = 1)]
+        steps: u32,
+    },
+    /// Show the status of all migrations
+    Status,
+}
+
+impl MigrateCommand {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let config = Config::load_from_env()?;
+        let db = ServerState::connect_db(&config).await?;
+
+        match self {
+            MigrateCommand::Up { steps } => Migrator::up(&db, steps).await?,
+            MigrateCommand::Down { steps } => Migrator::down(&db, Some(steps)).await?,
+            MigrateCommand::Status => Migrator::status(&db).await?,
+        }
+
+        Ok(())
+    }
+}
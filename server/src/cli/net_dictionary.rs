@@ -0,0 +1,36 @@
+use crate::state::ServerState;
+use clap::Args;
+use omnistat_net::compression::train_dictionary;
+use std::path::PathBuf;
+use tracing::info;
+
+#[derive(Args)]
+pub struct NetDictionaryArgs {
+    /// File to write the dictionary to, `net/dictionaries/weather.zdict` is bundled with the
+    /// protocol
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Largest size of the dictionary in bytes
+    #[arg(long, default_value_t = 16 * 1024)]
+    max_size: usize,
+}
+
+impl NetDictionaryArgs {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let state = ServerState::initialize().await?;
+        let samples = state.services.net.dictionary_samples().await?;
+        if samples.is_empty() {
+            anyhow::bail!("No hourly weather stored to train on");
+        }
+
+        let dictionary = train_dictionary(&samples, self.max_size)?;
+        std::fs::write(&self.output, &dictionary)?;
+        info!(
+            "Trained a dictionary of {} bytes on {} responses, written to '{}'",
+            dictionary.len(),
+            samples.len(),
+            self.output.display()
+        );
+        Ok(())
+    }
+}
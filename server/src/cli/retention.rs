@@ -0,0 +1,55 @@
+use crate::state::ServerState;
+use clap::Args;
+use tracing::info;
+
+#[derive(Args)]
+pub struct RetentionArgs {
+    /// Only apply to this user, all users if omitted
+    #[arg(long)]
+    user: Option<String>,
+    /// Report what would be rolled up without changing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl RetentionArgs {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let state = ServerState::initialize().await?;
+
+        let retention = &state.services.retention;
+        let reports = match &self.user {
+            Some(user) => {
+                state.config.load().get_user_or_err(user)?;
+                vec![retention.run_user(user, self.dry_run).await?]
+            }
+            None => {
+                if state.config.load().retention.hourly_days.is_none() {
+                    anyhow::bail!("No retention is configured, set retention.hourly_days");
+                }
+                retention.run(self.dry_run).await
+            }
+        };
+
+        let verb = if self.dry_run {
+            "Would roll up"
+        } else {
+            "Rolled up"
+        };
+        for report in reports {
+            info!(
+                "{verb} {} hour(s) of user '{}' before {} into {} day(s)",
+                report.hours, report.user_id, report.cutoff, report.days
+            );
+            if report.resynced_hours > 0 {
+                info!(
+                    "  and {} hour(s) synced again for days rolled up before",
+                    report.resynced_hours
+                );
+            }
+            for path in report.archived_to {
+                info!("  archived to {}", path.display());
+            }
+        }
+        Ok(())
+    }
+}
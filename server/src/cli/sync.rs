@@ -0,0 +1,73 @@
+use crate::state::ServerState;
+use clap::{Args, Subcommand};
+use tracing::info;
+
+#[derive(Subcommand)]
+pub enum SyncCommand {
+    /// Sync the hourly and daily weather forecast
+    Weather {
+        /// Only sync this user, all users if omitted
+        #[arg(long)]
+        user: Option<String>,
+    },
+}
+
+impl SyncCommand {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let state = ServerState::initialize().await?;
+        // Alerts, climate statistics and webhooks react to the syncs as they would in the server
+        let mut events = state.services.subscribe();
+
+        let weather = &state.services.weather;
+        match self {
+            SyncCommand::Weather { user: Some(user) } => {
+                weather.sync_hourly_weather_user(&user).await?;
+                weather.sync_daily_weather_user(&user).await?;
+                info!("Synced weather for user '{user}'");
+            }
+            SyncCommand::Weather { user: None } => {
+                weather.sync_hourly_weather().await;
+                weather.sync_daily_weather().await;
+            }
+        }
+
+        state.services.handle_events(&mut events).await;
+        Ok(())
+    }
+}
+
+#[derive(Args)]
+pub struct BackfillArgs {
+    /// Only backfill this user, all users if omitted
+    #[arg(long)]
+    user: Option<String>,
+    /// Amount of past days to fetch, at most 92
+    #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(u8).range(1..=92))]
+    days: u8,
+}
+
+impl BackfillArgs {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let state = ServerState::initialize().await?;
+        let mut events = state.services.subscribe();
+        let user_ids = match self.user {
+            Some(user) => vec![user],
+            None => state.config.load().users.keys().cloned().collect(),
+        };
+
+        for user_id in user_ids {
+            let count = state
+                .services
+                .weather
+                .backfill_hourly_weather_user(&user_id, self.days)
+                .await?;
+            info!(
+                "Backfilled {count} hours of the past {} days for user '{user_id}'",
+                self.days
+            );
+        }
+
+        state.services.handle_events(&mut events).await;
+        Ok(())
+    }
+}
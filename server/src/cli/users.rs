@@ -0,0 +1,110 @@
+use crate::config::Config;
+use anyhow::{Context, bail};
+use clap::Subcommand;
+use omnistat_core::types::latitude::Latitude;
+use omnistat_core::types::longitude::Longitude;
+use std::path::Path;
+use toml_edit::{DocumentMut, Item, Table, value};
+
+/// Edits the config file in place, a running server picks the changes up through hot reload.
+#[derive(Subcommand)]
+pub enum UsersCommand {
+    /// List all users with their location
+    List,
+    /// Add a user with a location
+    Add {
+        /// Id of the new user, a random UUID if omitted
+        #[arg(long)]
+        id: Option<String>,
+        #[arg(long, allow_hyphen_values = true)]
+        latitude: f64,
+        #[arg(long, allow_hyphen_values = true)]
+        longitude: f64,
+    },
+    /// Remove a user
+    Remove { id: String },
+}
+
+impl UsersCommand {
+    pub fn run(self) -> anyhow::Result<()> {
+        let path = Config::path_from_env()?;
+
+        match self {
+            UsersCommand::List => list_users(&path),
+            UsersCommand::Add {
+                id,
+                latitude,
+                longitude,
+            } => {
+                let id = id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+                add_user(&path, &id, latitude, longitude)
+            }
+            UsersCommand::Remove { id } => remove_user(&path, &id),
+        }
+    }
+}
+
+fn list_users(path: &Path) -> anyhow::Result<()> {
+    let config = Config::load_from_path(path, String::new())?;
+    let mut users: Vec<_> = config.users.iter().collect();
+    users.sort_by_key(|(id, _)| *id);
+
+    for (id, user) in users {
+        println!(
+            "{id}\t{}\t{}",
+            user.latitude.value(),
+            user.longitude.value()
+        );
+    }
+
+    Ok(())
+}
+
+fn add_user(path: &Path, id: &str, latitude: f64, longitude: f64) -> anyhow::Result<()> {
+    Latitude::try_new(latitude as f32)?;
+    Longitude::try_new(longitude as f32)?;
+
+    let mut document = read_document(path)?;
+    let users = users_table(&mut document)?;
+    if users.contains_key(id) {
+        bail!("User '{id}' already exists in config.");
+    }
+
+    let mut user = Table::new();
+    user.insert("latitude", value(latitude));
+    user.insert("longitude", value(longitude));
+    users.insert(id, Item::Table(user));
+
+    std::fs::write(path, document.to_string())?;
+    println!("Added user '{id}'");
+    Ok(())
+}
+
+fn remove_user(path: &Path, id: &str) -> anyhow::Result<()> {
+    let mut document = read_document(path)?;
+    let users = users_table(&mut document)?;
+    if users.remove(id).is_none() {
+        bail!("User '{id}' does not exist in config.");
+    }
+
+    std::fs::write(path, document.to_string())?;
+    println!("Removed user '{id}'");
+    Ok(())
+}
+
+fn read_document(path: &Path) -> anyhow::Result<DocumentMut> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config '{}'", path.display()))?;
+    Ok(source.parse()?)
+}
+
+fn users_table(document: &mut DocumentMut) -> anyhow::Result<&mut Table> {
+    let users = document.entry("users").or_insert_with(|| {
+        let mut table = Table::new();
+        table.set_implicit(true);
+        Item::Table(table)
+    });
+    users
+        .as_table_mut()
+        .context("`users` in config is not a table")
+}
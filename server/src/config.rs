@@ -1,10 +1,22 @@
 use anyhow::Context;
+use arc_swap::ArcSwap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+pub mod alert;
+pub mod check;
+pub mod net;
+pub mod notifier;
+pub mod reload;
+pub mod retention;
+pub mod units;
 pub mod user;
+pub mod webhook;
+
+/// The currently active config, atomically swapped on reload.
+pub type SharedConfig = Arc<ArcSwap<Config>>;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -12,18 +24,67 @@ pub struct Config {
     pub users: HashMap<String, user::ConfigUser>,
     #[serde(default)]
     pub db_url: String,
+    /// Address the HTTP API listens on, only read at startup
+    #[serde(default = "default_http_address")]
+    pub http_address: String,
+    /// Address the binary protocol of native clients listens on, disabled if unset, only read at startup
+    #[serde(default)]
+    pub net_address: Option<String>,
+    /// Native clients with this token see the data of all users. Clients have to send this or the
+    /// token of a user when connecting, any client is accepted if no token is configured. The
+    /// HTTP API takes the same tokens, the webhook admin routes only this one.
+    #[serde(default)]
+    pub net_token: Option<String>,
+    /// Serves the net protocol through TLS if set, only read at startup
+    #[serde(default)]
+    pub net_tls: Option<net::NetTlsConfig>,
+    /// Serves the net protocol as a WebSocket at `/net` of the HTTP API, independent of
+    /// `net_address`. It is as secure as the HTTP API, `net_tls` doesn't apply to it.
+    #[serde(default)]
+    pub net_websocket: bool,
+    #[serde(default)]
+    pub net_limits: net::NetLimitsConfig,
+    #[serde(default)]
+    pub net_compression: net::NetCompressionConfig,
+    #[serde(default)]
+    pub net_checksums: net::NetChecksumsConfig,
+    /// Channels alerts are delivered through, by name
+    #[serde(default)]
+    pub notifiers: HashMap<String, notifier::NotifierConfig>,
+    /// Receivers of server events, by name
+    #[serde(default)]
+    pub webhooks: HashMap<String, webhook::WebhookConfig>,
+    #[serde(default)]
+    pub retention: retention::RetentionConfig,
+}
+
+fn default_http_address() -> String {
+    "0.0.0.0:8080".to_string()
 }
 
 impl Config {
-    pub fn load_from_env() -> anyhow::Result<Arc<Self>> {
+    pub fn load_from_env() -> anyhow::Result<SharedConfig> {
         let db_url = std::env::var("DATABASE_URL").context("DATABASE_URL var not set")?;
+        let config_path = Self::path_from_env()?;
+        let config = Self::load_from_path(&config_path, db_url)?;
+        Ok(Arc::new(ArcSwap::from_pointee(config)))
+    }
+
+    pub fn path_from_env() -> anyhow::Result<PathBuf> {
         let config_path_string = std::env::var("CONFIG_PATH").context("CONFIG_PATH var not set")?;
-        let config_path = PathBuf::from(config_path_string);
-        let config_string = std::fs::read_to_string(config_path)?;
+        Ok(PathBuf::from(config_path_string))
+    }
+
+    pub fn load_from_path(path: &Path, db_url: String) -> anyhow::Result<Self> {
+        let config_string = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config '{}'", path.display()))?;
 
-        let mut config: Config = toml::from_str(&config_string)?;
+        let mut config = check::parse_config(path, &config_string).map_err(|problems| {
+            let problems: Vec<String> = problems.iter().map(ToString::to_string).collect();
+            anyhow::anyhow!("Invalid config\n{}", problems.join("\n"))
+        })?;
         config.db_url = db_url;
-        Ok(Arc::new(config))
+        Ok(config)
     }
 
     pub fn get_user_or_err(&self, user_id: &str) -> anyhow::Result<&user::ConfigUser> {
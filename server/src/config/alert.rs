@@ -0,0 +1,330 @@
+use crate::config::units::QuantityValue;
+use chrono::NaiveTime;
+use omnistat_core::error::CoreResult;
+use omnistat_core::types::length::Length;
+use omnistat_core::types::percentage::Percentage;
+use omnistat_core::types::pressure::Pressure;
+use omnistat_core::types::speed::Speed;
+use omnistat_core::types::temperature::Temperature;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// A user defined rule, evaluated against the forecast after every sync of the user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    /// Unique per user, used to deduplicate fired alerts
+    pub id: String,
+    #[serde(flatten)]
+    pub condition: AlertCondition,
+    /// How far ahead of now the forecast is checked
+    #[serde(default = "default_within_hours")]
+    pub within_hours: u32,
+    /// Only hours in this local time window are checked, e.g. `"07:00-09:00"`
+    #[serde(default)]
+    pub between: Option<LocalTimeWindow>,
+    /// Names of the notifiers in `[notifiers]` to deliver the alert through
+    #[serde(default)]
+    pub notifiers: Vec<String>,
+}
+
+fn default_within_hours() -> u32 {
+    24
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawAlertCondition", into = "RawAlertCondition")]
+pub enum AlertCondition {
+    /// `metric operator threshold`, the threshold is in the unit of the metric. The config may
+    /// give it with any unit of the metric instead, e.g. `"41 °F"` or `"2 in"`
+    Threshold {
+        metric: AlertMetric,
+        operator: AlertOperator,
+        threshold: f64,
+    },
+    /// Any hour has a weather code of the group
+    Weather { weather: WeatherGroup },
+}
+
+/// [`AlertCondition`] as written in the config.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum RawAlertCondition {
+    Threshold {
+        metric: AlertMetric,
+        operator: AlertOperator,
+        threshold: QuantityValue<f64>,
+    },
+    Weather {
+        weather: WeatherGroup,
+    },
+}
+
+impl TryFrom<RawAlertCondition> for AlertCondition {
+    type Error = String;
+
+    fn try_from(value: RawAlertCondition) -> Result<Self, Self::Error> {
+        Ok(match value {
+            RawAlertCondition::Threshold {
+                metric,
+                operator,
+                threshold,
+            } => AlertCondition::Threshold {
+                metric,
+                operator,
+                threshold: match threshold {
+                    QuantityValue::Number(threshold) => threshold,
+                    QuantityValue::Text(text) => metric
+                        .parse_threshold(&text)
+                        .map_err(|e| format!("invalid threshold for {}: {e}", metric.name()))?,
+                },
+            },
+            RawAlertCondition::Weather { weather } => AlertCondition::Weather { weather },
+        })
+    }
+}
+
+impl From<AlertCondition> for RawAlertCondition {
+    fn from(value: AlertCondition) -> Self {
+        match value {
+            AlertCondition::Threshold {
+                metric,
+                operator,
+                threshold,
+            } => RawAlertCondition::Threshold {
+                metric,
+                operator,
+                threshold: QuantityValue::Number(threshold),
+            },
+            AlertCondition::Weather { weather } => RawAlertCondition::Weather { weather },
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    /// °C
+    Temperature,
+    /// °C
+    TemperatureApparent,
+    /// °C
+    DewPoint,
+    /// %
+    RelativeHumidity,
+    /// %
+    CloudCover,
+    /// hPa
+    SurfacePressure,
+    /// km/h
+    WindSpeed,
+    /// km/h
+    WindGusts,
+    /// mm
+    Precipitation,
+    /// %
+    PrecipitationProbability,
+    /// mm
+    Rain,
+    /// mm
+    Snowfall,
+    /// m
+    Visibility,
+}
+
+impl AlertMetric {
+    pub fn name(self) -> &'static str {
+        match self {
+            AlertMetric::Temperature => "temperature",
+            AlertMetric::TemperatureApparent => "temperature_apparent",
+            AlertMetric::DewPoint => "dew_point",
+            AlertMetric::RelativeHumidity => "relative_humidity",
+            AlertMetric::CloudCover => "cloud_cover",
+            AlertMetric::SurfacePressure => "surface_pressure",
+            AlertMetric::WindSpeed => "wind_speed",
+            AlertMetric::WindGusts => "wind_gusts",
+            AlertMetric::Precipitation => "precipitation",
+            AlertMetric::PrecipitationProbability => "precipitation_probability",
+            AlertMetric::Rain => "rain",
+            AlertMetric::Snowfall => "snowfall",
+            AlertMetric::Visibility => "visibility",
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            AlertMetric::Temperature | AlertMetric::TemperatureApparent | AlertMetric::DewPoint => {
+                "°C"
+            }
+            AlertMetric::RelativeHumidity
+            | AlertMetric::CloudCover
+            | AlertMetric::PrecipitationProbability => "%",
+            AlertMetric::SurfacePressure => "hPa",
+            AlertMetric::WindSpeed | AlertMetric::WindGusts => "km/h",
+            AlertMetric::Precipitation | AlertMetric::Rain | AlertMetric::Snowfall => "mm",
+            AlertMetric::Visibility => "m",
+        }
+    }
+
+    /// Converts a quantity like `"41 °F"` to the unit of the metric. Rounded to 4 decimals to
+    /// hide the noise of the `f32` conversions, e.g. `"5 °C"` stays `5`.
+    pub fn parse_threshold(self, text: &str) -> CoreResult<f64> {
+        let value = match self {
+            AlertMetric::Temperature | AlertMetric::TemperatureApparent | AlertMetric::DewPoint => {
+                text.parse::<Temperature>()?.as_celsius() as f64
+            }
+            AlertMetric::RelativeHumidity
+            | AlertMetric::CloudCover
+            | AlertMetric::PrecipitationProbability => {
+                text.parse::<Percentage>()?.as_0_100() as f64
+            }
+            AlertMetric::SurfacePressure => text.parse::<Pressure>()?.as_hpa() as f64,
+            AlertMetric::WindSpeed | AlertMetric::WindGusts => {
+                text.parse::<Speed>()?.as_km_h() as f64
+            }
+            AlertMetric::Precipitation | AlertMetric::Rain | AlertMetric::Snowfall => {
+                text.parse::<Length>()?.as_millimeters()
+            }
+            AlertMetric::Visibility => text.parse::<Length>()?.as_meters(),
+        };
+        Ok((value * 10_000.0).round() / 10_000.0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertOperator {
+    #[serde(rename = "<")]
+    Less,
+    #[serde(rename = "<=")]
+    LessOrEqual,
+    #[serde(rename = ">")]
+    Greater,
+    #[serde(rename = ">=")]
+    GreaterOrEqual,
+}
+
+impl AlertOperator {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            AlertOperator::Less => "<",
+            AlertOperator::LessOrEqual => "<=",
+            AlertOperator::Greater => ">",
+            AlertOperator::GreaterOrEqual => ">=",
+        }
+    }
+
+    pub fn compare(self, value: f64, threshold: f64) -> bool {
+        match self {
+            AlertOperator::Less => value < threshold,
+            AlertOperator::LessOrEqual => value <= threshold,
+            AlertOperator::Greater => value > threshold,
+            AlertOperator::GreaterOrEqual => value >= threshold,
+        }
+    }
+}
+
+/// Groups of [`WMOCode`](omnistat_core::types::wmo_code::WMOCode)s a rule can match.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherGroup {
+    Fog,
+    Drizzle,
+    Rain,
+    Freezing,
+    Snow,
+    Showers,
+    Thunderstorm,
+}
+
+impl WeatherGroup {
+    pub fn name(self) -> &'static str {
+        match self {
+            WeatherGroup::Fog => "fog",
+            WeatherGroup::Drizzle => "drizzle",
+            WeatherGroup::Rain => "rain",
+            WeatherGroup::Freezing => "freezing",
+            WeatherGroup::Snow => "snow",
+            WeatherGroup::Showers => "showers",
+            WeatherGroup::Thunderstorm => "thunderstorm",
+        }
+    }
+}
+
+/// A window of local time, wraps around midnight if `end` is before `start`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct LocalTimeWindow {
+    /// Inclusive
+    pub start: NaiveTime,
+    /// Exclusive
+    pub end: NaiveTime,
+}
+
+impl LocalTimeWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            self.start <= time || time < self.end
+        }
+    }
+}
+
+impl TryFrom<String> for LocalTimeWindow {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let parse = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                .map_err(|_| format!("invalid time '{}', expected HH:MM", time.trim()))
+        };
+        let (start, end) = value
+            .split_once('-')
+            .ok_or_else(|| format!("invalid time window '{value}', expected HH:MM-HH:MM"))?;
+        Ok(Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        })
+    }
+}
+
+impl From<LocalTimeWindow> for String {
+    fn from(value: LocalTimeWindow) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for LocalTimeWindow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+impl Display for AlertRule {
+    /// E.g. `temperature_apparent < -5 °C within 12h between 07:00-09:00`
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.condition {
+            AlertCondition::Threshold {
+                metric,
+                operator,
+                threshold,
+            } => write!(
+                f,
+                "{} {} {threshold} {}",
+                metric.name(),
+                operator.symbol(),
+                metric.unit()
+            )?,
+            AlertCondition::Weather { weather } => write!(f, "weather is any {}", weather.name())?,
+        }
+        write!(f, " within {}h", self.within_hours)?;
+        if let Some(between) = &self.between {
+            write!(f, " between {between}")?;
+        }
+        Ok(())
+    }
+}
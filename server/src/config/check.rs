@@ -0,0 +1,488 @@
+use crate::config::Config;
+use crate::config::units::deserialize_bytes;
+use omnistat_core::types::latitude::Latitude;
+use omnistat_core::types::longitude::Longitude;
+use omnistat_net::compression::MAX_ZSTD_LEVEL;
+use serde::Deserialize;
+use serde::de::IgnoredAny;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml::Spanned;
+
+/// A single problem found in the config file, pointing at the offending line.
+#[derive(Debug)]
+pub struct ConfigProblem {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for ConfigProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.message
+        )
+    }
+}
+
+/// Mirrors [`Config`] but keeps the spans of every value, so each problem can be reported
+/// at its location instead of stopping at the first one like a regular deserialize does.
+#[derive(Deserialize)]
+struct CheckedConfig {
+    #[serde(default)]
+    users: BTreeMap<Spanned<String>, CheckedUser>,
+    net_token: Option<Spanned<toml::Value>>,
+    net_limits: Option<CheckedNetLimits>,
+    net_compression: Option<CheckedNetCompression>,
+    #[serde(default)]
+    notifiers: BTreeMap<String, IgnoredAny>,
+    retention: Option<CheckedRetention>,
+}
+
+#[derive(Deserialize)]
+struct CheckedUser {
+    latitude: Option<Spanned<toml::Value>>,
+    longitude: Option<Spanned<toml::Value>>,
+    #[serde(default)]
+    alerts: Vec<CheckedAlert>,
+    net_token: Option<Spanned<toml::Value>>,
+}
+
+#[derive(Deserialize)]
+struct CheckedAlert {
+    id: Option<Spanned<toml::Value>>,
+    #[serde(default)]
+    notifiers: Vec<Spanned<toml::Value>>,
+}
+
+#[derive(Deserialize)]
+struct CheckedNetLimits {
+    max_frame_size: Option<Spanned<toml::Value>>,
+    max_decoded_size: Option<Spanned<toml::Value>>,
+}
+
+#[derive(Deserialize)]
+struct CheckedNetCompression {
+    level: Option<Spanned<toml::Value>>,
+}
+
+#[derive(Deserialize)]
+struct CheckedRetention {
+    hourly_days: Option<Spanned<toml::Value>>,
+}
+
+/// Collects all problems of the config file at `path`, an empty list means the config is valid.
+pub fn check_config_file(path: &Path) -> anyhow::Result<Vec<ConfigProblem>> {
+    let source = std::fs::read_to_string(path)?;
+    Ok(parse_config(path, &source).err().unwrap_or_default())
+}
+
+/// Parses the config read from `path`, the config is only returned if it has no problems at all.
+pub fn parse_config(path: &Path, source: &str) -> Result<Config, Vec<ConfigProblem>> {
+    ConfigChecker::new(path, source).check()
+}
+
+struct ConfigChecker<'a> {
+    path: &'a Path,
+    source: &'a str,
+    problems: Vec<ConfigProblem>,
+}
+
+impl<'a> ConfigChecker<'a> {
+    fn new(path: &'a Path, source: &'a str) -> Self {
+        Self {
+            path,
+            source,
+            problems: Vec::new(),
+        }
+    }
+
+    fn check(mut self) -> Result<Config, Vec<ConfigProblem>> {
+        match toml::from_str::<CheckedConfig>(self.source) {
+            Ok(config) => {
+                self.check_users(&config);
+                self.check_net(&config);
+                if let Some(hourly_days) =
+                    config.retention.and_then(|retention| retention.hourly_days)
+                    && let Some((0, span)) =
+                        self.check_value::<u32>("retention.hourly_days", hourly_days)
+                {
+                    self.push(
+                        span,
+                        "retention.hourly_days: must be at least 1".to_string(),
+                    );
+                }
+            }
+            Err(e) => self.push_toml_error(&e),
+        }
+
+        // Safety net, the server must never reject a config this check accepted.
+        if self.problems.is_empty() {
+            match toml::from_str::<Config>(self.source) {
+                Ok(config) => return Ok(config),
+                Err(e) => self.push_toml_error(&e),
+            }
+        }
+
+        self.problems
+            .sort_by_key(|problem| (problem.line, problem.column));
+        Err(self.problems)
+    }
+
+    fn check_users(&mut self, config: &CheckedConfig) {
+        let mut net_tokens = HashSet::new();
+        if let Some(token) = &config.net_token {
+            self.check_net_token("net_token", token.clone(), &mut net_tokens);
+        }
+
+        for (user_id, user) in &config.users {
+            let user_span = user_id.span();
+            let user_id = user_id.get_ref();
+
+            match &user.latitude {
+                Some(latitude) => {
+                    self.check_value::<Latitude>(
+                        &format!("users.{user_id}.latitude"),
+                        latitude.clone(),
+                    );
+                }
+                None => self.push(
+                    user_span.clone(),
+                    format!("users.{user_id}: missing field `latitude`"),
+                ),
+            }
+            match &user.longitude {
+                Some(longitude) => {
+                    self.check_value::<Longitude>(
+                        &format!("users.{user_id}.longitude"),
+                        longitude.clone(),
+                    );
+                }
+                None => self.push(
+                    user_span,
+                    format!("users.{user_id}: missing field `longitude`"),
+                ),
+            }
+
+            let mut rule_ids = HashSet::new();
+            for rule in &user.alerts {
+                let Some(id) = &rule.id else {
+                    continue;
+                };
+                let Some((id, id_span)) =
+                    self.check_value::<String>(&format!("users.{user_id}.alerts.id"), id.clone())
+                else {
+                    continue;
+                };
+                if !rule_ids.insert(id.clone()) {
+                    self.push(
+                        id_span,
+                        format!("users.{user_id}: duplicate alert id '{id}'"),
+                    );
+                }
+                for notifier in &rule.notifiers {
+                    let key = format!("users.{user_id}.alerts.notifiers");
+                    if let Some((name, span)) = self.check_value::<String>(&key, notifier.clone())
+                        && !config.notifiers.contains_key(&name)
+                    {
+                        self.push(
+                            span,
+                            format!("users.{user_id}: alert '{id}' uses unknown notifier '{name}'"),
+                        );
+                    }
+                }
+            }
+
+            if let Some(token) = &user.net_token {
+                let key = format!("users.{user_id}.net_token");
+                self.check_net_token(&key, token.clone(), &mut net_tokens);
+            }
+        }
+    }
+
+    /// Tokens must not be empty and tell clients apart, so they can't be shared.
+    fn check_net_token(
+        &mut self,
+        key: &str,
+        token: Spanned<toml::Value>,
+        net_tokens: &mut HashSet<String>,
+    ) {
+        let Some((token, span)) = self.check_value::<String>(key, token) else {
+            return;
+        };
+        if token.is_empty() {
+            self.push(span, format!("{key}: must not be empty"));
+        } else if !net_tokens.insert(token) {
+            self.push(span, format!("{key}: is already in use"));
+        }
+    }
+
+    fn check_net(&mut self, config: &CheckedConfig) {
+        if let Some(limits) = &config.net_limits {
+            let sizes = [
+                ("net_limits.max_frame_size", &limits.max_frame_size),
+                ("net_limits.max_decoded_size", &limits.max_decoded_size),
+            ];
+            for (key, size) in sizes {
+                if let Some(size) = size
+                    && let Some((0, span)) = self.check_with(key, size.clone(), deserialize_bytes)
+                {
+                    self.push(span, format!("{key}: must be at least 1 byte"));
+                }
+            }
+        }
+
+        if let Some(level) = config
+            .net_compression
+            .as_ref()
+            .and_then(|compression| compression.level.clone())
+            && let Some((level, span)) = self.check_value::<u8>("net_compression.level", level)
+            && !(1..=MAX_ZSTD_LEVEL).contains(&level)
+        {
+            self.push(
+                span,
+                format!("net_compression.level: must be between 1 and {MAX_ZSTD_LEVEL}"),
+            );
+        }
+    }
+
+    /// The value with its span, `None` after pushing the problem if it has the wrong type.
+    fn check_value<T>(
+        &mut self,
+        key: &str,
+        value: Spanned<toml::Value>,
+    ) -> Option<(T, Range<usize>)>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.check_with(key, value, T::deserialize)
+    }
+
+    fn check_with<T>(
+        &mut self,
+        key: &str,
+        value: Spanned<toml::Value>,
+        deserialize: impl FnOnce(toml::Value) -> Result<T, toml::de::Error>,
+    ) -> Option<(T, Range<usize>)> {
+        let span = value.span();
+        match deserialize(value.into_inner()) {
+            Ok(value) => Some((value, span)),
+            Err(e) => {
+                self.push(span, format!("{key}: {}", e.message()));
+                None
+            }
+        }
+    }
+
+    fn push_toml_error(&mut self, error: &toml::de::Error) {
+        let span = error.span().unwrap_or(0..0);
+        self.push(span, error.message().to_string());
+    }
+
+    fn push(&mut self, span: Range<usize>, message: String) {
+        let (line, column) = line_column(self.source, span.start);
+        self.problems.push(ConfigProblem {
+            path: self.path.to_path_buf(),
+            line,
+            column,
+            message,
+        });
+    }
+}
+
+/// 1-based line and column of a byte offset.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+/// Runs the `check-config` mode, printing every problem and returning the process exit code.
+pub fn run_check_config() -> i32 {
+    match Config::path_from_env() {
+        Ok(path) => check_config_exit_code(&path),
+        Err(e) => {
+            eprintln!("{e:#}");
+            2
+        }
+    }
+}
+
+/// 0 if the config is valid, 1 if it has problems and 2 if it can't be read.
+fn check_config_exit_code(path: &Path) -> i32 {
+    match check_config_file(path) {
+        Ok(problems) if problems.is_empty() => {
+            println!("{}: config is valid", path.display());
+            0
+        }
+        Ok(problems) => {
+            for problem in &problems {
+                eprintln!("{problem}");
+            }
+            eprintln!("{} problem(s) found", problems.len());
+            1
+        }
+        Err(e) => {
+            eprintln!("Failed to read config '{}': {e}", path.display());
+            2
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(source: &str) -> Vec<String> {
+        match parse_config(Path::new("config.toml"), source) {
+            Ok(_) => Vec::new(),
+            Err(problems) => problems.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn rejects_shared_net_tokens() {
+        let source = r#"
+net_token = "server"
+
+[users.a]
+latitude = 1.0
+longitude = 2.0
+net_token = "shared"
+
+[users.b]
+latitude = 1.0
+longitude = 2.0
+net_token = "shared"
+
+[users.c]
+latitude = 1.0
+longitude = 2.0
+net_token = "server"
+"#;
+        assert_eq!(
+            problems(source),
+            [
+                "config.toml:12:13: users.b.net_token: is already in use",
+                "config.toml:17:13: users.c.net_token: is already in use",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_empty_net_tokens() {
+        let source = r#"
+net_token = ""
+
+[users.a]
+latitude = 1.0
+longitude = 2.0
+net_token = ""
+"#;
+        assert_eq!(
+            problems(source),
+            [
+                "config.toml:2:13: net_token: must not be empty",
+                "config.toml:7:13: users.a.net_token: must not be empty",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_coordinates_out_of_range() {
+        let source = r#"
+[users.a]
+latitude = 90.5
+longitude = -181.0
+"#;
+        assert_eq!(
+            problems(source),
+            [
+                "config.toml:3:12: users.a.latitude: Latitude 90.5 is out of range, expected a value between -90 and 90",
+                "config.toml:4:13: users.a.longitude: Longitude -181 is out of range, expected a value between -180 and 180",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_missing_coordinates() {
+        let source = r#"
+[users.a]
+longitude = 2.0
+
+[users.b]
+latitude = 1.0
+"#;
+        assert_eq!(
+            problems(source),
+            [
+                "config.toml:2:8: users.a: missing field `latitude`",
+                "config.toml:5:8: users.b: missing field `longitude`",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_every_problem_at_its_location() {
+        let source = r#"
+net_token = ""
+
+[users.a]
+latitude = -91.0
+longitude = 2.0
+
+[users.b]
+latitude = 1.0
+
+[net_compression]
+level = 30
+
+[retention]
+hourly_days = 0
+"#;
+        assert_eq!(
+            problems(source),
+            [
+                "config.toml:2:13: net_token: must not be empty",
+                "config.toml:5:12: users.a.latitude: Latitude -91 is out of range, expected a value between -90 and 90",
+                "config.toml:8:8: users.b: missing field `longitude`",
+                "config.toml:12:9: net_compression.level: must be between 1 and 22",
+                "config.toml:15:15: retention.hourly_days: must be at least 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn exit_code_tells_valid_invalid_and_unreadable_configs_apart() {
+        let path = std::env::temp_dir().join(format!("omnistat-check-{}.toml", std::process::id()));
+        std::fs::write(&path, "[users.a]\nlatitude = 1.0\nlongitude = 2.0\n").unwrap();
+        assert_eq!(check_config_exit_code(&path), 0);
+        std::fs::write(&path, "[users.a]\nlatitude = 100.0\nlongitude = 2.0\n").unwrap();
+        assert_eq!(check_config_exit_code(&path), 1);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(check_config_exit_code(&path), 2);
+    }
+
+    #[test]
+    fn accepts_distinct_net_tokens() {
+        let source = r#"
+net_token = "server"
+
+[users.a]
+latitude = 1.0
+longitude = 2.0
+net_token = "a"
+"#;
+        assert_eq!(problems(source), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,176 @@
+use crate::config::units::deserialize_bytes;
+use omnistat_net::checksum::{Checksums, OnMismatch};
+use omnistat_net::compression::Compression;
+use omnistat_net::handshake::Codec;
+use omnistat_net::limits::FrameLimits;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Sizes of frames accepted from native clients, new connections pick up changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetLimitsConfig {
+    /// Bytes of a frame as sent over the connection
+    #[serde(
+        default = "default_max_frame_size",
+        deserialize_with = "deserialize_bytes"
+    )]
+    pub max_frame_size: usize,
+    /// Bytes of a frame after decompression
+    #[serde(
+        default = "default_max_decoded_size",
+        deserialize_with = "deserialize_bytes"
+    )]
+    pub max_decoded_size: usize,
+}
+
+impl Default for NetLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_frame_size: default_max_frame_size(),
+            max_decoded_size: default_max_decoded_size(),
+        }
+    }
+}
+
+fn default_max_frame_size() -> usize {
+    FrameLimits::default().max_frame_size
+}
+
+fn default_max_decoded_size() -> usize {
+    FrameLimits::default().max_decoded_size
+}
+
+impl From<&NetLimitsConfig> for FrameLimits {
+    fn from(config: &NetLimitsConfig) -> Self {
+        FrameLimits {
+            max_frame_size: config.max_frame_size,
+            max_decoded_size: config.max_decoded_size,
+        }
+    }
+}
+
+/// Certificate and key the net protocol serves TLS with, PEM encoded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetTlsConfig {
+    /// Certificate chain, the certificate of the server first
+    pub certificate: PathBuf,
+    pub private_key: PathBuf,
+}
+
+/// How frames sent to native clients are compressed, new connections pick up changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetCompressionConfig {
+    /// Used if the client offers it, otherwise the best codec both sides support
+    #[serde(default)]
+    pub codec: NetCodec,
+    /// zstd level from 1 to 22
+    #[serde(default = "default_compression_level")]
+    pub level: u8,
+    /// Frames smaller than this many bytes are sent uncompressed
+    #[serde(
+        default = "default_compression_threshold",
+        deserialize_with = "deserialize_bytes"
+    )]
+    pub threshold: usize,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetCodec {
+    None,
+    #[default]
+    Zstd,
+    /// zstd with the dictionary trained on weather payloads
+    ZstdDictionary,
+    Lz4,
+}
+
+impl Default for NetCompressionConfig {
+    fn default() -> Self {
+        Self {
+            codec: NetCodec::default(),
+            level: default_compression_level(),
+            threshold: default_compression_threshold(),
+        }
+    }
+}
+
+fn default_compression_level() -> u8 {
+    Compression::default().level
+}
+
+fn default_compression_threshold() -> usize {
+    Compression::default().threshold
+}
+
+impl From<NetCodec> for Codec {
+    fn from(codec: NetCodec) -> Self {
+        match codec {
+            NetCodec::None => Codec::None,
+            NetCodec::Zstd => Codec::Zstd,
+            NetCodec::ZstdDictionary => Codec::ZstdDictionary,
+            NetCodec::Lz4 => Codec::Lz4,
+        }
+    }
+}
+
+impl From<&NetCompressionConfig> for Compression {
+    fn from(config: &NetCompressionConfig) -> Self {
+        Compression {
+            codec: config.codec.into(),
+            level: config.level,
+            threshold: config.threshold,
+        }
+    }
+}
+
+/// Checksums of frames exchanged with native clients, new connections pick up changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetChecksumsConfig {
+    /// Frames sent to clients carry a checksum
+    #[serde(default = "default_send_checksums")]
+    pub send: bool,
+    /// Frames of clients without a checksum are treated as corrupted
+    #[serde(default)]
+    pub require: bool,
+    /// What happens to corrupted frames of clients
+    #[serde(default)]
+    pub on_mismatch: NetOnMismatch,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetOnMismatch {
+    /// The connection is closed
+    #[default]
+    Close,
+    /// The frame is dropped, its request stays unanswered
+    Skip,
+}
+
+impl Default for NetChecksumsConfig {
+    fn default() -> Self {
+        Self {
+            send: default_send_checksums(),
+            require: false,
+            on_mismatch: NetOnMismatch::default(),
+        }
+    }
+}
+
+fn default_send_checksums() -> bool {
+    Checksums::default().send
+}
+
+impl From<&NetChecksumsConfig> for Checksums {
+    fn from(config: &NetChecksumsConfig) -> Self {
+        Checksums {
+            send: config.send,
+            require: config.require,
+            on_mismatch: match config.on_mismatch {
+                NetOnMismatch::Close => OnMismatch::Close,
+                NetOnMismatch::Skip => OnMismatch::Skip,
+            },
+        }
+    }
+}
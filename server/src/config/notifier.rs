@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+/// A named channel alerts are delivered through, referenced by name from the alert rules.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotifierConfig {
+    /// POSTs the alert as JSON
+    Webhook { url: String },
+    /// Sends a plain text mail
+    Smtp {
+        host: String,
+        #[serde(default)]
+        port: Option<u16>,
+        #[serde(default)]
+        tls: SmtpTls,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        from: String,
+        to: Vec<String>,
+    },
+    /// POSTs the message to `{url}/{topic}` like ntfy.sh expects it
+    Ntfy {
+        url: String,
+        topic: String,
+        #[serde(default)]
+        token: Option<String>,
+    },
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpTls {
+    /// Plain text, only meant for local relays
+    None,
+    #[default]
+    Starttls,
+    /// Implicit TLS, usually on port 465
+    Tls,
+}
@@ -0,0 +1,270 @@
+use crate::config::{Config, SharedConfig};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+
+/// Editors and `docker cp` tend to emit several events per save, wait for them to settle.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Ends the changes of options that are only read at startup.
+const REQUIRES_RESTART: &str = "(requires restart)";
+
+/// Watches `CONFIG_PATH` and listens for SIGHUP, swapping in the new config once it parsed
+/// successfully. An invalid config is logged and rejected, the running one stays active.
+pub fn start_config_watcher(config: SharedConfig) -> anyhow::Result<()> {
+    let path = Config::path_from_env()?;
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+
+    let file_name = path.file_name().map(|name| name.to_os_string());
+    let mut watcher =
+        notify::recommended_watcher(move |result: notify::Result<notify::Event>| match result {
+            Ok(event) => {
+                let is_change = matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                );
+                let is_config = event
+                    .paths
+                    .iter()
+                    .any(|event_path| event_path.file_name() == file_name.as_deref());
+                if is_change && is_config {
+                    let _ = sender.send(());
+                }
+            }
+            Err(e) => warn!("Config watcher error: {e}"),
+        })?;
+
+    // Watch the parent directory, files replaced via rename would otherwise drop the watch.
+    let watch_dir = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    watcher.watch(watch_dir, RecursiveMode::NonRecursive)?;
+
+    tokio::spawn(async move {
+        let _watcher = watcher;
+        let mut hangup = hangup_signal();
+
+        loop {
+            tokio::select! {
+                Some(()) = receiver.recv() => {
+                    tokio::time::sleep(DEBOUNCE).await;
+                    while receiver.try_recv().is_ok() {}
+                    debug!("Config file '{}' changed", path.display());
+                }
+                Some(()) = recv_hangup(&mut hangup) => {
+                    info!("Received SIGHUP");
+                }
+                else => break,
+            }
+            reload(&config, &path);
+        }
+    });
+
+    Ok(())
+}
+
+fn reload(config: &SharedConfig, path: &Path) {
+    let current = config.load_full();
+    let new_config = match Config::load_from_path(path, current.db_url.clone()) {
+        Ok(new_config) => new_config,
+        Err(e) => {
+            error!("Rejected invalid config, keeping the current one: {e:#}");
+            return;
+        }
+    };
+
+    let changes = diff(&current, &new_config);
+    if changes.is_empty() {
+        info!("Config reloaded, nothing changed");
+    } else {
+        info!("Config reloaded with {} change(s)", changes.len());
+        for change in changes {
+            if change.ends_with(REQUIRES_RESTART) {
+                warn!("  {change}");
+            } else {
+                info!("  {change}");
+            }
+        }
+    }
+
+    config.store(Arc::new(new_config));
+}
+
+fn diff(old: &Config, new: &Config) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    let mut user_ids: Vec<&String> = old.users.keys().chain(new.users.keys()).collect();
+    user_ids.sort();
+    user_ids.dedup();
+
+    for user_id in user_ids {
+        match (old.users.get(user_id), new.users.get(user_id)) {
+            (None, Some(user)) => changes.push(format!(
+                "+ user '{user_id}' ({}, {})",
+                user.latitude.value(),
+                user.longitude.value()
+            )),
+            (Some(_), None) => changes.push(format!("- user '{user_id}'")),
+            (Some(old_user), Some(new_user)) => {
+                let old_location = (old_user.latitude.value(), old_user.longitude.value());
+                let new_location = (new_user.latitude.value(), new_user.longitude.value());
+                if old_location != new_location {
+                    changes.push(format!(
+                        "~ user '{user_id}' ({}, {}) -> ({}, {})",
+                        old_location.0, old_location.1, new_location.0, new_location.1
+                    ));
+                }
+                if old_user.net_token != new_user.net_token {
+                    changes.push(format!("~ user '{user_id}' net token"));
+                }
+                if old_user.alerts != new_user.alerts {
+                    changes.push(format!(
+                        "~ user '{user_id}' alerts ({} -> {} rule(s))",
+                        old_user.alerts.len(),
+                        new_user.alerts.len()
+                    ));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    let mut notifiers: Vec<&String> = old.notifiers.keys().chain(new.notifiers.keys()).collect();
+    notifiers.sort();
+    notifiers.dedup();
+
+    for name in notifiers {
+        match (old.notifiers.get(name), new.notifiers.get(name)) {
+            (None, Some(_)) => changes.push(format!("+ notifier '{name}'")),
+            (Some(_), None) => changes.push(format!("- notifier '{name}'")),
+            (Some(old_notifier), Some(new_notifier)) if old_notifier != new_notifier => {
+                changes.push(format!("~ notifier '{name}'"))
+            }
+            _ => {}
+        }
+    }
+
+    let mut webhooks: Vec<&String> = old.webhooks.keys().chain(new.webhooks.keys()).collect();
+    webhooks.sort();
+    webhooks.dedup();
+
+    for name in webhooks {
+        match (old.webhooks.get(name), new.webhooks.get(name)) {
+            (None, Some(webhook)) => changes.push(format!("+ webhook '{name}' ({})", webhook.url)),
+            (Some(_), None) => changes.push(format!("- webhook '{name}'")),
+            (Some(old_webhook), Some(new_webhook)) if old_webhook != new_webhook => {
+                changes.push(format!("~ webhook '{name}'"))
+            }
+            _ => {}
+        }
+    }
+
+    if old.http_address != new.http_address {
+        changes.push(format!(
+            "~ http address {} -> {} {REQUIRES_RESTART}",
+            old.http_address, new.http_address
+        ));
+    }
+
+    if old.net_address != new.net_address {
+        changes.push(format!(
+            "~ net address {} -> {} {REQUIRES_RESTART}",
+            describe_address(old.net_address.as_deref()),
+            describe_address(new.net_address.as_deref())
+        ));
+    }
+
+    if old.net_tls != new.net_tls {
+        changes.push(format!("~ net TLS {REQUIRES_RESTART}"));
+    }
+
+    if old.net_websocket != new.net_websocket {
+        let state = if new.net_websocket {
+            "enabled"
+        } else {
+            "disabled"
+        };
+        changes.push(format!("~ net WebSocket {state}"));
+    }
+
+    if old.net_token != new.net_token {
+        changes.push("~ net token".to_string());
+    }
+
+    if old.net_limits != new.net_limits {
+        changes.push(format!(
+            "~ net limits (frames up to {} bytes, {} bytes decoded)",
+            new.net_limits.max_frame_size, new.net_limits.max_decoded_size
+        ));
+    }
+
+    if old.net_compression != new.net_compression {
+        changes.push(format!(
+            "~ net compression ({:?} level {}, frames from {} bytes)",
+            new.net_compression.codec, new.net_compression.level, new.net_compression.threshold
+        ));
+    }
+
+    if old.net_checksums != new.net_checksums {
+        changes.push(format!(
+            "~ net checksums (send {}, require {}, {:?} on mismatch)",
+            new.net_checksums.send, new.net_checksums.require, new.net_checksums.on_mismatch
+        ));
+    }
+
+    if old.retention != new.retention {
+        changes.push(format!(
+            "~ retention of hourly weather ({} -> {})",
+            describe_hourly_days(old.retention.hourly_days),
+            describe_hourly_days(new.retention.hourly_days)
+        ));
+    }
+
+    changes
+}
+
+fn describe_address(address: Option<&str>) -> &str {
+    address.unwrap_or("disabled")
+}
+
+fn describe_hourly_days(hourly_days: Option<u32>) -> String {
+    hourly_days.map_or_else(|| "forever".to_string(), |days| format!("{days} day(s)"))
+}
+
+#[cfg(unix)]
+type HangupSignal = Option<tokio::signal::unix::Signal>;
+#[cfg(not(unix))]
+type HangupSignal = ();
+
+#[cfg(unix)]
+fn hangup_signal() -> HangupSignal {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    match signal(SignalKind::hangup()) {
+        Ok(signal) => Some(signal),
+        Err(e) => {
+            warn!("Failed to listen for SIGHUP: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn hangup_signal() -> HangupSignal {}
+
+#[cfg(unix)]
+async fn recv_hangup(signal: &mut HangupSignal) -> Option<()> {
+    match signal {
+        Some(signal) => signal.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(not(unix))]
+async fn recv_hangup(_signal: &mut HangupSignal) -> Option<()> {
+    std::future::pending().await
+}
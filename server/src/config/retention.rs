@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// How long hourly weather is kept at full resolution.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Older hours are rolled up into daily aggregates and removed, kept forever if omitted
+    #[serde(default)]
+    pub hourly_days: Option<u32>,
+    /// Removed hours are appended to monthly NDJSON files of each user in this directory,
+    /// they are only deleted if omitted
+    #[serde(default)]
+    pub archive_dir: Option<PathBuf>,
+}
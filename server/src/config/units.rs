@@ -0,0 +1,23 @@
+use omnistat_core::types::digital_information::DigitalInformation;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// A config value given as a number in the unit of its option, or as a string with a unit
+/// parsed by [`omnistat_core::parse`], e.g. `16777216` or `"16 MiB"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum QuantityValue<N> {
+    Number(N),
+    Text(String),
+}
+
+/// Sizes in bytes, see [`QuantityValue`].
+pub fn deserialize_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    match QuantityValue::<usize>::deserialize(deserializer)? {
+        QuantityValue::Number(bytes) => Ok(bytes),
+        QuantityValue::Text(text) => text
+            .parse::<DigitalInformation>()
+            .map(|size| size.as_bytes() as usize)
+            .map_err(D::Error::custom),
+    }
+}
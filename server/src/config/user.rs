@@ -1,3 +1,4 @@
+use crate::config::alert::AlertRule;
 use omnistat_core::types::latitude::Latitude;
 use omnistat_core::types::longitude::Longitude;
 use serde::{Deserialize, Serialize};
@@ -6,4 +7,9 @@ use serde::{Deserialize, Serialize};
 pub struct ConfigUser {
     pub latitude: Latitude,
     pub longitude: Longitude,
+    #[serde(default)]
+    pub alerts: Vec<AlertRule>,
+    /// Native clients connecting with this token only see the data of this user
+    #[serde(default)]
+    pub net_token: Option<String>,
 }
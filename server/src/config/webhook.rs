@@ -0,0 +1,26 @@
+use crate::events::EventKind;
+use serde::{Deserialize, Serialize};
+
+/// Server events are POSTed as JSON to `url`, signed with `secret`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Key of the HMAC-SHA256 signature in the `X-Omnistat-Signature` header
+    pub secret: String,
+    /// The events to deliver, all if empty
+    #[serde(default)]
+    pub events: Vec<EventKind>,
+    /// Failed deliveries are retried with exponential backoff until they are dead-lettered
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+}
+
+fn default_max_attempts() -> u32 {
+    8
+}
+
+impl WebhookConfig {
+    pub fn wants(&self, kind: EventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
@@ -1,2 +1,34 @@
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, EntityTrait, IdenStatic, Iterable, PrimaryKeyToColumn,
+};
+
 pub mod entity;
 pub mod entity_ext;
+
+/// Inserts the rows, overwriting every column but the primary key of rows that already exist.
+pub async fn upsert_all<E, A>(
+    models: Vec<A>,
+    connection: &impl ConnectionTrait,
+) -> anyhow::Result<()>
+where
+    E: EntityTrait,
+    A: ActiveModelTrait<Entity = E> + Send,
+{
+    if models.is_empty() {
+        return Ok(());
+    }
+
+    let keys: Vec<E::Column> = E::PrimaryKey::iter().map(|key| key.into_column()).collect();
+    let updates =
+        E::Column::iter().filter(|column| !keys.iter().any(|key| key.as_str() == column.as_str()));
+    E::insert_many(models)
+        .on_conflict(
+            OnConflict::columns(keys.clone())
+                .update_columns(updates)
+                .to_owned(),
+        )
+        .exec(connection)
+        .await?;
+    Ok(())
+}
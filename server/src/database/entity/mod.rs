@@ -2,4 +2,13 @@
 
 pub mod prelude;
 
+pub mod alert_event;
+pub mod climate_day_normal;
+pub mod climate_month;
+pub mod climate_record;
+pub mod climate_state;
+pub mod daily_weather;
+pub mod data_quality;
 pub mod hourly_weather;
+pub mod hourly_weather_rollup;
+pub mod webhook_delivery;
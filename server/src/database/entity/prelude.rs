@@ -1,3 +1,12 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 2.0.0-rc.9
 
+pub use super::alert_event::Entity as AlertEvent;
+pub use super::climate_day_normal::Entity as ClimateDayNormal;
+pub use super::climate_month::Entity as ClimateMonth;
+pub use super::climate_record::Entity as ClimateRecord;
+pub use super::climate_state::Entity as ClimateState;
+pub use super::daily_weather::Entity as DailyWeather;
+pub use super::data_quality::Entity as DataQuality;
 pub use super::hourly_weather::Entity as HourlyWeather;
+pub use super::hourly_weather_rollup::Entity as HourlyWeatherRollup;
+pub use super::webhook_delivery::Entity as WebhookDelivery;
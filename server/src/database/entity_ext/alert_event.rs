@@ -0,0 +1,34 @@
+use crate::database::entity::alert_event;
+use sea_orm::prelude::DateTime;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+
+impl alert_event::Entity {
+    /// The fired but not yet resolved event of a rule, there is at most one.
+    pub async fn find_active(
+        user_id: &str,
+        rule_id: &str,
+        connection: &DatabaseConnection,
+    ) -> anyhow::Result<Option<alert_event::Model>> {
+        Ok(Self::find()
+            .filter(alert_event::Column::UserId.eq(user_id))
+            .filter(alert_event::Column::RuleId.eq(rule_id))
+            .filter(alert_event::Column::ResolvedAtUtc.is_null())
+            .order_by_desc(alert_event::Column::FiredAtUtc)
+            .one(connection)
+            .await?)
+    }
+
+    pub async fn resolve(
+        id: i32,
+        resolved_at: DateTime,
+        connection: &DatabaseConnection,
+    ) -> anyhow::Result<()> {
+        Self::update_many()
+            .col_expr(alert_event::Column::ResolvedAtUtc, Expr::value(resolved_at))
+            .filter(alert_event::Column::Id.eq(id))
+            .exec(connection)
+            .await?;
+        Ok(())
+    }
+}
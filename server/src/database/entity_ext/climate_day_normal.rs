@@ -0,0 +1,27 @@
+use crate::database::entity::climate_day_normal;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder};
+
+impl climate_day_normal::Entity {
+    pub async fn find_by_user(
+        user_id: &str,
+        connection: &impl ConnectionTrait,
+    ) -> anyhow::Result<Vec<climate_day_normal::Model>> {
+        Ok(Self::find()
+            .filter(climate_day_normal::Column::UserId.eq(user_id))
+            .order_by_asc(climate_day_normal::Column::Month)
+            .order_by_asc(climate_day_normal::Column::Day)
+            .all(connection)
+            .await?)
+    }
+
+    pub async fn delete_by_user(
+        user_id: &str,
+        connection: &impl ConnectionTrait,
+    ) -> anyhow::Result<()> {
+        Self::delete_many()
+            .filter(climate_day_normal::Column::UserId.eq(user_id))
+            .exec(connection)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use crate::database::entity::climate_month;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder};
+
+impl climate_month::Entity {
+    pub async fn find_by_user(
+        user_id: &str,
+        connection: &impl ConnectionTrait,
+    ) -> anyhow::Result<Vec<climate_month::Model>> {
+        Ok(Self::find()
+            .filter(climate_month::Column::UserId.eq(user_id))
+            .order_by_asc(climate_month::Column::Year)
+            .order_by_asc(climate_month::Column::Month)
+            .all(connection)
+            .await?)
+    }
+
+    pub async fn delete_by_user(
+        user_id: &str,
+        connection: &impl ConnectionTrait,
+    ) -> anyhow::Result<()> {
+        Self::delete_many()
+            .filter(climate_month::Column::UserId.eq(user_id))
+            .exec(connection)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use crate::database::entity::climate_record;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder};
+
+impl climate_record::Entity {
+    pub async fn find_by_user(
+        user_id: &str,
+        connection: &impl ConnectionTrait,
+    ) -> anyhow::Result<Vec<climate_record::Model>> {
+        Ok(Self::find()
+            .filter(climate_record::Column::UserId.eq(user_id))
+            .order_by_asc(climate_record::Column::Month)
+            .order_by_asc(climate_record::Column::Kind)
+            .all(connection)
+            .await?)
+    }
+
+    pub async fn delete_by_user(
+        user_id: &str,
+        connection: &impl ConnectionTrait,
+    ) -> anyhow::Result<()> {
+        Self::delete_many()
+            .filter(climate_record::Column::UserId.eq(user_id))
+            .exec(connection)
+            .await?;
+        Ok(())
+    }
+}
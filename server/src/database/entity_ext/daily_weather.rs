@@ -0,0 +1,132 @@
+use crate::database::entity::daily_weather;
+use chrono_tz::Tz;
+use omnistat_integrations::apis::open_meteo::daily_forecast::OpenMeteoDaily;
+use sea_orm::prelude::Date;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Select, Set};
+use std::str::FromStr;
+
+impl daily_weather::Entity {
+    pub async fn upsert(
+        active_model: daily_weather::ActiveModel,
+        connection: &DatabaseConnection,
+    ) -> anyhow::Result<()> {
+        Self::insert(active_model)
+            .on_conflict(
+                sea_orm::sea_query::OnConflict::columns([
+                    daily_weather::Column::UserId,
+                    daily_weather::Column::Date,
+                ])
+                .update_columns([
+                    daily_weather::Column::Timezone,
+                    daily_weather::Column::WmoCode,
+                    daily_weather::Column::TemperatureMax,
+                    daily_weather::Column::TemperatureMean,
+                    daily_weather::Column::TemperatureMin,
+                    daily_weather::Column::TemperatureApparentMax,
+                    daily_weather::Column::TemperatureApparentMean,
+                    daily_weather::Column::TemperatureApparentMin,
+                    daily_weather::Column::PrecipitationSum,
+                    daily_weather::Column::RainSum,
+                    daily_weather::Column::ShowersSum,
+                    daily_weather::Column::SnowfallSum,
+                    daily_weather::Column::PrecipitationHours,
+                    daily_weather::Column::PrecipitationProbabilityMax,
+                    daily_weather::Column::SunriseUtc,
+                    daily_weather::Column::SunsetUtc,
+                    daily_weather::Column::SunshineDuration,
+                    daily_weather::Column::DaylightDuration,
+                    daily_weather::Column::WindSpeedMax,
+                    daily_weather::Column::WindGustsMax,
+                    daily_weather::Column::WindDirectionDominant,
+                    daily_weather::Column::UvIndexMax,
+                    daily_weather::Column::UvIndexClearSkyMax,
+                    daily_weather::Column::ShortwaveRadiationSum,
+                ])
+                .to_owned(),
+            )
+            .exec(connection)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn find_by_user_and_date(
+        user_id: &str,
+        date: Date,
+        connection: &DatabaseConnection,
+    ) -> anyhow::Result<Option<daily_weather::Model>> {
+        Ok(Self::find_by_id((user_id.to_string(), date))
+            .one(connection)
+            .await?)
+    }
+
+    /// Days of a user from `from` (inclusive) to `to` (exclusive), ordered by date.
+    pub fn find_by_user_in_range(
+        user_id: &str,
+        from: Option<Date>,
+        to: Option<Date>,
+    ) -> Select<Self> {
+        let mut select = Self::find().filter(daily_weather::Column::UserId.eq(user_id));
+        if let Some(from) = from {
+            select = select.filter(daily_weather::Column::Date.gte(from));
+        }
+        if let Some(to) = to {
+            select = select.filter(daily_weather::Column::Date.lt(to));
+        }
+        select.order_by_asc(daily_weather::Column::Date)
+    }
+
+    pub async fn find_latest_by_user(
+        user_id: &str,
+        connection: &DatabaseConnection,
+    ) -> anyhow::Result<Option<daily_weather::Model>> {
+        Ok(Self::find()
+            .filter(daily_weather::Column::UserId.eq(user_id))
+            .order_by_desc(daily_weather::Column::Date)
+            .one(connection)
+            .await?)
+    }
+
+    /// The timezone of the user's location as reported with the last daily forecast, UTC if none is stored.
+    pub async fn find_timezone_by_user(
+        user_id: &str,
+        connection: &DatabaseConnection,
+    ) -> anyhow::Result<Tz> {
+        let latest = Self::find_latest_by_user(user_id, connection).await?;
+        Ok(latest
+            .and_then(|daily| Tz::from_str(&daily.timezone).ok())
+            .unwrap_or(Tz::UTC))
+    }
+}
+
+impl daily_weather::ActiveModel {
+    pub fn from_open_meteo(forecast: &OpenMeteoDaily, user_id: &str) -> Self {
+        daily_weather::ActiveModel {
+            user_id: Set(user_id.to_string()),
+            date: Set(forecast.time),
+            timezone: Set(forecast.timezone.name().to_string()),
+            wmo_code: Set(u8::from(forecast.wmo_code) as i32),
+            temperature_max: Set(forecast.temperature_2m_max.as_celsius()),
+            temperature_mean: Set(forecast.temperature_2m_mean.as_celsius()),
+            temperature_min: Set(forecast.temperature_2m_min.as_celsius()),
+            temperature_apparent_max: Set(forecast.apparent_temperature_max.as_celsius()),
+            temperature_apparent_mean: Set(forecast.apparent_temperature_mean.as_celsius()),
+            temperature_apparent_min: Set(forecast.apparent_temperature_min.as_celsius()),
+            precipitation_sum: Set(forecast.precipitation_sum.as_millimeters()),
+            rain_sum: Set(forecast.rain_sum.as_millimeters()),
+            showers_sum: Set(forecast.showers_sum.as_millimeters()),
+            snowfall_sum: Set(forecast.snowfall_sum.as_millimeters()),
+            precipitation_hours: Set(forecast.precipitation_hours),
+            precipitation_probability_max: Set(forecast.precipitation_probability_max.as_0_1()),
+            sunrise_utc: Set(forecast.sunrise.naive_utc()),
+            sunset_utc: Set(forecast.sunset.naive_utc()),
+            sunshine_duration: Set(forecast.sunshine_duration.as_secs_f32()),
+            daylight_duration: Set(forecast.daylight_duration.as_secs_f32()),
+            wind_speed_max: Set(forecast.wind_speed_10m_max.as_km_h()),
+            wind_gusts_max: Set(forecast.wind_gusts_10m_max.as_km_h()),
+            wind_direction_dominant: Set(forecast.wind_direction_10m_dominant.as_degrees()),
+            uv_index_max: Set(forecast.uv_index_max.value()),
+            uv_index_clear_sky_max: Set(forecast.uv_index_clear_sky_max.value()),
+            shortwave_radiation_sum: Set(forecast.shortwave_radiation_sum.as_mj_m2()),
+        }
+    }
+}
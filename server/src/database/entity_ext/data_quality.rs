@@ -0,0 +1,45 @@
+use crate::database::entity::data_quality;
+use sea_orm::sea_query::{Expr, ExprTrait, OnConflict};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+
+impl data_quality::Entity {
+    /// Flags an hour, an hour flagged again keeps its first flag time and counts the occurrence.
+    pub async fn flag(
+        active_model: data_quality::ActiveModel,
+        connection: &DatabaseConnection,
+    ) -> anyhow::Result<()> {
+        Self::insert(active_model)
+            .on_conflict(
+                OnConflict::columns([data_quality::Column::UserId, data_quality::Column::TimeUtc])
+                    .update_columns([
+                        data_quality::Column::Checks,
+                        data_quality::Column::Message,
+                        data_quality::Column::Payload,
+                        data_quality::Column::LastFlaggedAtUtc,
+                    ])
+                    .value(
+                        data_quality::Column::Occurrences,
+                        Expr::col((data_quality::Entity, data_quality::Column::Occurrences)).add(1),
+                    )
+                    .to_owned(),
+            )
+            .exec(connection)
+            .await?;
+        Ok(())
+    }
+
+    /// Most recently flagged first.
+    pub async fn find_by_user(
+        user_id: &str,
+        limit: u64,
+        connection: &DatabaseConnection,
+    ) -> anyhow::Result<Vec<data_quality::Model>> {
+        Ok(Self::find()
+            .filter(data_quality::Column::UserId.eq(user_id))
+            .order_by_desc(data_quality::Column::LastFlaggedAtUtc)
+            .order_by_desc(data_quality::Column::TimeUtc)
+            .limit(limit)
+            .all(connection)
+            .await?)
+    }
+}
@@ -1,8 +1,44 @@
 use crate::database::entity::hourly_weather;
 use omnistat_integrations::apis::open_meteo::hourly_forecast::OpenMeteoHourly;
-use sea_orm::{DatabaseConnection, EntityTrait, Set};
+use sea_orm::prelude::DateTime;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Select,
+    Set,
+};
 
 impl hourly_weather::Entity {
+    /// Hours of a user ordered by time, `from` is inclusive and `to` exclusive.
+    pub fn find_by_user_in_range(
+        user_id: &str,
+        from: Option<DateTime>,
+        to: Option<DateTime>,
+    ) -> Select<Self> {
+        let mut select = Self::find().filter(hourly_weather::Column::UserId.eq(user_id));
+        if let Some(from) = from {
+            select = select.filter(hourly_weather::Column::TimeUtc.gte(from));
+        }
+        if let Some(to) = to {
+            select = select.filter(hourly_weather::Column::TimeUtc.lt(to));
+        }
+        select.order_by_asc(hourly_weather::Column::TimeUtc)
+    }
+
+    /// Deletes the hours of a user from `from` (inclusive) to `to` (exclusive), returns the amount deleted.
+    pub async fn delete_by_user_in_range(
+        user_id: &str,
+        from: DateTime,
+        to: DateTime,
+        connection: &impl ConnectionTrait,
+    ) -> anyhow::Result<u64> {
+        let result = Self::delete_many()
+            .filter(hourly_weather::Column::UserId.eq(user_id))
+            .filter(hourly_weather::Column::TimeUtc.gte(from))
+            .filter(hourly_weather::Column::TimeUtc.lt(to))
+            .exec(connection)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
     pub async fn upsert(
         active_model: hourly_weather::ActiveModel,
         connection: &DatabaseConnection,
@@ -0,0 +1,169 @@
+use crate::database::entity::{hourly_weather, hourly_weather_rollup};
+use chrono_tz::Tz;
+use sea_orm::prelude::Date;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, Iterable, QueryFilter, QueryOrder, Select, Set,
+};
+use std::collections::BTreeMap;
+
+impl hourly_weather_rollup::Entity {
+    /// Days of a user from `from` (inclusive) to `to` (exclusive), ordered by date.
+    pub fn find_by_user_in_range(
+        user_id: &str,
+        from: Option<Date>,
+        to: Option<Date>,
+    ) -> Select<Self> {
+        let mut select = Self::find().filter(hourly_weather_rollup::Column::UserId.eq(user_id));
+        if let Some(from) = from {
+            select = select.filter(hourly_weather_rollup::Column::Date.gte(from));
+        }
+        if let Some(to) = to {
+            select = select.filter(hourly_weather_rollup::Column::Date.lt(to));
+        }
+        select.order_by_asc(hourly_weather_rollup::Column::Date)
+    }
+
+    /// Replaces the rollup of a day.
+    pub async fn upsert(
+        active_model: hourly_weather_rollup::ActiveModel,
+        connection: &impl ConnectionTrait,
+    ) -> anyhow::Result<()> {
+        let update_columns = hourly_weather_rollup::Column::iter().filter(|column| {
+            !matches!(
+                column,
+                hourly_weather_rollup::Column::UserId | hourly_weather_rollup::Column::Date
+            )
+        });
+        Self::insert(active_model)
+            .on_conflict(
+                OnConflict::columns([
+                    hourly_weather_rollup::Column::UserId,
+                    hourly_weather_rollup::Column::Date,
+                ])
+                .update_columns(update_columns)
+                .to_owned(),
+            )
+            .exec(connection)
+            .await?;
+        Ok(())
+    }
+}
+
+impl hourly_weather_rollup::ActiveModel {
+    /// Aggregates the hours of a local day, `hours` must not be empty.
+    pub fn from_hours(
+        user_id: &str,
+        date: Date,
+        timezone: Tz,
+        hours: &[hourly_weather::Model],
+    ) -> Self {
+        let temperature = Stats::of(hours, |hour| hour.temperature_actual as f64);
+        let temperature_apparent = Stats::of(hours, |hour| hour.temperature_apparent as f64);
+        let relative_humidity = Stats::of(hours, |hour| hour.relative_humidity as f64);
+        let dew_point = Stats::of(hours, |hour| hour.dew_point as f64);
+        let surface_pressure = Stats::of(hours, |hour| hour.surface_pressure as f64);
+        let cloud_cover = Stats::of(hours, |hour| hour.cloud_cover as f64);
+        let wind_speed = Stats::of(hours, |hour| hour.wind_speed as f64);
+        let max_wind_speed = Stats::of(hours, |hour| hour.max_wind_speed as f64);
+        let precipitation_probability =
+            Stats::of(hours, |hour| hour.precipitation_probability as f64);
+        let snow_depth = Stats::of(hours, |hour| hour.snow_depth);
+        let visibility = Stats::of(hours, |hour| hour.visibility);
+        let shortwave_radiation = Stats::of(hours, |hour| hour.shortwave_radiation as f64);
+
+        hourly_weather_rollup::ActiveModel {
+            user_id: Set(user_id.to_string()),
+            date: Set(date),
+            timezone: Set(timezone.name().to_string()),
+            hours: Set(hours.len() as i32),
+            wmo_code: Set(dominant_wmo_code(hours)),
+            temperature_min: Set(temperature.min as f32),
+            temperature_mean: Set(temperature.mean() as f32),
+            temperature_max: Set(temperature.max as f32),
+            temperature_apparent_min: Set(temperature_apparent.min as f32),
+            temperature_apparent_mean: Set(temperature_apparent.mean() as f32),
+            temperature_apparent_max: Set(temperature_apparent.max as f32),
+            relative_humidity_min: Set(relative_humidity.min as f32),
+            relative_humidity_mean: Set(relative_humidity.mean() as f32),
+            relative_humidity_max: Set(relative_humidity.max as f32),
+            dew_point_min: Set(dew_point.min as f32),
+            dew_point_mean: Set(dew_point.mean() as f32),
+            dew_point_max: Set(dew_point.max as f32),
+            surface_pressure_min: Set(surface_pressure.min as f32),
+            surface_pressure_mean: Set(surface_pressure.mean() as f32),
+            surface_pressure_max: Set(surface_pressure.max as f32),
+            cloud_cover_min: Set(cloud_cover.min as f32),
+            cloud_cover_mean: Set(cloud_cover.mean() as f32),
+            cloud_cover_max: Set(cloud_cover.max as f32),
+            wind_speed_min: Set(wind_speed.min as f32),
+            wind_speed_mean: Set(wind_speed.mean() as f32),
+            wind_speed_max: Set(wind_speed.max as f32),
+            max_wind_speed_max: Set(max_wind_speed.max as f32),
+            wind_direction_mean: Set(mean_wind_direction(hours)),
+            total_precipitation_sum: Set(Stats::of(hours, |hour| hour.total_precipitation).sum),
+            rain_sum: Set(Stats::of(hours, |hour| hour.rain).sum),
+            showers_sum: Set(Stats::of(hours, |hour| hour.showers).sum),
+            snowfall_sum: Set(Stats::of(hours, |hour| hour.snowfall).sum),
+            precipitation_probability_max: Set(precipitation_probability.max as f32),
+            snow_depth_max: Set(snow_depth.max),
+            visibility_min: Set(visibility.min),
+            visibility_mean: Set(visibility.mean()),
+            visibility_max: Set(visibility.max),
+            shortwave_radiation_mean: Set(shortwave_radiation.mean() as f32),
+            shortwave_radiation_max: Set(shortwave_radiation.max as f32),
+        }
+    }
+}
+
+struct Stats {
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: usize,
+}
+
+impl Stats {
+    fn of(hours: &[hourly_weather::Model], value: impl Fn(&hourly_weather::Model) -> f64) -> Self {
+        hours.iter().map(value).fold(
+            Stats {
+                min: f64::INFINITY,
+                max: f64::NEG_INFINITY,
+                sum: 0.0,
+                count: 0,
+            },
+            |stats, value| Stats {
+                min: stats.min.min(value),
+                max: stats.max.max(value),
+                sum: stats.sum + value,
+                count: stats.count + 1,
+            },
+        )
+    }
+
+    fn mean(&self) -> f64 {
+        self.sum / self.count.max(1) as f64
+    }
+}
+
+/// The most frequent code of the day, ties go to the more severe, higher code.
+fn dominant_wmo_code(hours: &[hourly_weather::Model]) -> i32 {
+    let mut counts: BTreeMap<i32, usize> = BTreeMap::new();
+    for hour in hours {
+        *counts.entry(hour.wmo_code).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(code, count)| (*count, *code))
+        .map(|(code, _)| code)
+        .unwrap_or_default()
+}
+
+/// Directions are averaged as vectors, so 350° and 10° average to 0° instead of 180°.
+fn mean_wind_direction(hours: &[hourly_weather::Model]) -> f32 {
+    let (sin, cos) = hours.iter().fold((0.0, 0.0), |(sin, cos), hour| {
+        let radians = (hour.wind_direction as f64).to_radians();
+        (sin + radians.sin(), cos + radians.cos())
+    });
+    f64::atan2(sin, cos).to_degrees().rem_euclid(360.0) as f32
+}
@@ -1 +1,9 @@
+pub mod alert_event;
+pub mod climate_day_normal;
+pub mod climate_month;
+pub mod climate_record;
+pub mod daily_weather;
+pub mod data_quality;
 pub mod hourly_weather;
+pub mod hourly_weather_rollup;
+pub mod webhook_delivery;
@@ -0,0 +1,56 @@
+use crate::database::entity::webhook_delivery;
+use sea_orm::prelude::DateTime;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+
+/// Values of the `status` column.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// Waiting for its next attempt
+    Pending,
+    Delivered,
+    /// Gave up after the maximum amount of attempts, only delivered again when replayed
+    Dead,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Dead => "dead",
+        }
+    }
+}
+
+impl webhook_delivery::Entity {
+    /// Pending deliveries whose next attempt is due, oldest first.
+    pub async fn find_due(
+        now: DateTime,
+        limit: u64,
+        connection: &DatabaseConnection,
+    ) -> anyhow::Result<Vec<webhook_delivery::Model>> {
+        Ok(Self::find()
+            .filter(webhook_delivery::Column::Status.eq(DeliveryStatus::Pending.as_str()))
+            .filter(webhook_delivery::Column::NextAttemptAtUtc.lte(now))
+            .order_by_asc(webhook_delivery::Column::Id)
+            .limit(limit)
+            .all(connection)
+            .await?)
+    }
+
+    /// Newest first.
+    pub async fn find_by_status(
+        status: DeliveryStatus,
+        limit: u64,
+        connection: &DatabaseConnection,
+    ) -> anyhow::Result<Vec<webhook_delivery::Model>> {
+        Ok(Self::find()
+            .filter(webhook_delivery::Column::Status.eq(status.as_str()))
+            .order_by_desc(webhook_delivery::Column::Id)
+            .limit(limit)
+            .all(connection)
+            .await?)
+    }
+}
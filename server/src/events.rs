@@ -0,0 +1,100 @@
+use crate::services::climate::record::RecordKind;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+const CAPACITY: usize = 256;
+
+/// Something that happened inside the server, other parts react to it without being called directly.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum ServerEvent {
+    /// The hourly forecast of a user was stored
+    HourlyWeatherSynced {
+        user_id: String,
+    },
+    /// The daily forecast of a user was stored
+    DailyWeatherSynced {
+        user_id: String,
+    },
+    AlertFired(AlertFired),
+    RecordBroken(RecordBroken),
+}
+
+impl ServerEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            ServerEvent::HourlyWeatherSynced { .. } => EventKind::HourlyWeatherSynced,
+            ServerEvent::DailyWeatherSynced { .. } => EventKind::DailyWeatherSynced,
+            ServerEvent::AlertFired(_) => EventKind::AlertFired,
+            ServerEvent::RecordBroken(_) => EventKind::RecordBroken,
+        }
+    }
+}
+
+/// The kinds of [`ServerEvent`]s, named like the `event` field of their JSON.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    HourlyWeatherSynced,
+    DailyWeatherSynced,
+    AlertFired,
+    RecordBroken,
+}
+
+impl EventKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            EventKind::HourlyWeatherSynced => "hourly_weather_synced",
+            EventKind::DailyWeatherSynced => "daily_weather_synced",
+            EventKind::AlertFired => "alert_fired",
+            EventKind::RecordBroken => "record_broken",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertFired {
+    pub user_id: String,
+    pub rule_id: String,
+    /// The rule as written, e.g. `temperature_apparent < -5 °C within 12h`
+    pub rule: String,
+    pub message: String,
+    /// First forecast hour matching the rule
+    pub first_match: chrono::DateTime<chrono::Utc>,
+    pub fired_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A day beat the all-time record of a user, records are only checked for completed days.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordBroken {
+    pub user_id: String,
+    pub record: RecordKind,
+    pub value: f64,
+    pub previous: f64,
+    pub unit: &'static str,
+    /// Local day of the new record
+    pub date: chrono::NaiveDate,
+    /// Unknown if the day was only stored as a rollup
+    pub time: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Fans out [`ServerEvent`]s to every subscriber, events published without subscribers are dropped.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ServerEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: ServerEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerEvent> {
+        self.sender.subscribe()
+    }
+}
@@ -0,0 +1,109 @@
+use crate::state::ServerState;
+use axum::Router;
+use axum::http::StatusCode;
+use axum::http::header::WWW_AUTHENTICATE;
+use axum::middleware;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tracing::{error, info, warn};
+
+mod auth;
+mod calendar;
+mod climate;
+mod data_quality;
+mod digest;
+mod export;
+mod net;
+mod webhook;
+
+pub async fn start_http_server(state: Arc<ServerState>) -> anyhow::Result<()> {
+    let config = state.config.load_full();
+    let address = config.http_address.clone();
+    let listener = TcpListener::bind(&address).await?;
+    info!("HTTP API listening on {address}");
+    if config.net_websocket && config.net_tls.is_some() {
+        warn!(
+            "The net WebSocket at /net isn't covered by net_tls, \
+            it needs a TLS proxy in front of the HTTP API"
+        );
+    }
+
+    let user_routes = Router::new()
+        .route("/users/{user_id}/climate", get(climate::climate))
+        .route(
+            "/users/{user_id}/data-quality",
+            get(data_quality::data_quality),
+        )
+        .route("/users/{user_id}/digest", get(digest::digest))
+        .route(
+            "/users/{user_id}/weather.ics",
+            get(calendar::weather_calendar),
+        )
+        .route(
+            "/users/{user_id}/hourly-weather/export",
+            get(export::export_hourly_weather),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_user,
+        ));
+    let admin_routes = Router::new()
+        .route("/webhooks/deliveries", get(webhook::deliveries))
+        .route("/webhooks/deliveries/{id}/replay", post(webhook::replay))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_admin,
+        ));
+    // The WebSocket authenticates in the handshake of the protocol
+    let router = Router::new()
+        .merge(user_routes)
+        .merge(admin_routes)
+        .route("/net", get(net::net_websocket))
+        .with_state(state);
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router).await {
+            error!("HTTP API stopped: {e}");
+        }
+    });
+
+    Ok(())
+}
+
+pub enum HttpError {
+    /// Missing or unknown token
+    Unauthorized,
+    /// Valid token without access to the resource
+    Forbidden(String),
+    NotFound(String),
+    Internal(anyhow::Error),
+}
+
+impl IntoResponse for HttpError {
+    fn into_response(self) -> Response {
+        match self {
+            HttpError::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                [(WWW_AUTHENTICATE, "Bearer")],
+                "Missing or invalid token",
+            )
+                .into_response(),
+            HttpError::Forbidden(message) => (StatusCode::FORBIDDEN, message).into_response(),
+            HttpError::NotFound(message) => (StatusCode::NOT_FOUND, message).into_response(),
+            HttpError::Internal(e) => {
+                error!("HTTP request failed: {e:#}");
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+impl From<anyhow::Error> for HttpError {
+    fn from(e: anyhow::Error) -> Self {
+        HttpError::Internal(e)
+    }
+}
+
+pub type HttpResult<T> = Result<T, HttpError>;
@@ -0,0 +1,69 @@
+use crate::http::HttpError;
+use crate::state::ServerState;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::HeaderMap;
+use axum::http::header::AUTHORIZATION;
+use axum::middleware::Next;
+use axum::response::Response;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The token can be passed as a query parameter for clients that can't set headers, e.g.
+/// calendar apps subscribing to the ICS feed.
+#[derive(Deserialize)]
+pub struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Routes of a user accept the server token and the token of that user, like the net protocol.
+pub async fn require_user(
+    State(state): State<Arc<ServerState>>,
+    Path(params): Path<HashMap<String, String>>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Result<Response, HttpError> {
+    let token = request_token(&headers, query.token.as_deref());
+    let access = state
+        .services
+        .net
+        .authenticate(token)
+        .ok_or(HttpError::Unauthorized)?;
+    let user_id = params
+        .get("user_id")
+        .map(String::as_str)
+        .unwrap_or_default();
+    if !access.allows(user_id) {
+        return Err(HttpError::Forbidden(format!(
+            "Token does not grant access to user {user_id}"
+        )));
+    }
+    Ok(next.run(request).await)
+}
+
+/// Admin routes only accept the server token, and are closed while none is configured.
+pub async fn require_admin(
+    State(state): State<Arc<ServerState>>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Result<Response, HttpError> {
+    let token = request_token(&headers, query.token.as_deref());
+    if !state.services.net.is_admin(token) {
+        return Err(HttpError::Unauthorized);
+    }
+    Ok(next.run(request).await)
+}
+
+/// `Authorization: Bearer <token>`, or the `token` query parameter.
+fn request_token<'a>(headers: &'a HeaderMap, query: Option<&'a str>) -> Option<&'a str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .or(query)
+}
@@ -0,0 +1,55 @@
+use crate::http::{HttpError, HttpResult};
+use crate::services::calendar::CalendarOptions;
+use crate::services::export::column::UnitSystem;
+use crate::state::ServerState;
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+pub struct CalendarQuery {
+    #[serde(default)]
+    sun: bool,
+    #[serde(default)]
+    severe: bool,
+    #[serde(default)]
+    units: UnitSystem,
+    #[serde(default = "default_past_days")]
+    past_days: u32,
+}
+
+fn default_past_days() -> u32 {
+    7
+}
+
+pub async fn weather_calendar(
+    State(state): State<Arc<ServerState>>,
+    Path(user_id): Path<String>,
+    Query(query): Query<CalendarQuery>,
+) -> HttpResult<Response> {
+    if !state.config.load().users.contains_key(&user_id) {
+        return Err(HttpError::NotFound(format!(
+            "User '{user_id}' does not exist"
+        )));
+    }
+
+    let options = CalendarOptions {
+        sun: query.sun,
+        severe: query.severe,
+        units: query.units,
+        past_days: query.past_days,
+    };
+    let body = state
+        .services
+        .calendar
+        .weather_calendar(&user_id, &options)
+        .await?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        body,
+    )
+        .into_response())
+}
@@ -0,0 +1,33 @@
+use crate::http::{HttpError, HttpResult};
+use crate::services::climate::Climate;
+use crate::state::ServerState;
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+pub struct ClimateQuery {
+    /// Includes the normals of every day of the year
+    #[serde(default)]
+    daily: bool,
+}
+
+pub async fn climate(
+    State(state): State<Arc<ServerState>>,
+    Path(user_id): Path<String>,
+    Query(query): Query<ClimateQuery>,
+) -> HttpResult<Json<Climate>> {
+    if !state.config.load().users.contains_key(&user_id) {
+        return Err(HttpError::NotFound(format!(
+            "User '{user_id}' does not exist"
+        )));
+    }
+
+    let climate = state
+        .services
+        .climate
+        .climate(&user_id, query.daily)
+        .await?;
+    Ok(Json(climate))
+}
@@ -0,0 +1,61 @@
+use crate::database::entity::data_quality;
+use crate::http::{HttpError, HttpResult};
+use crate::state::ServerState;
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Serialize)]
+pub struct FlaggedHourResponse {
+    time: DateTime<Utc>,
+    checks: Vec<String>,
+    message: String,
+    /// The rejected values as exported in NDJSON
+    payload: serde_json::Value,
+    occurrences: i32,
+    first_flagged_at: DateTime<Utc>,
+    last_flagged_at: DateTime<Utc>,
+}
+
+impl From<data_quality::Model> for FlaggedHourResponse {
+    fn from(model: data_quality::Model) -> Self {
+        Self {
+            time: model.time_utc.and_utc(),
+            checks: model.checks.split(',').map(str::to_string).collect(),
+            message: model.message,
+            payload: serde_json::from_str(&model.payload)
+                .unwrap_or(serde_json::Value::String(model.payload)),
+            occurrences: model.occurrences,
+            first_flagged_at: model.first_flagged_at_utc.and_utc(),
+            last_flagged_at: model.last_flagged_at_utc.and_utc(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DataQualityQuery {
+    limit: Option<u64>,
+}
+
+pub async fn data_quality(
+    State(state): State<Arc<ServerState>>,
+    Path(user_id): Path<String>,
+    Query(query): Query<DataQualityQuery>,
+) -> HttpResult<Json<Vec<FlaggedHourResponse>>> {
+    if !state.config.load().users.contains_key(&user_id) {
+        return Err(HttpError::NotFound(format!(
+            "User '{user_id}' does not exist"
+        )));
+    }
+
+    let flagged = state
+        .services
+        .weather
+        .data_quality(&user_id, query.limit.unwrap_or(100))
+        .await?;
+    Ok(Json(
+        flagged.into_iter().map(FlaggedHourResponse::from).collect(),
+    ))
+}
@@ -0,0 +1,42 @@
+use crate::http::{HttpError, HttpResult};
+use crate::services::digest::DigestFormat;
+use crate::services::export::column::UnitSystem;
+use crate::state::ServerState;
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+pub struct DigestQuery {
+    /// Local day of the user, today if omitted
+    date: Option<NaiveDate>,
+    #[serde(default)]
+    format: DigestFormat,
+    #[serde(default)]
+    units: UnitSystem,
+}
+
+pub async fn digest(
+    State(state): State<Arc<ServerState>>,
+    Path(user_id): Path<String>,
+    Query(query): Query<DigestQuery>,
+) -> HttpResult<Response> {
+    if !state.config.load().users.contains_key(&user_id) {
+        return Err(HttpError::NotFound(format!(
+            "User '{user_id}' does not exist"
+        )));
+    }
+
+    let digest = state
+        .services
+        .digest
+        .digest(&user_id, query.date, query.units)
+        .await?
+        .ok_or_else(|| HttpError::NotFound(format!("No weather stored for user '{user_id}'")))?;
+    let body = state.services.digest.render(&digest, query.format)?;
+
+    Ok(([(header::CONTENT_TYPE, query.format.content_type())], body).into_response())
+}
@@ -0,0 +1,62 @@
+use crate::http::{HttpError, HttpResult};
+use crate::services::export::column::UnitSystem;
+use crate::services::export::format::ExportFormat;
+use crate::services::export::{ExportRequest, day_bounds};
+use crate::state::ServerState;
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    /// First day to export (UTC)
+    from: Option<NaiveDate>,
+    /// Last day to export (UTC, inclusive)
+    to: Option<NaiveDate>,
+    #[serde(default)]
+    format: ExportFormat,
+    #[serde(default)]
+    units: UnitSystem,
+}
+
+pub async fn export_hourly_weather(
+    State(state): State<Arc<ServerState>>,
+    Path(user_id): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> HttpResult<Response> {
+    if !state.config.load().users.contains_key(&user_id) {
+        return Err(HttpError::NotFound(format!(
+            "User '{user_id}' does not exist"
+        )));
+    }
+
+    let file_name = format!("{user_id}-hourly-weather.{}", query.format.extension());
+    let (from, to) = day_bounds(query.from, query.to);
+    let request = ExportRequest {
+        user_id,
+        from,
+        to,
+        format: query.format,
+        units: query.units,
+    };
+    let stream = state.services.export.export_hourly_weather(request)?;
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                query.format.content_type().to_string(),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
@@ -0,0 +1,233 @@
+use crate::http::{HttpError, HttpResult};
+use crate::net::HANDSHAKE_TIMEOUT;
+use crate::services::net::NetAccess;
+use crate::state::ServerState;
+use anyhow::Context;
+use axum::extract::State;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::Response;
+use bytes::BytesMut;
+use futures::stream::{SplitSink, SplitStream};
+use futures::{SinkExt, StreamExt, future};
+use omnistat_net::checksum::{CHECKSUM_SIZE, Checksums};
+use omnistat_net::codec::TypedCodec;
+use omnistat_net::compression::Compression;
+use omnistat_net::error::NetError;
+use omnistat_net::handshake;
+use omnistat_net::json::{self, JsonClientFrame, JsonServerFrame, JsonWelcome};
+use omnistat_net::limits::FrameLimits;
+use omnistat_net::rpc::{self, ClientFrame, PayloadFormat, ServerFrame};
+use std::io::{self, ErrorKind};
+use std::sync::Arc;
+use tokio_util::codec::{Decoder, Encoder};
+use tracing::{debug, warn};
+
+/// Subprotocol of WebSocket clients sending the frames of the TCP listener as binary messages.
+const BINARY_PROTOCOL: &str = "omnistat";
+/// Subprotocol of WebSocket clients sending [`omnistat_net::json`] as text messages.
+const JSON_PROTOCOL: &str = "omnistat.json";
+
+/// Bytes a frame has in front of its data at most, `length u32 | flags u8 | [checksum u32]`.
+const FRAME_HEADER_SIZE: usize = 5 + CHECKSUM_SIZE;
+
+/// The net protocol for clients that can't open TCP connections, like browsers. Clients without
+/// a subprotocol get the binary one.
+///
+/// Served with the transport security of the HTTP API, which is plain unless a TLS proxy is in
+/// front of it, so it is only available with `net_websocket = true`.
+pub async fn net_websocket(
+    State(state): State<Arc<ServerState>>,
+    upgrade: WebSocketUpgrade,
+) -> HttpResult<Response> {
+    let config = state.config.load();
+    if !config.net_websocket {
+        return Err(HttpError::NotFound(
+            "Net WebSocket disabled, net_websocket isn't set".to_string(),
+        ));
+    }
+    let limits = FrameLimits::from(&config.net_limits);
+
+    Ok(upgrade
+        .protocols([BINARY_PROTOCOL, JSON_PROTOCOL])
+        .max_message_size(limits.max_frame_size + FRAME_HEADER_SIZE)
+        .on_upgrade(move |socket| async move {
+            let format = match socket.protocol().map(|protocol| protocol.as_bytes()) {
+                Some(protocol) if protocol == JSON_PROTOCOL.as_bytes() => PayloadFormat::Json,
+                _ => PayloadFormat::Bincode,
+            };
+            debug!("Net WebSocket opened with {format:?} payloads");
+            if let Err(e) = serve_websocket(state, socket, format).await {
+                warn!("Net WebSocket failed: {e:#}");
+            }
+        }))
+}
+
+async fn serve_websocket(
+    state: Arc<ServerState>,
+    socket: WebSocket,
+    format: PayloadFormat,
+) -> anyhow::Result<()> {
+    let (mut sender, mut receiver) = socket.split();
+    let hello = tokio::time::timeout(HANDSHAKE_TIMEOUT, receiver.next())
+        .await
+        .map_err(|_| anyhow::anyhow!("Handshake timed out"))?;
+    let Some(hello) = hello.transpose()? else {
+        return Ok(());
+    };
+
+    let mut access = None;
+    let authorize = |token: Option<&str>| {
+        access = state.services.net.authenticate(token);
+        access.is_some()
+    };
+    match format {
+        PayloadFormat::Bincode => {
+            let config = state.config.load_full();
+            let compression = Compression::from(&config.net_compression);
+            let hello = hello.into_data();
+            let mut reply = Vec::new();
+            let mut stream = tokio::io::join(&hello[..], &mut reply);
+            let negotiated = handshake::accept(&mut stream, compression.codec, authorize).await;
+            sender.send(Message::binary(reply)).await?;
+            let negotiated = match negotiated {
+                Ok(negotiated) => negotiated,
+                Err(e) => return reject(sender, e).await,
+            };
+            let access = access.context("Handshake accepted without access")?;
+            debug!(
+                "Net WebSocket speaks protocol version {} with {:?}, access {access:?}",
+                negotiated.version, negotiated.codec
+            );
+
+            let compression = Compression {
+                codec: negotiated.codec,
+                ..compression
+            };
+            let limits = FrameLimits::from(&config.net_limits);
+            let checksums = Checksums::from(&config.net_checksums);
+            let codec = TypedCodec::new(limits, compression, checksums);
+            serve_binary(state, access, sender, receiver, codec).await
+        }
+        PayloadFormat::Json => {
+            let accepted = match hello {
+                Message::Text(hello) => json::accept(&hello, authorize),
+                _ => Err(NetError::Handshake(
+                    "hello is not a text message".to_string(),
+                )),
+            };
+            let welcome = serde_json::to_string(&JsonWelcome::from(&accepted))?;
+            sender.send(Message::text(welcome)).await?;
+            if let Err(e) = accepted {
+                return reject(sender, e).await;
+            }
+            let access = access.context("Handshake accepted without access")?;
+            debug!("Net WebSocket speaks JSON, access {access:?}");
+
+            serve_json(state, access, sender, receiver).await
+        }
+    }
+}
+
+/// Closes the WebSocket after the client was told why it was rejected.
+async fn reject(mut sender: SplitSink<WebSocket, Message>, e: NetError) -> anyhow::Result<()> {
+    let _ = sender.close().await;
+    Err(e.into())
+}
+
+/// Every binary message carries exactly one frame.
+async fn serve_binary(
+    state: Arc<ServerState>,
+    access: NetAccess,
+    sender: SplitSink<WebSocket, Message>,
+    receiver: SplitStream<WebSocket>,
+    mut decoder: TypedCodec<ClientFrame, ServerFrame>,
+) -> anyhow::Result<()> {
+    let mut encoder = decoder.clone();
+
+    let incoming = messages(receiver).filter_map(move |message| {
+        future::ready(match message {
+            Message::Binary(data) => Some(decode_frame(&mut decoder, BytesMut::from(data))),
+            Message::Text(_) => Some(Err(NetError::InvalidFrame(
+                "text message on a binary connection".to_string(),
+            ))),
+            _ => None,
+        })
+    });
+    let outgoing = outgoing(sender).with(move |frame: ServerFrame| {
+        let mut buffer = BytesMut::new();
+        let encoded = encoder.encode(frame, &mut buffer);
+        future::ready(encoded.map(|_| Message::binary(buffer.freeze())))
+    });
+
+    let handlers = Arc::new(state.services.net.handlers(access));
+    rpc::serve_frames(incoming, outgoing, handlers, PayloadFormat::Bincode).await?;
+    debug!("Net WebSocket closed");
+    Ok(())
+}
+
+/// Every text message carries exactly one JSON object.
+async fn serve_json(
+    state: Arc<ServerState>,
+    access: NetAccess,
+    sender: SplitSink<WebSocket, Message>,
+    receiver: SplitStream<WebSocket>,
+) -> anyhow::Result<()> {
+    let incoming = messages(receiver).filter_map(|message| {
+        future::ready(match message {
+            Message::Text(text) => Some(
+                serde_json::from_str::<JsonClientFrame>(&text)
+                    .map_err(NetError::from)
+                    .and_then(JsonClientFrame::into_frame),
+            ),
+            Message::Binary(_) => Some(Err(NetError::InvalidFrame(
+                "binary message on a JSON connection".to_string(),
+            ))),
+            _ => None,
+        })
+    });
+    let outgoing = outgoing(sender).with(|frame: ServerFrame| {
+        let encoded =
+            JsonServerFrame::try_from(frame).and_then(|frame| Ok(serde_json::to_string(&frame)?));
+        future::ready(encoded.map(Message::text))
+    });
+
+    let handlers = Arc::new(state.services.net.handlers(access));
+    rpc::serve_frames(incoming, outgoing, handlers, PayloadFormat::Json).await?;
+    debug!("Net WebSocket closed");
+    Ok(())
+}
+
+fn decode_frame(
+    decoder: &mut TypedCodec<ClientFrame, ServerFrame>,
+    mut buffer: BytesMut,
+) -> Result<ClientFrame, NetError> {
+    let frame = decoder
+        .decode(&mut buffer)?
+        .ok_or_else(|| NetError::InvalidFrame("message ends within the frame".to_string()))?;
+    if !buffer.is_empty() {
+        return Err(NetError::InvalidFrame(
+            "message continues after the frame".to_string(),
+        ));
+    }
+    Ok(frame)
+}
+
+/// Messages until the client closes the connection. WebSocket errors end it as well, they mean
+/// the client is gone or broke the WebSocket protocol, which axum already rejected.
+fn messages(receiver: SplitStream<WebSocket>) -> impl futures::Stream<Item = Message> {
+    receiver
+        .take_while(|message| {
+            if let Err(e) = message {
+                debug!("Net WebSocket ended: {e}");
+            }
+            future::ready(matches!(message, Ok(message) if !matches!(message, Message::Close(_))))
+        })
+        .filter_map(|message| future::ready(message.ok()))
+}
+
+/// Failing to send means the client is gone, which ends a connection as usual.
+fn outgoing(
+    sender: SplitSink<WebSocket, Message>,
+) -> impl futures::Sink<Message, Error = NetError> + Send + 'static {
+    sender.sink_map_err(|e| NetError::IO(io::Error::new(ErrorKind::BrokenPipe, e)))
+}
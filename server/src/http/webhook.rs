@@ -0,0 +1,74 @@
+use crate::database::entity::webhook_delivery;
+use crate::database::entity_ext::webhook_delivery::DeliveryStatus;
+use crate::http::{HttpError, HttpResult};
+use crate::state::ServerState;
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Serialize)]
+pub struct DeliveryResponse {
+    id: i32,
+    webhook: String,
+    event: String,
+    status: String,
+    attempts: i32,
+    next_attempt_at: DateTime<Utc>,
+    last_error: Option<String>,
+    created_at: DateTime<Utc>,
+    delivered_at: Option<DateTime<Utc>>,
+}
+
+impl From<webhook_delivery::Model> for DeliveryResponse {
+    fn from(model: webhook_delivery::Model) -> Self {
+        Self {
+            id: model.id,
+            webhook: model.webhook,
+            event: model.event,
+            status: model.status,
+            attempts: model.attempts,
+            next_attempt_at: model.next_attempt_at_utc.and_utc(),
+            last_error: model.last_error,
+            created_at: model.created_at_utc.and_utc(),
+            delivered_at: model.delivered_at_utc.map(|time| time.and_utc()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DeliveriesQuery {
+    /// Dead-lettered deliveries if omitted
+    status: Option<DeliveryStatus>,
+    limit: Option<u64>,
+}
+
+pub async fn deliveries(
+    State(state): State<Arc<ServerState>>,
+    Query(query): Query<DeliveriesQuery>,
+) -> HttpResult<Json<Vec<DeliveryResponse>>> {
+    let deliveries = state
+        .services
+        .webhook
+        .deliveries(
+            query.status.unwrap_or(DeliveryStatus::Dead),
+            query.limit.unwrap_or(100),
+        )
+        .await?;
+    Ok(Json(deliveries.into_iter().map(Into::into).collect()))
+}
+
+/// Sends the delivery again right away and responds with its new state.
+pub async fn replay(
+    State(state): State<Arc<ServerState>>,
+    Path(id): Path<i32>,
+) -> HttpResult<Json<DeliveryResponse>> {
+    let delivery = state
+        .services
+        .webhook
+        .replay(id)
+        .await?
+        .ok_or_else(|| HttpError::NotFound(format!("Webhook delivery {id} does not exist")))?;
+    Ok(Json(delivery.into()))
+}
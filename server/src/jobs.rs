@@ -2,11 +2,17 @@ use crate::state::ServerState;
 use std::sync::Arc;
 use tokio_cron_scheduler::JobScheduler;
 
+mod daily_weather_report;
 mod hourly_weather_report;
+mod retention;
+mod webhook_delivery;
 
 pub async fn start_jobs(state: Arc<ServerState>) -> anyhow::Result<()> {
     let scheduler = JobScheduler::new().await?;
-    hourly_weather_report::job_hourly_weather_report(&scheduler, state).await?;
+    hourly_weather_report::job_hourly_weather_report(&scheduler, state.clone()).await?;
+    daily_weather_report::job_daily_weather_report(&scheduler, state.clone()).await?;
+    webhook_delivery::job_webhook_delivery(&scheduler, state.clone()).await?;
+    retention::job_retention(&scheduler, state).await?;
     scheduler.start().await?;
     Ok(())
 }
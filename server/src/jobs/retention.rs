@@ -0,0 +1,17 @@
+use crate::state::ServerState;
+use std::sync::Arc;
+use tokio_cron_scheduler::{Job, JobScheduler};
+
+pub async fn job_retention(
+    scheduler: &JobScheduler,
+    state: Arc<ServerState>,
+) -> anyhow::Result<()> {
+    let job = Job::new_async("0 30 3 * * *", move |_uuid, _l| {
+        let state = state.clone();
+        Box::pin(async move {
+            state.services.retention.run(false).await;
+        })
+    })?;
+    scheduler.add(job).await?;
+    Ok(())
+}
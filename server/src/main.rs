@@ -1,11 +1,20 @@
+use crate::cli::{Cli, Command};
+use crate::config::reload::start_config_watcher;
+use crate::http::start_http_server;
 use crate::jobs::start_jobs;
+use crate::net::start_net_server;
 use crate::state::ServerState;
-use tracing::info;
+use clap::Parser;
+use tracing::{error, info};
 
 mod apis;
+mod cli;
 mod config;
 mod database;
+mod events;
+mod http;
 mod jobs;
+mod net;
 mod services;
 mod state;
 
@@ -13,16 +22,38 @@ mod state;
 async fn main() {
     init_tracing();
 
-    let state = ServerState::initialize().await.unwrap();
+    let command = Cli::parse().command.unwrap_or(Command::Serve);
+    if let Err(e) = command.run().await {
+        error!("{e:#}");
+        std::process::exit(1);
+    }
+}
+
+async fn serve() -> anyhow::Result<()> {
+    let state = ServerState::initialize().await?;
     info!("Initialized state");
 
-    start_jobs(state.clone()).await.unwrap();
+    start_jobs(state.clone()).await?;
     info!("Started jobs");
 
+    state.services.alert.start_listening();
+    state.services.climate.start_listening();
+    state.services.webhook.start_listening();
+    info!("Listening for alert, climate and webhook events");
+
+    start_config_watcher(state.config.clone())?;
+    info!("Watching config for changes");
+
+    start_http_server(state.clone()).await?;
+    info!("Started HTTP API");
+
+    start_net_server(state.clone()).await?;
+
     info!("Server started");
 
-    tokio::signal::ctrl_c().await.unwrap();
+    tokio::signal::ctrl_c().await?;
     info!("Shutting down...");
+    Ok(())
 }
 
 fn init_tracing() {
@@ -37,5 +68,7 @@ fn init_tracing() {
             }
         }))
         .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
+        // Commands like export and digest write their output to stdout
+        .with_writer(std::io::stderr)
         .init();
 }
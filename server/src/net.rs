@@ -0,0 +1,116 @@
+use crate::state::ServerState;
+use anyhow::Context;
+use omnistat_net::checksum::Checksums;
+use omnistat_net::compression::Compression;
+use omnistat_net::handshake;
+use omnistat_net::limits::FrameLimits;
+use omnistat_net::rpc;
+use omnistat_net::tls::{self, TlsAcceptor};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, error, info, warn};
+
+/// Clients that don't finish the handshake in time are disconnected.
+pub(crate) const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Listens for native clients speaking the omnistat-net protocol, if an address is configured.
+pub async fn start_net_server(state: Arc<ServerState>) -> anyhow::Result<()> {
+    let config = state.config.load_full();
+    let Some(address) = config.net_address.clone() else {
+        info!("Net protocol disabled, no net_address configured");
+        return Ok(());
+    };
+    let tls = match &config.net_tls {
+        Some(tls) => Some(
+            tls::acceptor(&tls.certificate, &tls.private_key)
+                .context("Failed to set up TLS of the net protocol")?,
+        ),
+        None => None,
+    };
+    let listener = TcpListener::bind(&address).await?;
+    match tls {
+        Some(_) => info!("Net protocol listening on {address} with TLS"),
+        None => info!("Net protocol listening on {address}"),
+    }
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => {
+                    let state = state.clone();
+                    let tls = tls.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(state, tls, stream, peer).await {
+                            warn!("Net connection {peer} failed: {e:#}");
+                        }
+                    });
+                }
+                Err(e) => error!("Failed to accept net connection: {e}"),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+async fn handle_connection(
+    state: Arc<ServerState>,
+    tls: Option<TlsAcceptor>,
+    stream: TcpStream,
+    peer: SocketAddr,
+) -> anyhow::Result<()> {
+    debug!("Net connection {peer} opened");
+    stream.set_nodelay(true)?;
+
+    match tls {
+        Some(acceptor) => {
+            let stream = tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream))
+                .await
+                .map_err(|_| anyhow::anyhow!("TLS handshake timed out"))?
+                .context("TLS handshake failed")?;
+            serve_connection(state, stream, peer).await
+        }
+        None => serve_connection(state, stream, peer).await,
+    }
+}
+
+async fn serve_connection<S>(
+    state: Arc<ServerState>,
+    mut stream: S,
+    peer: SocketAddr,
+) -> anyhow::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let config = state.config.load_full();
+    let mut access = None;
+    let authorize = |token: Option<&str>| {
+        access = state.services.net.authenticate(token);
+        access.is_some()
+    };
+    let compression = Compression::from(&config.net_compression);
+    let handshake = handshake::accept(&mut stream, compression.codec, authorize);
+    let negotiated = tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake)
+        .await
+        .map_err(|_| anyhow::anyhow!("Handshake timed out"))??;
+    let access = access.context("Handshake accepted without access")?;
+    debug!(
+        "Net connection {peer} speaks protocol version {} with {:?}, access {access:?}",
+        negotiated.version, negotiated.codec
+    );
+
+    let handlers = Arc::new(state.services.net.handlers(access));
+    let (reader, writer) = tokio::io::split(stream);
+    let limits = FrameLimits::from(&config.net_limits);
+    let compression = Compression {
+        codec: negotiated.codec,
+        ..compression
+    };
+    let checksums = Checksums::from(&config.net_checksums);
+    rpc::serve(reader, writer, handlers, limits, compression, checksums).await?;
+    debug!("Net connection {peer} closed");
+    Ok(())
+}
@@ -1,25 +1,83 @@
 use crate::apis::Apis;
-use crate::config::Config;
+use crate::config::SharedConfig;
+use crate::events::{EventBus, ServerEvent};
 use sea_orm::DatabaseConnection;
 use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::TryRecvError;
 
-mod weather;
+pub mod alert;
+pub mod calendar;
+pub mod climate;
+pub mod digest;
+pub mod export;
+pub mod net;
+pub mod retention;
+pub mod weather;
+pub mod webhook;
 
 pub struct ServiceInitContext {
-    pub config: Arc<Config>,
+    pub config: SharedConfig,
     pub db: Arc<DatabaseConnection>,
     pub apis: Arc<Apis>,
+    pub events: EventBus,
 }
 
 #[derive(Clone)]
 pub struct Services {
+    pub alert: Arc<alert::AlertService>,
+    pub calendar: Arc<calendar::CalendarService>,
+    pub climate: Arc<climate::ClimateService>,
+    pub digest: Arc<digest::DigestService>,
+    pub export: Arc<export::ExportService>,
+    pub net: Arc<net::NetService>,
+    pub retention: Arc<retention::RetentionService>,
     pub weather: Arc<weather::WeatherService>,
+    pub webhook: Arc<webhook::WebhookService>,
+    events: EventBus,
 }
 
 impl Services {
     pub fn initialize(context: ServiceInitContext) -> Arc<Self> {
         Arc::new(Self {
+            events: context.events.clone(),
+            alert: alert::AlertService::initialize(&context),
+            calendar: calendar::CalendarService::initialize(&context),
+            climate: climate::ClimateService::initialize(&context),
+            digest: digest::DigestService::initialize(&context),
+            export: export::ExportService::initialize(&context),
+            net: net::NetService::initialize(&context),
+            retention: retention::RetentionService::initialize(&context),
             weather: weather::WeatherService::initialize(&context),
+            webhook: webhook::WebhookService::initialize(&context),
         })
     }
+
+    /// Events published from now on, for [`Services::handle_events`].
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerEvent> {
+        self.events.subscribe()
+    }
+
+    /// Reacts to received events like the listeners of a running server do, including the events
+    /// that causes, then waits for the deliveries of alerts and webhooks. For commands that
+    /// publish events without the listeners running.
+    pub async fn handle_events(&self, events: &mut broadcast::Receiver<ServerEvent>) {
+        loop {
+            match events.try_recv() {
+                Ok(event) => {
+                    self.alert.handle(&event).await;
+                    self.climate.handle(&event).await;
+                    self.webhook.handle(&event).await;
+                }
+                Err(TryRecvError::Lagged(skipped)) => {
+                    self.alert.handle_missed(skipped).await;
+                    self.climate.handle_missed(skipped).await;
+                    self.webhook.handle_missed(skipped);
+                }
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+        self.alert.wait_for_deliveries().await;
+        self.webhook.deliver_due().await;
+    }
 }
@@ -0,0 +1,236 @@
+use crate::config::SharedConfig;
+use crate::config::alert::AlertRule;
+use crate::database::entity::{alert_event, daily_weather, hourly_weather};
+use crate::events::{AlertFired, EventBus, ServerEvent};
+use crate::services::ServiceInitContext;
+use crate::services::weather::hour::WeatherHour;
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use chrono_tz::Tz;
+use rule::AlertMatch;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::task::TaskTracker;
+use tracing::{error, info, warn};
+
+mod notifier;
+mod rule;
+
+/// Notifiers give up after this, a hanging endpoint must not hold up other deliveries.
+pub(crate) const NOTIFY_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+pub(crate) const NOTIFY_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct AlertService {
+    config: SharedConfig,
+    db: Arc<DatabaseConnection>,
+    events: EventBus,
+    http: reqwest::Client,
+    /// Deliveries run in the background, evaluating the next user doesn't wait for them
+    deliveries: TaskTracker,
+}
+
+impl AlertService {
+    pub fn initialize(context: &ServiceInitContext) -> Arc<Self> {
+        Arc::new(Self {
+            config: context.config.clone(),
+            db: context.db.clone(),
+            events: context.events.clone(),
+            http: reqwest::Client::builder()
+                .connect_timeout(NOTIFY_CONNECT_TIMEOUT)
+                .timeout(NOTIFY_TIMEOUT)
+                .build()
+                .expect("Notifier HTTP client should build"),
+            deliveries: TaskTracker::new(),
+        })
+    }
+
+    /// Evaluates the rules of a user whenever their forecast was synced.
+    pub fn start_listening(self: &Arc<Self>) {
+        let service = self.clone();
+        let mut events = self.events.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => service.handle(&event).await,
+                    Err(RecvError::Lagged(skipped)) => service.handle_missed(skipped).await,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Evaluates the rules of a user whose forecast was synced.
+    pub async fn handle(&self, event: &ServerEvent) {
+        if let ServerEvent::HourlyWeatherSynced { user_id } = event
+            && let Err(e) = self.evaluate_user(user_id).await
+        {
+            error!("Failed to evaluate alerts of user '{user_id}': {e:#}");
+        }
+    }
+
+    /// Catches up on missed events by evaluating the rules of every user.
+    pub async fn handle_missed(&self, skipped: u64) {
+        warn!("Missed {skipped} event(s), evaluating the alerts of all users");
+        self.evaluate_all().await;
+    }
+
+    pub async fn evaluate_all(&self) {
+        let config = self.config.load_full();
+        for user_id in config.users.keys() {
+            if let Err(e) = self.evaluate_user(user_id).await {
+                error!("Failed to evaluate alerts of user '{user_id}': {e:#}");
+            }
+        }
+    }
+
+    /// Evaluates every rule of the user against the stored forecast, returns the amount of fired alerts.
+    ///
+    /// A rule fires once when it starts matching and not again until it stopped matching.
+    /// Rules with a local time window fire once per day the window matches.
+    pub async fn evaluate_user(&self, user_id: &str) -> anyhow::Result<usize> {
+        let config = self.config.load_full();
+        let user = config.get_user_or_err(user_id)?;
+        let Some(within_hours) = user.alerts.iter().map(|rule| rule.within_hours).max() else {
+            return Ok(0);
+        };
+
+        let now = Utc::now();
+        let current_hour = now.duration_trunc(TimeDelta::hours(1))?;
+        let until = now + TimeDelta::hours(within_hours as i64);
+        let hours: Vec<WeatherHour> = hourly_weather::Entity::find_by_user_in_range(
+            user_id,
+            Some(current_hour.naive_utc()),
+            Some(until.naive_utc()),
+        )
+        .all(self.db.as_ref())
+        .await?
+        .iter()
+        .map(WeatherHour::from)
+        .collect();
+        let timezone =
+            daily_weather::Entity::find_timezone_by_user(user_id, self.db.as_ref()).await?;
+
+        let mut fired = 0;
+        for rule in &user.alerts {
+            let first_match = rule.first_match(&hours, now, timezone);
+            let active =
+                alert_event::Entity::find_active(user_id, &rule.id, self.db.as_ref()).await?;
+
+            match (first_match, active) {
+                (None, None) => {}
+                (None, Some(active)) => {
+                    alert_event::Entity::resolve(active.id, now.naive_utc(), self.db.as_ref())
+                        .await?;
+                    info!("Alert '{}' of user '{user_id}' resolved", rule.id);
+                }
+                (Some(first_match), Some(active))
+                    if !is_new_occurrence(rule, &active, &first_match, timezone) => {}
+                (Some(first_match), active) => {
+                    if let Some(active) = active {
+                        alert_event::Entity::resolve(active.id, now.naive_utc(), self.db.as_ref())
+                            .await?;
+                    }
+                    self.fire(user_id, rule, first_match, timezone).await?;
+                    fired += 1;
+                }
+            }
+        }
+        Ok(fired)
+    }
+
+    /// Waits until the alerts fired so far are delivered, for commands exiting afterwards.
+    pub async fn wait_for_deliveries(&self) {
+        self.deliveries.close();
+        self.deliveries.wait().await;
+        self.deliveries.reopen();
+    }
+
+    /// Sends a made up alert through a notifier, to check its settings.
+    pub async fn test_notifier(&self, name: &str) -> anyhow::Result<()> {
+        let config = self.config.load_full();
+        let notifier = config
+            .notifiers
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Notifier '{name}' does not exist in config."))?;
+        let now = Utc::now();
+        let alert = AlertFired {
+            user_id: "test".to_string(),
+            rule_id: "test".to_string(),
+            rule: "test notification".to_string(),
+            message: format!("Test notification of notifier '{name}'"),
+            first_match: now,
+            fired_at: now,
+        };
+        notifier.send(&self.http, &alert).await
+    }
+
+    async fn fire(
+        &self,
+        user_id: &str,
+        rule: &AlertRule,
+        first_match: AlertMatch,
+        timezone: Tz,
+    ) -> anyhow::Result<()> {
+        let fired_at = Utc::now();
+        let local_time = first_match.time.with_timezone(&timezone);
+        let alert = AlertFired {
+            user_id: user_id.to_string(),
+            rule_id: rule.id.clone(),
+            rule: rule.to_string(),
+            message: format!(
+                "{rule}: {} at {} ({timezone})",
+                first_match.observed,
+                local_time.format("%a %d %b %H:%M")
+            ),
+            first_match: first_match.time,
+            fired_at,
+        };
+
+        alert_event::ActiveModel {
+            user_id: Set(alert.user_id.clone()),
+            rule_id: Set(alert.rule_id.clone()),
+            first_match_utc: Set(alert.first_match.naive_utc()),
+            message: Set(alert.message.clone()),
+            fired_at_utc: Set(fired_at.naive_utc()),
+            resolved_at_utc: Set(None),
+            ..Default::default()
+        }
+        .insert(self.db.as_ref())
+        .await?;
+        info!("Alert fired: {}", alert.message);
+
+        let config = self.config.load_full();
+        for name in &rule.notifiers {
+            let Some(notifier) = config.notifiers.get(name) else {
+                warn!("Alert '{}' uses unknown notifier '{name}'", rule.id);
+                continue;
+            };
+            let (name, notifier) = (name.clone(), notifier.clone());
+            let (http, alert) = (self.http.clone(), alert.clone());
+            self.deliveries.spawn(async move {
+                if let Err(e) = notifier.send(&http, &alert).await {
+                    error!(
+                        "Failed to notify '{name}' of alert '{}': {e:#}",
+                        alert.rule_id
+                    );
+                }
+            });
+        }
+
+        self.events.publish(ServerEvent::AlertFired(alert));
+        Ok(())
+    }
+}
+
+/// Rules with a local time window fire again for every day their window matches.
+fn is_new_occurrence(
+    rule: &AlertRule,
+    active: &alert_event::Model,
+    first_match: &AlertMatch,
+    timezone: Tz,
+) -> bool {
+    let local_date = |time: DateTime<Utc>| time.with_timezone(&timezone).date_naive();
+    rule.between.is_some()
+        && local_date(active.first_match_utc.and_utc()) != local_date(first_match.time)
+}
@@ -0,0 +1,249 @@
+use crate::config::notifier::{NotifierConfig, SmtpTls};
+use crate::events::AlertFired;
+use crate::services::alert::NOTIFY_TIMEOUT;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+impl NotifierConfig {
+    /// Delivers the alert, every notifier type gets the same title and message.
+    pub async fn send(&self, http: &reqwest::Client, alert: &AlertFired) -> anyhow::Result<()> {
+        let title = format!("Weather alert '{}' for {}", alert.rule_id, alert.user_id);
+        match self {
+            NotifierConfig::Webhook { url } => {
+                http.post(url)
+                    .json(alert)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            NotifierConfig::Ntfy { url, topic, token } => {
+                let mut request = http
+                    .post(format!("{}/{topic}", url.trim_end_matches('/')))
+                    .header("Title", title)
+                    .header("Tags", "warning")
+                    .body(alert.message.clone());
+                if let Some(token) = token {
+                    request = request.bearer_auth(token);
+                }
+                request.send().await?.error_for_status()?;
+            }
+            NotifierConfig::Smtp {
+                host,
+                port,
+                tls,
+                username,
+                password,
+                from,
+                to,
+            } => {
+                let mut message = Message::builder()
+                    .from(from.parse::<Mailbox>()?)
+                    .subject(title);
+                for to in to {
+                    message = message.to(to.parse::<Mailbox>()?);
+                }
+                let message = message.body(alert.message.clone())?;
+
+                let mut transport = match tls {
+                    SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+                    SmtpTls::Starttls => {
+                        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?
+                    }
+                    SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+                };
+                if let Some(port) = port {
+                    transport = transport.port(*port);
+                }
+                transport = transport.timeout(Some(NOTIFY_TIMEOUT));
+                if let Some(username) = username {
+                    transport = transport.credentials(Credentials::new(
+                        username.clone(),
+                        password.clone().unwrap_or_default(),
+                    ));
+                }
+                transport.build().send(message).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::Bytes;
+    use axum::extract::State;
+    use axum::http::{HeaderMap, StatusCode, Uri};
+    use chrono::{TimeZone, Utc};
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    struct Request {
+        path: String,
+        headers: HeaderMap,
+        body: Bytes,
+    }
+
+    type Requests = Arc<Mutex<Vec<Request>>>;
+
+    /// An HTTP server answering every request with `status`, returns its URL.
+    async fn http_stub(status: StatusCode) -> (String, Requests) {
+        async fn record(
+            State((status, requests)): State<(StatusCode, Requests)>,
+            uri: Uri,
+            headers: HeaderMap,
+            body: Bytes,
+        ) -> StatusCode {
+            requests.lock().unwrap().push(Request {
+                path: uri.path().to_string(),
+                headers,
+                body,
+            });
+            status
+        }
+
+        let requests = Requests::default();
+        let app = Router::new()
+            .fallback(record)
+            .with_state((status, requests.clone()));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, requests)
+    }
+
+    /// An SMTP server accepting a single mail, returns its port and the mail once received.
+    async fn smtp_stub() -> (u16, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mail = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            writer.write_all(b"220 stub ESMTP\r\n").await.unwrap();
+            let mut mail = String::new();
+            while let Some(line) = lines.next_line().await.unwrap() {
+                let command = line.to_ascii_uppercase();
+                let reply: &[u8] = if command.starts_with("EHLO") {
+                    b"250 stub\r\n"
+                } else if command.starts_with("DATA") {
+                    writer.write_all(b"354 go ahead\r\n").await.unwrap();
+                    while let Some(line) = lines.next_line().await.unwrap() {
+                        if line == "." {
+                            break;
+                        }
+                        mail.push_str(&line);
+                        mail.push('\n');
+                    }
+                    b"250 queued\r\n"
+                } else if command.starts_with("QUIT") {
+                    writer.write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                writer.write_all(reply).await.unwrap();
+            }
+            mail
+        });
+        (port, mail)
+    }
+
+    fn alert() -> AlertFired {
+        let now = Utc.with_ymd_and_hms(2026, 10, 20, 4, 0, 0).unwrap();
+        AlertFired {
+            user_id: "home".to_string(),
+            rule_id: "frost".to_string(),
+            rule: "temperature < 0 °C within 24h".to_string(),
+            message: "temperature < 0 °C within 24h: -2.0 °C at Tue 20 Oct 06:00".to_string(),
+            first_match: now,
+            fired_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn webhook_posts_the_alert_as_json() {
+        let (url, requests) = http_stub(StatusCode::OK).await;
+        let notifier = NotifierConfig::Webhook {
+            url: format!("{url}/hook"),
+        };
+
+        notifier
+            .send(&reqwest::Client::new(), &alert())
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/hook");
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body, serde_json::to_value(alert()).unwrap());
+    }
+
+    #[tokio::test]
+    async fn ntfy_posts_the_message_to_the_topic() {
+        let (url, requests) = http_stub(StatusCode::OK).await;
+        let notifier = NotifierConfig::Ntfy {
+            url: format!("{url}/"),
+            topic: "weather".to_string(),
+            token: Some("secret".to_string()),
+        };
+
+        notifier
+            .send(&reqwest::Client::new(), &alert())
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.path, "/weather");
+        assert_eq!(request.headers["title"], "Weather alert 'frost' for home");
+        assert_eq!(request.headers["tags"], "warning");
+        assert_eq!(request.headers["authorization"], "Bearer secret");
+        assert_eq!(request.body, alert().message.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn error_status_fails_the_delivery() {
+        let (url, _) = http_stub(StatusCode::INTERNAL_SERVER_ERROR).await;
+        let notifier = NotifierConfig::Webhook { url };
+
+        let result = notifier.send(&reqwest::Client::new(), &alert()).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn smtp_sends_a_mail() {
+        let (port, mail) = smtp_stub().await;
+        let notifier = NotifierConfig::Smtp {
+            host: "127.0.0.1".to_string(),
+            port: Some(port),
+            tls: SmtpTls::None,
+            username: None,
+            password: None,
+            from: "omnistat@example.com".to_string(),
+            to: vec!["me@example.com".to_string()],
+        };
+
+        // Plain ASCII, anything else is encoded in the mail
+        let alert = AlertFired {
+            message: "Frost expected at 06:00".to_string(),
+            ..alert()
+        };
+
+        notifier
+            .send(&reqwest::Client::new(), &alert)
+            .await
+            .unwrap();
+
+        let mail = mail.await.unwrap();
+        assert!(mail.contains("Subject: Weather alert 'frost' for home"));
+        assert!(mail.contains("To: me@example.com"));
+        assert!(mail.contains("Frost expected at 06:00"));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::config::alert::{AlertCondition, AlertMetric, AlertRule, WeatherGroup};
+use crate::services::weather::hour::WeatherHour;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use omnistat_core::types::wmo_code::WMOCode;
+
+/// The first hour a rule matched, with what matched formatted for humans.
+pub struct AlertMatch {
+    pub time: DateTime<Utc>,
+    pub observed: String,
+}
+
+impl AlertRule {
+    /// Finds the first hour matching the rule, `hours` must be ordered and start at the current hour.
+    pub fn first_match(
+        &self,
+        hours: &[WeatherHour],
+        now: DateTime<Utc>,
+        timezone: Tz,
+    ) -> Option<AlertMatch> {
+        let until = now + chrono::TimeDelta::hours(self.within_hours as i64);
+        hours
+            .iter()
+            .take_while(|hour| hour.time < until)
+            .filter(|hour| match &self.between {
+                Some(between) => between.contains(hour.time.with_timezone(&timezone).time()),
+                None => true,
+            })
+            .find_map(|hour| self.condition.observe(hour))
+    }
+}
+
+impl AlertCondition {
+    fn observe(&self, hour: &WeatherHour) -> Option<AlertMatch> {
+        let observed = match self {
+            AlertCondition::Threshold {
+                metric,
+                operator,
+                threshold,
+            } => {
+                let value = metric.value(hour);
+                if !operator.compare(value, *threshold) {
+                    return None;
+                }
+                format!("{value:.1} {}", metric.unit())
+            }
+            AlertCondition::Weather { weather } => {
+                if !weather.contains(hour.wmo_code) {
+                    return None;
+                }
+                hour.wmo_code.description().to_string()
+            }
+        };
+        Some(AlertMatch {
+            time: hour.time,
+            observed,
+        })
+    }
+}
+
+impl AlertMetric {
+    /// The value of the hour in the unit of the metric.
+    pub fn value(self, hour: &WeatherHour) -> f64 {
+        match self {
+            AlertMetric::Temperature => hour.temperature.as_celsius() as f64,
+            AlertMetric::TemperatureApparent => hour.apparent_temperature.as_celsius() as f64,
+            AlertMetric::DewPoint => hour.dew_point.as_celsius() as f64,
+            AlertMetric::RelativeHumidity => hour.relative_humidity.as_0_100() as f64,
+            AlertMetric::CloudCover => hour.cloud_cover.as_0_100() as f64,
+            AlertMetric::SurfacePressure => hour.surface_pressure.as_hpa() as f64,
+            AlertMetric::WindSpeed => hour.wind_speed.as_km_h() as f64,
+            AlertMetric::WindGusts => hour.max_wind_speed.as_km_h() as f64,
+            AlertMetric::Precipitation => hour.total_precipitation.as_millimeters(),
+            AlertMetric::PrecipitationProbability => {
+                hour.precipitation_probability.as_0_100() as f64
+            }
+            AlertMetric::Rain => hour.rain.as_millimeters(),
+            AlertMetric::Snowfall => hour.snowfall.as_millimeters(),
+            AlertMetric::Visibility => hour.visibility.as_meters(),
+        }
+    }
+}
+
+impl WeatherGroup {
+    pub fn contains(self, code: WMOCode) -> bool {
+        use WMOCode::*;
+        match self {
+            WeatherGroup::Fog => matches!(code, Foggy | RimeFog),
+            WeatherGroup::Drizzle => matches!(
+                code,
+                LightDrizzle | Drizzle | HeavyDrizzle | LightFreezingDrizzle | FreezingDrizzle
+            ),
+            WeatherGroup::Rain => matches!(
+                code,
+                LightRain | Rain | HeavyRain | LightFreezingRain | FreezingRain
+            ),
+            WeatherGroup::Freezing => matches!(
+                code,
+                LightFreezingDrizzle | FreezingDrizzle | LightFreezingRain | FreezingRain
+            ),
+            WeatherGroup::Snow => matches!(
+                code,
+                LightSnow | Snow | HeavySnow | SnowGrains | LightSnowShowers | SnowShowers
+            ),
+            WeatherGroup::Showers => matches!(
+                code,
+                LightShowers | Showers | HeavyShowers | LightSnowShowers | SnowShowers
+            ),
+            WeatherGroup::Thunderstorm => matches!(
+                code,
+                Thunderstorm | LightThunderstormHail | ThunderstormHail
+            ),
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use crate::database::entity::{daily_weather, hourly_weather};
+use crate::services::ServiceInitContext;
+use crate::services::calendar::ics::IcsWriter;
+use crate::services::export::column::{Unit, UnitSystem};
+use crate::services::weather::hour::{WeatherHour, consecutive_windows, local_day_bounds};
+use chrono::{DateTime, Days, TimeDelta, Utc};
+use chrono_tz::Tz;
+use omnistat_core::types::length::Length;
+use omnistat_core::types::speed::Speed;
+use omnistat_core::types::wmo_code::WMOCode;
+use sea_orm::DatabaseConnection;
+use std::sync::Arc;
+
+mod ics;
+
+/// Hours with gusts from this speed on are severe, Beaufort 9 (strong gale) starts at 75 km/h.
+const SEVERE_GUSTS_KM_H: f32 = 75.0;
+
+pub struct CalendarOptions {
+    /// Adds timed events at sunrise and sunset
+    pub sun: bool,
+    /// Adds timed events for windows of thunderstorms, freezing or heavy precipitation and gales
+    pub severe: bool,
+    pub units: UnitSystem,
+    /// Days before today that are still published
+    pub past_days: u32,
+}
+
+pub struct CalendarService {
+    db: Arc<DatabaseConnection>,
+}
+
+impl CalendarService {
+    pub fn initialize(context: &ServiceInitContext) -> Arc<Self> {
+        Arc::new(Self {
+            db: context.db.clone(),
+        })
+    }
+
+    /// The weather of a user as an iCalendar feed with an all-day event per stored day.
+    pub async fn weather_calendar(
+        &self,
+        user_id: &str,
+        options: &CalendarOptions,
+    ) -> anyhow::Result<String> {
+        let timezone =
+            daily_weather::Entity::find_timezone_by_user(user_id, self.db.as_ref()).await?;
+        let today = Utc::now().with_timezone(&timezone).date_naive();
+        let from = today
+            .checked_sub_days(Days::new(options.past_days as u64))
+            .unwrap_or(today);
+
+        let days = daily_weather::Entity::find_by_user_in_range(user_id, Some(from), None)
+            .all(self.db.as_ref())
+            .await?;
+        let hours: Vec<WeatherHour> = if options.severe {
+            let (start, _) = local_day_bounds(from, timezone);
+            hourly_weather::Entity::find_by_user_in_range(user_id, Some(start.naive_utc()), None)
+                .all(self.db.as_ref())
+                .await?
+                .iter()
+                .map(WeatherHour::from)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let calendar = CalendarBuilder {
+            user_id,
+            timezone,
+            units: options.units,
+            stamp: Utc::now(),
+            ics: IcsWriter::default(),
+        };
+        Ok(calendar.build(&days, options.sun, &hours))
+    }
+}
+
+struct CalendarBuilder<'a> {
+    user_id: &'a str,
+    timezone: Tz,
+    units: UnitSystem,
+    /// `DTSTAMP` of all events, the feed is generated on every request
+    stamp: DateTime<Utc>,
+    ics: IcsWriter,
+}
+
+impl CalendarBuilder<'_> {
+    fn build(mut self, days: &[daily_weather::Model], sun: bool, hours: &[WeatherHour]) -> String {
+        self.ics.begin("VCALENDAR");
+        self.ics.raw("VERSION", "2.0");
+        self.ics.text("PRODID", "-//Omnistat//Weather Calendar//EN");
+        self.ics.raw("CALSCALE", "GREGORIAN");
+        self.ics.raw("METHOD", "PUBLISH");
+        self.ics
+            .text("X-WR-CALNAME", &format!("Weather {}", self.user_id));
+        self.ics.text("X-WR-TIMEZONE", self.timezone.name());
+        // Forecasts change with every sync, clients should not cache the feed for long.
+        self.ics.raw("REFRESH-INTERVAL;VALUE=DURATION", "PT1H");
+        self.ics.raw("X-PUBLISHED-TTL", "PT1H");
+
+        for day in days {
+            self.day(day);
+            if sun {
+                self.sun(day);
+            }
+        }
+        for window in consecutive_windows(hours, is_severe) {
+            self.severe_window(window);
+        }
+
+        self.ics.end("VCALENDAR");
+        self.ics.finish()
+    }
+
+    fn day(&mut self, day: &daily_weather::Model) {
+        let description =
+            WMOCode::from(u8::try_from(day.wmo_code).unwrap_or(u8::MAX)).description();
+        let summary = format!(
+            "{description}, {} / {}, {}",
+            self.format(day.temperature_min as f64, Unit::Celsius),
+            self.format(day.temperature_max as f64, Unit::Celsius),
+            self.format(day.precipitation_sum, Unit::Millimeters),
+        );
+        let details = [
+            format!(
+                "Feels like {} / {}",
+                self.format(day.temperature_apparent_min as f64, Unit::Celsius),
+                self.format(day.temperature_apparent_max as f64, Unit::Celsius),
+            ),
+            format!(
+                "Precipitation: {} in {:.0} h, up to {:.0} % probability",
+                self.format(day.precipitation_sum, Unit::Millimeters),
+                day.precipitation_hours,
+                day.precipitation_probability_max * 100.0,
+            ),
+            format!(
+                "Wind: up to {}, gusts {}",
+                self.format(day.wind_speed_max as f64, Unit::KilometersPerHour),
+                self.format(day.wind_gusts_max as f64, Unit::KilometersPerHour),
+            ),
+            format!("UV index: up to {:.1}", day.uv_index_max),
+            format!(
+                "Sunrise {}, sunset {}",
+                self.local_time(day.sunrise_utc.and_utc()),
+                self.local_time(day.sunset_utc.and_utc()),
+            ),
+        ];
+
+        self.ics.begin("VEVENT");
+        self.ics.text(
+            "UID",
+            &format!("{}-{}-day@omnistat", self.user_id, day.date),
+        );
+        self.ics.date_time("DTSTAMP", self.stamp);
+        self.ics.date("DTSTART", day.date);
+        self.ics
+            .date("DTEND", day.date.succ_opt().unwrap_or(day.date));
+        self.ics.text("SUMMARY", &summary);
+        self.ics.text("DESCRIPTION", &details.join("\n"));
+        // All-day weather shouldn't block the day in free/busy lookups.
+        self.ics.raw("TRANSP", "TRANSPARENT");
+        self.ics.end("VEVENT");
+    }
+
+    fn sun(&mut self, day: &daily_weather::Model) {
+        for (kind, summary, time) in [
+            ("sunrise", "Sunrise", day.sunrise_utc.and_utc()),
+            ("sunset", "Sunset", day.sunset_utc.and_utc()),
+        ] {
+            self.ics.begin("VEVENT");
+            self.ics.text(
+                "UID",
+                &format!("{}-{}-{kind}@omnistat", self.user_id, day.date),
+            );
+            self.ics.date_time("DTSTAMP", self.stamp);
+            self.ics.date_time("DTSTART", time);
+            self.ics.date_time("DTEND", time);
+            self.ics.text("SUMMARY", summary);
+            self.ics.raw("TRANSP", "TRANSPARENT");
+            self.ics.end("VEVENT");
+        }
+    }
+
+    /// A timed event over consecutive severe hours, ending at the end of the last hour.
+    fn severe_window(&mut self, window: &[WeatherHour]) {
+        let (Some(first), Some(last)) = (window.first(), window.last()) else {
+            return;
+        };
+        let code = window
+            .iter()
+            .map(|hour| hour.wmo_code)
+            .max_by_key(|code| u8::from(*code))
+            .unwrap_or(first.wmo_code);
+        let gusts = window
+            .iter()
+            .map(|hour| hour.max_wind_speed)
+            .fold(Speed::from_m_s(0.0), Speed::max)
+            .as_km_h();
+        let precipitation = window
+            .iter()
+            .map(|hour| hour.total_precipitation)
+            .sum::<Length>()
+            .as_millimeters();
+
+        let mut summary = format!("Severe weather: {}", code.description());
+        if gusts >= SEVERE_GUSTS_KM_H {
+            summary.push_str(&format!(
+                ", gusts up to {}",
+                self.format(gusts as f64, Unit::KilometersPerHour)
+            ));
+        }
+        let details = format!(
+            "{} from {} to {}\nPrecipitation: {}\nGusts up to {}",
+            code.description(),
+            self.local_time(first.time),
+            self.local_time(last.time + TimeDelta::hours(1)),
+            self.format(precipitation, Unit::Millimeters),
+            self.format(gusts as f64, Unit::KilometersPerHour),
+        );
+
+        self.ics.begin("VEVENT");
+        self.ics.text(
+            "UID",
+            &format!(
+                "{}-{}-severe@omnistat",
+                self.user_id,
+                first.time.format("%Y%m%dT%H%M%SZ")
+            ),
+        );
+        self.ics.date_time("DTSTAMP", self.stamp);
+        self.ics.date_time("DTSTART", first.time);
+        self.ics.date_time("DTEND", last.time + TimeDelta::hours(1));
+        self.ics.text("SUMMARY", &summary);
+        self.ics.text("DESCRIPTION", &details);
+        self.ics.end("VEVENT");
+    }
+
+    fn format(&self, value: f64, unit: Unit) -> String {
+        unit.format(value, self.units)
+    }
+
+    fn local_time(&self, time: DateTime<Utc>) -> String {
+        time.with_timezone(&self.timezone)
+            .format("%H:%M")
+            .to_string()
+    }
+}
+
+fn is_severe(hour: &WeatherHour) -> bool {
+    use WMOCode::*;
+    let severe_code = matches!(
+        hour.wmo_code,
+        HeavyRain
+            | HeavySnow
+            | HeavyShowers
+            | FreezingDrizzle
+            | LightFreezingRain
+            | FreezingRain
+            | Thunderstorm
+            | LightThunderstormHail
+            | ThunderstormHail
+    );
+    severe_code || hour.max_wind_speed.as_km_h() >= SEVERE_GUSTS_KM_H
+}
@@ -0,0 +1,76 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Content lines longer than this many octets are folded, see RFC 5545 section 3.1.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Writes an iCalendar document line by line, taking care of escaping, folding and CRLF endings.
+#[derive(Default)]
+pub struct IcsWriter {
+    output: String,
+}
+
+impl IcsWriter {
+    pub fn begin(&mut self, component: &str) {
+        self.line("BEGIN", component);
+    }
+
+    pub fn end(&mut self, component: &str) {
+        self.line("END", component);
+    }
+
+    /// A property whose value is already valid iCalendar, e.g. a duration.
+    pub fn raw(&mut self, name: &str, value: &str) {
+        self.line(name, value);
+    }
+
+    /// A `TEXT` property, escaped as required.
+    pub fn text(&mut self, name: &str, value: &str) {
+        self.line(name, &escape_text(value));
+    }
+
+    pub fn date(&mut self, name: &str, date: NaiveDate) {
+        self.line(
+            &format!("{name};VALUE=DATE"),
+            &date.format("%Y%m%d").to_string(),
+        );
+    }
+
+    pub fn date_time(&mut self, name: &str, time: DateTime<Utc>) {
+        self.line(name, &time.format("%Y%m%dT%H%M%SZ").to_string());
+    }
+
+    pub fn finish(self) -> String {
+        self.output
+    }
+
+    fn line(&mut self, name: &str, value: &str) {
+        let line = format!("{name}:{value}");
+        let mut octets = 0;
+        for char in line.chars() {
+            // Folding must not split a multi-byte character.
+            if octets + char.len_utf8() > MAX_LINE_OCTETS {
+                self.output.push_str("\r\n ");
+                // The leading space of a continuation line counts towards its length.
+                octets = 1;
+            }
+            self.output.push(char);
+            octets += char.len_utf8();
+        }
+        self.output.push_str("\r\n");
+    }
+}
+
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
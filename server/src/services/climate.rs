@@ -0,0 +1,475 @@
+use crate::config::SharedConfig;
+use crate::database::entity::{
+    climate_day_normal, climate_month, climate_record, climate_state, daily_weather,
+    hourly_weather, hourly_weather_rollup,
+};
+use crate::database::upsert_all;
+use crate::events::{EventBus, RecordBroken, ServerEvent};
+use crate::services::ServiceInitContext;
+use crate::services::climate::day::DaySummary;
+use crate::services::climate::record::RecordKind;
+use crate::services::climate::tables::{ALL_TIME, ClimateTables};
+use crate::services::weather::hour::local_day_bounds;
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    IntoActiveModel, TransactionTrait,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, info, warn};
+
+pub mod day;
+pub mod record;
+mod tables;
+
+/// Normals and records of a user, temperatures in °C, precipitation in mm and gusts in km/h.
+#[derive(Debug, Serialize)]
+pub struct Climate {
+    pub user_id: String,
+    pub timezone: String,
+    /// Last local day included, `None` until the first completed day was processed
+    pub processed_through: Option<NaiveDate>,
+    pub monthly_normals: Vec<MonthlyNormal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_normals: Option<Vec<DailyNormal>>,
+    pub records: Vec<Record>,
+    pub streaks: Streaks,
+    pub degree_days: Vec<MonthDegreeDays>,
+}
+
+/// Averages over every stored day of the month, totals are scaled to the length of the month.
+#[derive(Debug, Serialize)]
+pub struct MonthlyNormal {
+    pub month: u32,
+    pub days: i32,
+    pub temperature_min: f64,
+    pub temperature_mean: f64,
+    pub temperature_max: f64,
+    pub precipitation: f64,
+    pub frost_days: f64,
+    pub dry_days: f64,
+    pub heating_degree_days: f64,
+    pub cooling_degree_days: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyNormal {
+    pub month: i32,
+    pub day: i32,
+    /// Number of years the day was observed in
+    pub samples: i32,
+    pub temperature_min: f64,
+    pub temperature_mean: f64,
+    pub temperature_max: f64,
+    pub precipitation: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Record {
+    /// 1-12, all-time records have no month
+    pub month: Option<i32>,
+    pub kind: RecordKind,
+    pub value: f64,
+    pub unit: &'static str,
+    pub date: NaiveDate,
+    pub time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Streaks {
+    /// Days with less than 1 mm of precipitation
+    pub dry: Streak,
+    /// Days with a minimum temperature below 0 °C
+    pub frost: Streak,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Streak {
+    pub current_days: i32,
+    pub current_start: Option<NaiveDate>,
+    pub longest_days: i32,
+    pub longest_start: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MonthDegreeDays {
+    pub year: i32,
+    pub month: i32,
+    pub days: i32,
+    pub heating: f64,
+    pub cooling: f64,
+}
+
+pub struct ClimateService {
+    config: SharedConfig,
+    db: Arc<DatabaseConnection>,
+    events: EventBus,
+}
+
+impl ClimateService {
+    pub fn initialize(context: &ServiceInitContext) -> Arc<Self> {
+        Arc::new(Self {
+            config: context.config.clone(),
+            db: context.db.clone(),
+            events: context.events.clone(),
+        })
+    }
+
+    /// Adds newly completed days to the statistics after every hourly sync.
+    pub fn start_listening(self: &Arc<Self>) {
+        let service = self.clone();
+        let mut events = self.events.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => service.handle(&event).await,
+                    Err(RecvError::Lagged(skipped)) => service.handle_missed(skipped).await,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Adds the days completed by an hourly sync to the statistics of its user.
+    pub async fn handle(&self, event: &ServerEvent) {
+        if let ServerEvent::HourlyWeatherSynced { user_id } = event
+            && let Err(e) = self.update_user(user_id).await
+        {
+            error!("Failed to update the climate of user '{user_id}': {e:#}");
+        }
+    }
+
+    /// Catches up on missed events by updating the climate of every user.
+    pub async fn handle_missed(&self, skipped: u64) {
+        warn!("Missed {skipped} event(s), updating the climate of all users");
+        self.update_all().await;
+    }
+
+    pub async fn update_all(&self) {
+        let config = self.config.load_full();
+        for user_id in config.users.keys() {
+            if let Err(e) = self.update_user(user_id).await {
+                error!("Failed to update the climate of user '{user_id}': {e:#}");
+            }
+        }
+    }
+
+    /// Adds the local days completed since the last update, returns the amount of days added.
+    /// Days are only added once, hours backfilled before that need a [`Self::rebuild_user`].
+    pub async fn update_user(&self, user_id: &str) -> anyhow::Result<usize> {
+        self.update(user_id, true).await
+    }
+
+    /// Recomputes the statistics of a user from all stored hours and rollups. The old statistics
+    /// stay in place until the new ones are committed in their stead.
+    pub async fn rebuild_user(&self, user_id: &str) -> anyhow::Result<usize> {
+        let transaction = self.db.begin().await?;
+        climate_state::Entity::delete_by_id(user_id)
+            .exec(&transaction)
+            .await?;
+        climate_month::Entity::delete_by_user(user_id, &transaction).await?;
+        climate_day_normal::Entity::delete_by_user(user_id, &transaction).await?;
+        climate_record::Entity::delete_by_user(user_id, &transaction).await?;
+        // Records replaced while rebuilding aren't news.
+        let (days, _) = self.update_in(user_id, &transaction).await?;
+        transaction.commit().await?;
+        Ok(days)
+    }
+
+    async fn update(&self, user_id: &str, publish: bool) -> anyhow::Result<usize> {
+        let transaction = self.db.begin().await?;
+        let (days, broken) = self.update_in(user_id, &transaction).await?;
+        transaction.commit().await?;
+
+        if publish {
+            for broken in broken {
+                info!(
+                    "User '{user_id}' broke the {} record with {} {}",
+                    broken.record.name(),
+                    broken.value,
+                    broken.unit
+                );
+                self.events.publish(ServerEvent::RecordBroken(broken));
+            }
+        }
+        Ok(days)
+    }
+
+    /// Adds the days since the last update within the transaction, returns the amount of days
+    /// added and the all-time records they broke.
+    async fn update_in(
+        &self,
+        user_id: &str,
+        db: &DatabaseTransaction,
+    ) -> anyhow::Result<(usize, Vec<RecordBroken>)> {
+        let timezone =
+            daily_weather::Entity::find_timezone_by_user(user_id, self.db.as_ref()).await?;
+        let today = Utc::now().with_timezone(&timezone).date_naive();
+        let Some(last) = today.pred_opt() else {
+            return Ok((0, Vec::new()));
+        };
+
+        let state = climate_state::Entity::find_by_id(user_id).one(db).await?;
+        let first = match &state {
+            Some(state) => state.processed_through.succ_opt(),
+            None => self.first_stored_date(user_id, timezone, db).await?,
+        };
+        let Some(first) = first.filter(|first| *first <= last) else {
+            return Ok((0, Vec::new()));
+        };
+
+        let days = self
+            .day_summaries(user_id, timezone, first, last, db)
+            .await?;
+        let mut tables = ClimateTables::new(
+            user_id,
+            state,
+            first,
+            climate_month::Entity::find_by_user(user_id, db).await?,
+            climate_day_normal::Entity::find_by_user(user_id, db).await?,
+            climate_record::Entity::find_by_user(user_id, db).await?,
+        );
+        for date in first.iter_days().take_while(|date| *date <= last) {
+            match days.get(&date) {
+                Some(day) => tables.add_day(day),
+                None => tables.skip_day(date),
+            }
+        }
+
+        upsert_all(vec![tables.state.into_active_model().reset_all()], db).await?;
+        upsert_all(
+            tables
+                .months
+                .into_values()
+                .map(|month| month.into_active_model().reset_all())
+                .collect(),
+            db,
+        )
+        .await?;
+        upsert_all(
+            tables
+                .day_normals
+                .into_values()
+                .map(|normal| normal.into_active_model().reset_all())
+                .collect(),
+            db,
+        )
+        .await?;
+        upsert_all(
+            tables
+                .records
+                .into_values()
+                .map(|record| record.into_active_model().reset_all())
+                .collect(),
+            db,
+        )
+        .await?;
+        Ok((days.len(), tables.broken))
+    }
+
+    async fn first_stored_date(
+        &self,
+        user_id: &str,
+        timezone: Tz,
+        db: &impl ConnectionTrait,
+    ) -> anyhow::Result<Option<NaiveDate>> {
+        let first_hour = hourly_weather::Entity::find_by_user_in_range(user_id, None, None)
+            .one(db)
+            .await?
+            .map(|hour| {
+                hour.time_utc
+                    .and_utc()
+                    .with_timezone(&timezone)
+                    .date_naive()
+            });
+        let first_rollup =
+            hourly_weather_rollup::Entity::find_by_user_in_range(user_id, None, None)
+                .one(db)
+                .await?
+                .map(|rollup| rollup.date);
+        Ok(first_hour.into_iter().chain(first_rollup).min())
+    }
+
+    /// Summaries of the local days from `first` to `last`, from their hours where they are
+    /// still stored and from their rollups otherwise.
+    async fn day_summaries(
+        &self,
+        user_id: &str,
+        timezone: Tz,
+        first: NaiveDate,
+        last: NaiveDate,
+        db: &impl ConnectionTrait,
+    ) -> anyhow::Result<BTreeMap<NaiveDate, DaySummary>> {
+        let (from, _) = local_day_bounds(first, timezone);
+        let (_, to) = local_day_bounds(last, timezone);
+
+        let mut days: BTreeMap<NaiveDate, DaySummary> =
+            hourly_weather_rollup::Entity::find_by_user_in_range(
+                user_id,
+                Some(first),
+                last.succ_opt(),
+            )
+            .all(db)
+            .await?
+            .iter()
+            .map(|rollup| (rollup.date, DaySummary::from_rollup(rollup)))
+            .collect();
+
+        let hours = hourly_weather::Entity::find_by_user_in_range(
+            user_id,
+            Some(from.naive_utc()),
+            Some(to.naive_utc()),
+        )
+        .all(db)
+        .await?;
+        let local_date = |hour: &hourly_weather::Model| {
+            hour.time_utc
+                .and_utc()
+                .with_timezone(&timezone)
+                .date_naive()
+        };
+        for day in hours.chunk_by(|a, b| local_date(a) == local_date(b)) {
+            let Some(date) = day.first().map(local_date) else {
+                continue;
+            };
+            if let Some(summary) = DaySummary::from_hours(date, day) {
+                days.insert(date, summary);
+            }
+        }
+        Ok(days)
+    }
+
+    /// The statistics of a user, daily normals are only included if asked for.
+    pub async fn climate(&self, user_id: &str, daily_normals: bool) -> anyhow::Result<Climate> {
+        let db = self.db.as_ref();
+        let timezone = daily_weather::Entity::find_timezone_by_user(user_id, db).await?;
+        let state = climate_state::Entity::find_by_id(user_id).one(db).await?;
+        let months = climate_month::Entity::find_by_user(user_id, db).await?;
+        let records = climate_record::Entity::find_by_user(user_id, db).await?;
+
+        let daily_normals = if daily_normals {
+            Some(
+                climate_day_normal::Entity::find_by_user(user_id, db)
+                    .await?
+                    .into_iter()
+                    .map(DailyNormal::from)
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        Ok(Climate {
+            user_id: user_id.to_string(),
+            timezone: timezone.name().to_string(),
+            processed_through: state.as_ref().map(|state| state.processed_through),
+            monthly_normals: monthly_normals(&months),
+            daily_normals,
+            records: records
+                .into_iter()
+                .filter_map(|record| {
+                    let kind = RecordKind::from_name(&record.kind)?;
+                    Some(Record {
+                        month: (record.month != ALL_TIME).then_some(record.month),
+                        kind,
+                        value: record.value,
+                        unit: kind.unit(),
+                        date: record.date,
+                        time: record.time_utc.map(|time| time.and_utc()),
+                    })
+                })
+                .collect(),
+            streaks: Streaks::from(state.as_ref()),
+            degree_days: months
+                .iter()
+                .map(|month| MonthDegreeDays {
+                    year: month.year,
+                    month: month.month,
+                    days: month.days,
+                    heating: month.heating_degree_days,
+                    cooling: month.cooling_degree_days,
+                })
+                .collect(),
+        })
+    }
+}
+
+fn monthly_normals(months: &[climate_month::Model]) -> Vec<MonthlyNormal> {
+    (1..=12)
+        .filter_map(|month| {
+            let rows: Vec<&climate_month::Model> = months
+                .iter()
+                .filter(|row| row.month == month as i32)
+                .collect();
+            let days: i32 = rows.iter().map(|row| row.days).sum();
+            if days == 0 {
+                return None;
+            }
+            let per_day = |sum: f64| sum / days as f64;
+            let per_month = |sum: f64| per_day(sum) * days_in_month(month) as f64;
+            let sum =
+                |value: fn(&climate_month::Model) -> f64| rows.iter().map(|row| value(row)).sum();
+
+            Some(MonthlyNormal {
+                month,
+                days,
+                temperature_min: per_day(sum(|row| row.temperature_min_sum)),
+                temperature_mean: per_day(sum(|row| row.temperature_mean_sum)),
+                temperature_max: per_day(sum(|row| row.temperature_max_sum)),
+                precipitation: per_month(sum(|row| row.precipitation_sum)),
+                frost_days: per_month(sum(|row| row.frost_days as f64)),
+                dry_days: per_month(sum(|row| row.dry_days as f64)),
+                heating_degree_days: per_month(sum(|row| row.heating_degree_days)),
+                cooling_degree_days: per_month(sum(|row| row.cooling_degree_days)),
+            })
+        })
+        .collect()
+}
+
+/// Length of the month in a common year.
+fn days_in_month(month: u32) -> i64 {
+    let first = NaiveDate::from_ymd_opt(2001, month, 1);
+    let next = NaiveDate::from_ymd_opt(2001 + (month / 12) as i32, month % 12 + 1, 1);
+    match (first, next) {
+        (Some(first), Some(next)) => (next - first).num_days(),
+        _ => 30,
+    }
+}
+
+impl From<climate_day_normal::Model> for DailyNormal {
+    fn from(normal: climate_day_normal::Model) -> Self {
+        let samples = normal.samples.max(1) as f64;
+        Self {
+            month: normal.month,
+            day: normal.day,
+            samples: normal.samples,
+            temperature_min: normal.temperature_min_sum / samples,
+            temperature_mean: normal.temperature_mean_sum / samples,
+            temperature_max: normal.temperature_max_sum / samples,
+            precipitation: normal.precipitation_sum / samples,
+        }
+    }
+}
+
+impl From<Option<&climate_state::Model>> for Streaks {
+    fn from(state: Option<&climate_state::Model>) -> Self {
+        Self {
+            dry: Streak {
+                current_days: state.map_or(0, |state| state.dry_streak_days),
+                current_start: state.and_then(|state| state.dry_streak_start),
+                longest_days: state.map_or(0, |state| state.longest_dry_streak_days),
+                longest_start: state.and_then(|state| state.longest_dry_streak_start),
+            },
+            frost: Streak {
+                current_days: state.map_or(0, |state| state.frost_streak_days),
+                current_start: state.and_then(|state| state.frost_streak_start),
+                longest_days: state.map_or(0, |state| state.longest_frost_streak_days),
+                longest_start: state.and_then(|state| state.longest_frost_streak_start),
+            },
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use crate::database::entity::{hourly_weather, hourly_weather_rollup};
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Base temperature of heating and cooling degree days in °C.
+const DEGREE_DAY_BASE_CELSIUS: f64 = 18.0;
+/// Days with less precipitation are dry, as defined by the WMO.
+const DRY_DAY_MAX_MILLIMETERS: f64 = 1.0;
+
+/// The observed weather of a completed local day, from its hours or its rollup.
+pub struct DaySummary {
+    pub date: NaiveDate,
+    pub temperature_min: f64,
+    pub temperature_mean: f64,
+    pub temperature_max: f64,
+    pub precipitation: f64,
+    pub gust_max: f64,
+    /// Times are only known if the day still has its hours
+    pub coldest_hour: Option<DateTime<Utc>>,
+    pub hottest_hour: Option<DateTime<Utc>>,
+    pub strongest_gust: Option<DateTime<Utc>>,
+}
+
+impl DaySummary {
+    pub fn from_hours(date: NaiveDate, hours: &[hourly_weather::Model]) -> Option<Self> {
+        let coldest = hours
+            .iter()
+            .min_by(|a, b| a.temperature_actual.total_cmp(&b.temperature_actual))?;
+        let hottest = hours
+            .iter()
+            .max_by(|a, b| a.temperature_actual.total_cmp(&b.temperature_actual))?;
+        let gust = hours
+            .iter()
+            .max_by(|a, b| a.max_wind_speed.total_cmp(&b.max_wind_speed))?;
+        let temperature_sum: f64 = hours
+            .iter()
+            .map(|hour| hour.temperature_actual as f64)
+            .sum();
+
+        Some(Self {
+            date,
+            temperature_min: coldest.temperature_actual as f64,
+            temperature_mean: temperature_sum / hours.len() as f64,
+            temperature_max: hottest.temperature_actual as f64,
+            precipitation: hours.iter().map(|hour| hour.total_precipitation).sum(),
+            gust_max: gust.max_wind_speed as f64,
+            coldest_hour: Some(coldest.time_utc.and_utc()),
+            hottest_hour: Some(hottest.time_utc.and_utc()),
+            strongest_gust: Some(gust.time_utc.and_utc()),
+        })
+    }
+
+    pub fn from_rollup(rollup: &hourly_weather_rollup::Model) -> Self {
+        Self {
+            date: rollup.date,
+            temperature_min: rollup.temperature_min as f64,
+            temperature_mean: rollup.temperature_mean as f64,
+            temperature_max: rollup.temperature_max as f64,
+            precipitation: rollup.total_precipitation_sum,
+            gust_max: rollup.max_wind_speed_max as f64,
+            coldest_hour: None,
+            hottest_hour: None,
+            strongest_gust: None,
+        }
+    }
+
+    pub fn is_dry(&self) -> bool {
+        self.precipitation < DRY_DAY_MAX_MILLIMETERS
+    }
+
+    pub fn is_frost(&self) -> bool {
+        self.temperature_min < 0.0
+    }
+
+    pub fn heating_degrees(&self) -> f64 {
+        (DEGREE_DAY_BASE_CELSIUS - self.temperature_mean).max(0.0)
+    }
+
+    pub fn cooling_degrees(&self) -> f64 {
+        (self.temperature_mean - DEGREE_DAY_BASE_CELSIUS).max(0.0)
+    }
+}
@@ -0,0 +1,60 @@
+use crate::services::climate::day::DaySummary;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordKind {
+    HottestHour,
+    ColdestHour,
+    WettestDay,
+    StrongestGust,
+}
+
+impl RecordKind {
+    pub const ALL: [RecordKind; 4] = [
+        RecordKind::HottestHour,
+        RecordKind::ColdestHour,
+        RecordKind::WettestDay,
+        RecordKind::StrongestGust,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RecordKind::HottestHour => "hottest_hour",
+            RecordKind::ColdestHour => "coldest_hour",
+            RecordKind::WettestDay => "wettest_day",
+            RecordKind::StrongestGust => "strongest_gust",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            RecordKind::HottestHour | RecordKind::ColdestHour => "°C",
+            RecordKind::WettestDay => "mm",
+            RecordKind::StrongestGust => "km/h",
+        }
+    }
+
+    /// The value of the day this record is about and when it was reached, if known.
+    pub fn observe(self, day: &DaySummary) -> (f64, Option<DateTime<Utc>>) {
+        match self {
+            RecordKind::HottestHour => (day.temperature_max, day.hottest_hour),
+            RecordKind::ColdestHour => (day.temperature_min, day.coldest_hour),
+            RecordKind::WettestDay => (day.precipitation, None),
+            RecordKind::StrongestGust => (day.gust_max, day.strongest_gust),
+        }
+    }
+
+    /// Ties keep the earlier record.
+    pub fn beats(self, value: f64, record: f64) -> bool {
+        match self {
+            RecordKind::ColdestHour => value < record,
+            _ => value > record,
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::database::entity::{climate_day_normal, climate_month, climate_record, climate_state};
+use crate::events::RecordBroken;
+use crate::services::climate::day::DaySummary;
+use crate::services::climate::record::RecordKind;
+use chrono::{Datelike, NaiveDate};
+use std::collections::BTreeMap;
+
+/// Scope of the all-time records, monthly records are scoped by their month 1-12.
+pub const ALL_TIME: i32 = 0;
+
+/// The climate tables of a user held in memory, days are added in order and the result is
+/// written back as a whole.
+pub struct ClimateTables {
+    user_id: String,
+    pub state: climate_state::Model,
+    pub months: BTreeMap<(i32, i32), climate_month::Model>,
+    pub day_normals: BTreeMap<(i32, i32), climate_day_normal::Model>,
+    pub records: BTreeMap<(i32, RecordKind), climate_record::Model>,
+    /// All-time records that replaced an earlier one
+    pub broken: Vec<RecordBroken>,
+}
+
+impl ClimateTables {
+    pub fn new(
+        user_id: &str,
+        state: Option<climate_state::Model>,
+        start: NaiveDate,
+        months: Vec<climate_month::Model>,
+        day_normals: Vec<climate_day_normal::Model>,
+        records: Vec<climate_record::Model>,
+    ) -> Self {
+        let state = state.unwrap_or_else(|| climate_state::Model {
+            user_id: user_id.to_string(),
+            processed_through: start.pred_opt().unwrap_or(start),
+            dry_streak_days: 0,
+            dry_streak_start: None,
+            longest_dry_streak_days: 0,
+            longest_dry_streak_start: None,
+            frost_streak_days: 0,
+            frost_streak_start: None,
+            longest_frost_streak_days: 0,
+            longest_frost_streak_start: None,
+        });
+
+        Self {
+            user_id: user_id.to_string(),
+            state,
+            months: months
+                .into_iter()
+                .map(|month| ((month.year, month.month), month))
+                .collect(),
+            day_normals: day_normals
+                .into_iter()
+                .map(|normal| ((normal.month, normal.day), normal))
+                .collect(),
+            records: records
+                .into_iter()
+                .filter_map(|record| {
+                    Some(((record.month, RecordKind::from_name(&record.kind)?), record))
+                })
+                .collect(),
+            broken: Vec::new(),
+        }
+    }
+
+    pub fn add_day(&mut self, day: &DaySummary) {
+        self.add_to_month(day);
+        self.add_to_day_normal(day);
+        for kind in RecordKind::ALL {
+            self.check_record(ALL_TIME, kind, day);
+            self.check_record(day.date.month() as i32, kind, day);
+        }
+
+        let state = &mut self.state;
+        (state.dry_streak_days, state.dry_streak_start) = continue_streak(
+            day.is_dry(),
+            day.date,
+            state.dry_streak_days,
+            state.dry_streak_start,
+        );
+        if state.dry_streak_days > state.longest_dry_streak_days {
+            state.longest_dry_streak_days = state.dry_streak_days;
+            state.longest_dry_streak_start = state.dry_streak_start;
+        }
+        (state.frost_streak_days, state.frost_streak_start) = continue_streak(
+            day.is_frost(),
+            day.date,
+            state.frost_streak_days,
+            state.frost_streak_start,
+        );
+        if state.frost_streak_days > state.longest_frost_streak_days {
+            state.longest_frost_streak_days = state.frost_streak_days;
+            state.longest_frost_streak_start = state.frost_streak_start;
+        }
+        state.processed_through = day.date;
+    }
+
+    /// A day without data, streaks can't continue across it.
+    pub fn skip_day(&mut self, date: NaiveDate) {
+        let state = &mut self.state;
+        (state.dry_streak_days, state.dry_streak_start) = (0, None);
+        (state.frost_streak_days, state.frost_streak_start) = (0, None);
+        state.processed_through = date;
+    }
+
+    fn add_to_month(&mut self, day: &DaySummary) {
+        let (year, month) = (day.date.year(), day.date.month() as i32);
+        let entry = self
+            .months
+            .entry((year, month))
+            .or_insert_with(|| climate_month::Model {
+                user_id: self.user_id.clone(),
+                year,
+                month,
+                days: 0,
+                temperature_min_sum: 0.0,
+                temperature_mean_sum: 0.0,
+                temperature_max_sum: 0.0,
+                precipitation_sum: 0.0,
+                heating_degree_days: 0.0,
+                cooling_degree_days: 0.0,
+                frost_days: 0,
+                dry_days: 0,
+            });
+        entry.days += 1;
+        entry.temperature_min_sum += day.temperature_min;
+        entry.temperature_mean_sum += day.temperature_mean;
+        entry.temperature_max_sum += day.temperature_max;
+        entry.precipitation_sum += day.precipitation;
+        entry.heating_degree_days += day.heating_degrees();
+        entry.cooling_degree_days += day.cooling_degrees();
+        entry.frost_days += day.is_frost() as i32;
+        entry.dry_days += day.is_dry() as i32;
+    }
+
+    fn add_to_day_normal(&mut self, day: &DaySummary) {
+        let (month, day_of_month) = (day.date.month() as i32, day.date.day() as i32);
+        let entry = self
+            .day_normals
+            .entry((month, day_of_month))
+            .or_insert_with(|| climate_day_normal::Model {
+                user_id: self.user_id.clone(),
+                month,
+                day: day_of_month,
+                samples: 0,
+                temperature_min_sum: 0.0,
+                temperature_mean_sum: 0.0,
+                temperature_max_sum: 0.0,
+                precipitation_sum: 0.0,
+            });
+        entry.samples += 1;
+        entry.temperature_min_sum += day.temperature_min;
+        entry.temperature_mean_sum += day.temperature_mean;
+        entry.temperature_max_sum += day.temperature_max;
+        entry.precipitation_sum += day.precipitation;
+    }
+
+    fn check_record(&mut self, month: i32, kind: RecordKind, day: &DaySummary) {
+        let (value, time) = kind.observe(day);
+        let new_record = climate_record::Model {
+            user_id: self.user_id.clone(),
+            month,
+            kind: kind.name().to_string(),
+            value,
+            date: day.date,
+            time_utc: time.map(|time| time.naive_utc()),
+        };
+
+        match self.records.get_mut(&(month, kind)) {
+            Some(record) if kind.beats(value, record.value) => {
+                if month == ALL_TIME {
+                    self.broken.push(RecordBroken {
+                        user_id: self.user_id.clone(),
+                        record: kind,
+                        value,
+                        previous: record.value,
+                        unit: kind.unit(),
+                        date: day.date,
+                        time,
+                    });
+                }
+                *record = new_record;
+            }
+            Some(_) => {}
+            None => {
+                self.records.insert((month, kind), new_record);
+            }
+        }
+    }
+}
+
+fn continue_streak(
+    continues: bool,
+    date: NaiveDate,
+    days: i32,
+    start: Option<NaiveDate>,
+) -> (i32, Option<NaiveDate>) {
+    match (continues, start) {
+        (false, _) => (0, None),
+        (true, Some(start)) if days > 0 => (days + 1, Some(start)),
+        (true, _) => (1, Some(date)),
+    }
+}
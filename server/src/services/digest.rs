@@ -0,0 +1,271 @@
+use crate::database::entity::{daily_weather, hourly_weather};
+use crate::services::ServiceInitContext;
+use crate::services::export::column::{Unit, UnitSystem};
+use crate::services::weather::hour::{WeatherHour, consecutive_windows, local_day_bounds};
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use chrono_tz::Tz;
+use minijinja::Environment;
+use minijinja::syntax::SyntaxConfig;
+use minijinja::value::Serde;
+use omnistat_core::types::length::Length;
+use omnistat_core::types::temperature::Temperature;
+use omnistat_core::types::wmo_code::WMOCode;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// An hour is part of a precipitation window if either threshold is reached.
+const PRECIPITATION_MIN_MILLIMETERS: f64 = 0.1;
+const PRECIPITATION_MIN_PROBABILITY: f32 = 0.5;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestFormat {
+    #[default]
+    Markdown,
+    Html,
+    Text,
+}
+
+impl DigestFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            DigestFormat::Markdown => "text/markdown; charset=utf-8",
+            DigestFormat::Html => "text/html; charset=utf-8",
+            DigestFormat::Text => "text/plain; charset=utf-8",
+        }
+    }
+
+    /// Template names end in their format, so HTML gets auto escaped.
+    fn template(self) -> &'static str {
+        match self {
+            DigestFormat::Markdown => "digest.md",
+            DigestFormat::Html => "digest.html",
+            DigestFormat::Text => "digest.txt",
+        }
+    }
+}
+
+/// The summary of one local day of a user, values are already formatted in the requested units.
+#[derive(Debug, Serialize)]
+pub struct Digest {
+    pub user_id: String,
+    pub date: NaiveDate,
+    pub timezone: String,
+    pub summary: Option<String>,
+    pub temperature_min: Option<String>,
+    pub temperature_max: Option<String>,
+    pub precipitation_total: Option<String>,
+    pub precipitation_windows: Vec<PrecipitationWindow>,
+    pub wind_gust_peak: Option<WindGust>,
+    pub uv_index_max: Option<String>,
+    pub sunrise: Option<String>,
+    pub sunset: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrecipitationWindow {
+    /// Local `HH:MM`
+    pub start: String,
+    pub end: String,
+    pub total: String,
+    pub probability_max: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WindGust {
+    pub speed: String,
+    /// Local `HH:MM`, unknown if only the daily maximum is stored
+    pub time: Option<String>,
+}
+
+pub struct DigestService {
+    db: Arc<DatabaseConnection>,
+    templates: Environment<'static>,
+}
+
+impl DigestService {
+    pub fn initialize(context: &ServiceInitContext) -> Arc<Self> {
+        let mut templates = Environment::new();
+        templates.set_syntax(
+            SyntaxConfig::builder()
+                .keep_trailing_newline(true)
+                .build()
+                .expect("Default delimiters should be valid"),
+        );
+        templates
+            .add_template("digest.md", include_str!("../../templates/digest.md"))
+            .expect("Digest markdown template should be valid");
+        templates
+            .add_template("digest.html", include_str!("../../templates/digest.html"))
+            .expect("Digest HTML template should be valid");
+        templates
+            .add_template("digest.txt", include_str!("../../templates/digest.txt"))
+            .expect("Digest text template should be valid");
+
+        Arc::new(Self {
+            db: context.db.clone(),
+            templates,
+        })
+    }
+
+    /// Builds the digest of a local day, today in the user's timezone if no date is given.
+    /// Returns `None` if neither hourly nor daily weather is stored for that day.
+    pub async fn digest(
+        &self,
+        user_id: &str,
+        date: Option<NaiveDate>,
+        units: UnitSystem,
+    ) -> anyhow::Result<Option<Digest>> {
+        let timezone =
+            daily_weather::Entity::find_timezone_by_user(user_id, self.db.as_ref()).await?;
+        let date = date.unwrap_or_else(|| Utc::now().with_timezone(&timezone).date_naive());
+
+        let daily =
+            daily_weather::Entity::find_by_user_and_date(user_id, date, self.db.as_ref()).await?;
+        let (from, to) = local_day_bounds(date, timezone);
+        let hours: Vec<WeatherHour> = hourly_weather::Entity::find_by_user_in_range(
+            user_id,
+            Some(from.naive_utc()),
+            Some(to.naive_utc()),
+        )
+        .all(self.db.as_ref())
+        .await?
+        .iter()
+        .map(WeatherHour::from)
+        .collect();
+
+        if daily.is_none() && hours.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(DigestBuilder { timezone, units }.build(
+            user_id,
+            date,
+            daily.as_ref(),
+            &hours,
+        )))
+    }
+
+    pub fn render(&self, digest: &Digest, format: DigestFormat) -> anyhow::Result<String> {
+        let template = self.templates.get_template(format.template())?;
+        Ok(template.render(Serde(digest))?)
+    }
+}
+
+struct DigestBuilder {
+    timezone: Tz,
+    units: UnitSystem,
+}
+
+impl DigestBuilder {
+    fn build(
+        &self,
+        user_id: &str,
+        date: NaiveDate,
+        daily: Option<&daily_weather::Model>,
+        hours: &[WeatherHour],
+    ) -> Digest {
+        let temperatures = hours.iter().map(|hour| hour.temperature);
+        let temperature_min = temperatures
+            .clone()
+            .reduce(Temperature::min)
+            .map(|temperature| temperature.as_celsius() as f64)
+            .or(daily.map(|daily| daily.temperature_min as f64));
+        let temperature_max = temperatures
+            .reduce(Temperature::max)
+            .map(|temperature| temperature.as_celsius() as f64)
+            .or(daily.map(|daily| daily.temperature_max as f64));
+
+        let precipitation_total = if hours.is_empty() {
+            daily.map(|daily| daily.precipitation_sum)
+        } else {
+            Some(
+                hours
+                    .iter()
+                    .map(|hour| hour.total_precipitation)
+                    .sum::<Length>()
+                    .as_millimeters(),
+            )
+        };
+
+        let wind_gust_peak = hours
+            .iter()
+            .max_by(|a, b| a.max_wind_speed.total_cmp(&b.max_wind_speed))
+            .map(|hour| WindGust {
+                speed: self.format(
+                    hour.max_wind_speed.as_km_h() as f64,
+                    Unit::KilometersPerHour,
+                ),
+                time: Some(self.local_time(hour.time)),
+            })
+            .or(daily.map(|daily| WindGust {
+                speed: self.format(daily.wind_gusts_max as f64, Unit::KilometersPerHour),
+                time: None,
+            }));
+
+        // The daily code is the most severe of the day, which is the highest code.
+        let wmo_code = daily
+            .map(|daily| WMOCode::from(u8::try_from(daily.wmo_code).unwrap_or(u8::MAX)))
+            .or(hours
+                .iter()
+                .map(|hour| hour.wmo_code)
+                .max_by_key(|code| u8::from(*code)));
+
+        Digest {
+            user_id: user_id.to_string(),
+            date,
+            timezone: self.timezone.name().to_string(),
+            summary: wmo_code.map(|code| code.description().to_string()),
+            temperature_min: temperature_min.map(|value| self.format(value, Unit::Celsius)),
+            temperature_max: temperature_max.map(|value| self.format(value, Unit::Celsius)),
+            precipitation_total: precipitation_total
+                .map(|value| self.format(value, Unit::Millimeters)),
+            precipitation_windows: self.precipitation_windows(hours),
+            wind_gust_peak,
+            uv_index_max: daily.map(|daily| format!("{:.1}", daily.uv_index_max)),
+            sunrise: daily.map(|daily| self.local_time(daily.sunrise_utc.and_utc())),
+            sunset: daily.map(|daily| self.local_time(daily.sunset_utc.and_utc())),
+        }
+    }
+
+    /// Groups consecutive wet hours, a window ends at the end of its last hour.
+    fn precipitation_windows(&self, hours: &[WeatherHour]) -> Vec<PrecipitationWindow> {
+        let is_wet = |hour: &WeatherHour| {
+            hour.total_precipitation.as_millimeters() >= PRECIPITATION_MIN_MILLIMETERS
+                || hour.precipitation_probability.as_0_1() >= PRECIPITATION_MIN_PROBABILITY
+        };
+
+        consecutive_windows(hours, is_wet)
+            .into_iter()
+            .filter_map(|window| {
+                let (first, last) = (window.first()?, window.last()?);
+                let total = window
+                    .iter()
+                    .map(|hour| hour.total_precipitation)
+                    .sum::<Length>()
+                    .as_millimeters();
+                let probability_max = window
+                    .iter()
+                    .map(|hour| hour.precipitation_probability.as_0_100())
+                    .fold(0.0, f32::max);
+                Some(PrecipitationWindow {
+                    start: self.local_time(first.time),
+                    end: self.local_time(last.time + TimeDelta::hours(1)),
+                    total: self.format(total, Unit::Millimeters),
+                    probability_max: format!("{probability_max:.0} %"),
+                })
+            })
+            .collect()
+    }
+
+    fn format(&self, value: f64, unit: Unit) -> String {
+        unit.format(value, self.units)
+    }
+
+    fn local_time(&self, time: DateTime<Utc>) -> String {
+        time.with_timezone(&self.timezone)
+            .format("%H:%M")
+            .to_string()
+    }
+}
@@ -0,0 +1,131 @@
+use crate::database::entity::hourly_weather;
+use crate::services::ServiceInitContext;
+use chrono::NaiveDate;
+use futures::Stream;
+use sea_orm::prelude::DateTime;
+use sea_orm::{ColumnTrait, DatabaseConnection, QueryFilter, QuerySelect};
+use std::sync::Arc;
+
+pub mod column;
+pub mod format;
+
+const PAGE_SIZE: u64 = 1_000;
+
+pub struct ExportRequest {
+    pub user_id: String,
+    /// Inclusive
+    pub from: Option<DateTime>,
+    /// Exclusive
+    pub to: Option<DateTime>,
+    pub format: format::ExportFormat,
+    pub units: column::UnitSystem,
+}
+
+/// Converts an inclusive range of UTC days into `from`/`to` bounds.
+pub fn day_bounds(
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> (Option<DateTime>, Option<DateTime>) {
+    let from = from.and_then(|date| date.and_hms_opt(0, 0, 0));
+    let to = to
+        .and_then(|date| date.succ_opt())
+        .and_then(|date| date.and_hms_opt(0, 0, 0));
+    (from, to)
+}
+
+/// Encodes rows in one go, for callers that already hold all of them.
+pub fn encode_rows(
+    rows: &[hourly_weather::Model],
+    format: format::ExportFormat,
+    units: column::UnitSystem,
+) -> anyhow::Result<Vec<u8>> {
+    let mut encoder = format.encoder(units)?;
+    let mut bytes = encoder.header()?;
+    bytes.extend(encoder.encode(rows)?);
+    bytes.extend(encoder.finish()?);
+    Ok(bytes)
+}
+
+pub struct ExportService {
+    db: Arc<DatabaseConnection>,
+}
+
+impl ExportService {
+    pub fn initialize(context: &ServiceInitContext) -> Arc<Self> {
+        Arc::new(Self {
+            db: context.db.clone(),
+        })
+    }
+
+    /// Streams the encoded hours of a user page by page, the rows are never all held in memory.
+    pub fn export_hourly_weather(
+        &self,
+        request: ExportRequest,
+    ) -> anyhow::Result<impl Stream<Item = anyhow::Result<Vec<u8>>> + Send + 'static> {
+        let cursor = ExportCursor {
+            db: self.db.clone(),
+            encoder: Some(request.format.encoder(request.units)?),
+            header_written: false,
+            after: None,
+            request,
+        };
+
+        Ok(futures::stream::try_unfold(
+            cursor,
+            |mut cursor| async move {
+                let chunk = cursor.next_chunk().await?;
+                Ok(chunk.map(|chunk| (chunk, cursor)))
+            },
+        ))
+    }
+}
+
+struct ExportCursor {
+    db: Arc<DatabaseConnection>,
+    request: ExportRequest,
+    /// Taken once all rows are encoded
+    encoder: Option<Box<dyn format::RowEncoder>>,
+    header_written: bool,
+    /// Time of the last exported row
+    after: Option<DateTime>,
+}
+
+impl ExportCursor {
+    async fn next_chunk(&mut self) -> anyhow::Result<Option<Vec<u8>>> {
+        if self.encoder.is_none() {
+            return Ok(None);
+        }
+
+        if !self.header_written {
+            self.header_written = true;
+            if let Some(encoder) = self.encoder.as_mut() {
+                return encoder.header().map(Some);
+            }
+        }
+
+        let rows = next_page(&self.db, &self.request, self.after).await?;
+        match (rows.last(), self.encoder.as_mut()) {
+            (Some(last), Some(encoder)) => {
+                self.after = Some(last.time_utc);
+                encoder.encode(&rows).map(Some)
+            }
+            _ => match self.encoder.take() {
+                Some(encoder) => encoder.finish().map(Some),
+                None => Ok(None),
+            },
+        }
+    }
+}
+
+async fn next_page(
+    db: &DatabaseConnection,
+    request: &ExportRequest,
+    after: Option<DateTime>,
+) -> anyhow::Result<Vec<hourly_weather::Model>> {
+    let mut select =
+        hourly_weather::Entity::find_by_user_in_range(&request.user_id, request.from, request.to);
+    if let Some(after) = after {
+        select = select.filter(hourly_weather::Column::TimeUtc.gt(after));
+    }
+    Ok(select.limit(PAGE_SIZE).all(db).await?)
+}
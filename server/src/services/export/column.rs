@@ -0,0 +1,176 @@
+use crate::database::entity::hourly_weather;
+use omnistat_core::types::length::Length;
+use omnistat_core::types::percentage::Percentage;
+use omnistat_core::types::pressure::Pressure;
+use omnistat_core::types::speed::Speed;
+use omnistat_core::types::temperature::Temperature;
+use sea_orm::prelude::DateTime;
+use serde::Deserialize;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitSystem {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+pub enum Value {
+    Text(String),
+    Time(DateTime),
+    Integer(i32),
+    Float(f64),
+}
+
+#[derive(Copy, Clone)]
+pub enum ColumnKind {
+    Text,
+    Time,
+    Integer,
+    Float,
+}
+
+/// The unit a column is stored in, conversions go through the matching core type.
+#[derive(Copy, Clone)]
+pub enum Unit {
+    Celsius,
+    /// Stored as 0.0-1.0, exported as 0-100
+    Percent,
+    HectoPascal,
+    KilometersPerHour,
+    Degrees,
+    Millimeters,
+    Centimeters,
+    Meters,
+    WattsPerSquareMeter,
+}
+
+impl Unit {
+    pub fn symbol(self, units: UnitSystem) -> &'static str {
+        match (self, units) {
+            (Unit::Celsius, UnitSystem::Metric) => "°C",
+            (Unit::Celsius, UnitSystem::Imperial) => "°F",
+            (Unit::Percent, _) => "%",
+            (Unit::HectoPascal, UnitSystem::Metric) => "hPa",
+            (Unit::HectoPascal, UnitSystem::Imperial) => "inHg",
+            (Unit::KilometersPerHour, UnitSystem::Metric) => "km/h",
+            (Unit::KilometersPerHour, UnitSystem::Imperial) => "mph",
+            (Unit::Degrees, _) => "°",
+            (Unit::Millimeters, UnitSystem::Metric) => "mm",
+            (Unit::Centimeters, UnitSystem::Metric) => "cm",
+            (Unit::Millimeters | Unit::Centimeters, UnitSystem::Imperial) => "in",
+            (Unit::Meters, UnitSystem::Metric) => "m",
+            (Unit::Meters, UnitSystem::Imperial) => "mi",
+            (Unit::WattsPerSquareMeter, _) => "W/m²",
+        }
+    }
+
+    /// The converted value with one decimal and its symbol, e.g. `12.3 °C`.
+    pub fn format(self, value: f64, units: UnitSystem) -> String {
+        format!("{:.1} {}", self.convert(value, units), self.symbol(units))
+    }
+
+    pub fn convert(self, value: f64, units: UnitSystem) -> f64 {
+        match (self, units) {
+            (Unit::Percent, _) => Percentage::from_0_1(value as f32).as_0_100() as f64,
+            (_, UnitSystem::Metric) => value,
+            (Unit::Celsius, UnitSystem::Imperial) => {
+                Temperature::from_celsius(value as f32).as_fahrenheit() as f64
+            }
+            (Unit::HectoPascal, UnitSystem::Imperial) => {
+                Pressure::from_hpa(value as f32).as_inhg() as f64
+            }
+            (Unit::KilometersPerHour, UnitSystem::Imperial) => {
+                Speed::from_km_h(value as f32).as_mph() as f64
+            }
+            (Unit::Millimeters, UnitSystem::Imperial) => {
+                Length::from_millimeters(value).as_inches()
+            }
+            (Unit::Centimeters, UnitSystem::Imperial) => {
+                Length::from_centimeters(value).as_inches()
+            }
+            (Unit::Meters, UnitSystem::Imperial) => Length::from_meters(value).as_miles(),
+            (Unit::Degrees | Unit::WattsPerSquareMeter, UnitSystem::Imperial) => value,
+        }
+    }
+}
+
+pub struct Column {
+    pub name: &'static str,
+    pub kind: ColumnKind,
+    pub unit: Option<Unit>,
+    /// Reads the value as stored in the database
+    read: fn(&hourly_weather::Model) -> Value,
+}
+
+impl Column {
+    /// The column name with its unit, e.g. `temperature_actual [°C]`.
+    pub fn annotated_name(&self, units: UnitSystem) -> String {
+        match self.unit {
+            Some(unit) => format!("{} [{}]", self.name, unit.symbol(units)),
+            None => self.name.to_string(),
+        }
+    }
+
+    /// Floats are rounded to 4 decimals to hide the noise of the `f32` columns and conversions.
+    pub fn value(&self, row: &hourly_weather::Model, units: UnitSystem) -> Value {
+        match ((self.read)(row), self.unit) {
+            (Value::Float(value), Some(unit)) => {
+                Value::Float((unit.convert(value, units) * 10_000.0).round() / 10_000.0)
+            }
+            (value, _) => value,
+        }
+    }
+}
+
+macro_rules! float_column {
+    ($field:ident, $unit:expr) => {
+        Column {
+            name: stringify!($field),
+            kind: ColumnKind::Float,
+            unit: Some($unit),
+            read: |row| Value::Float(row.$field as f64),
+        }
+    };
+}
+
+pub const COLUMNS: [Column; 23] = [
+    Column {
+        name: "user_id",
+        kind: ColumnKind::Text,
+        unit: None,
+        read: |row| Value::Text(row.user_id.clone()),
+    },
+    Column {
+        name: "time_utc",
+        kind: ColumnKind::Time,
+        unit: None,
+        read: |row| Value::Time(row.time_utc),
+    },
+    Column {
+        name: "wmo_code",
+        kind: ColumnKind::Integer,
+        unit: None,
+        read: |row| Value::Integer(row.wmo_code),
+    },
+    float_column!(temperature_actual, Unit::Celsius),
+    float_column!(temperature_apparent, Unit::Celsius),
+    float_column!(relative_humidity, Unit::Percent),
+    float_column!(dew_point, Unit::Celsius),
+    float_column!(surface_pressure, Unit::HectoPascal),
+    float_column!(cloud_cover, Unit::Percent),
+    float_column!(cloud_cover_low, Unit::Percent),
+    float_column!(cloud_cover_mid, Unit::Percent),
+    float_column!(cloud_cover_high, Unit::Percent),
+    float_column!(wind_speed, Unit::KilometersPerHour),
+    float_column!(max_wind_speed, Unit::KilometersPerHour),
+    float_column!(wind_direction, Unit::Degrees),
+    float_column!(total_precipitation, Unit::Millimeters),
+    float_column!(precipitation_probability, Unit::Percent),
+    float_column!(rain, Unit::Millimeters),
+    float_column!(snowfall, Unit::Millimeters),
+    float_column!(snow_depth, Unit::Centimeters),
+    float_column!(showers, Unit::Millimeters),
+    float_column!(visibility, Unit::Meters),
+    float_column!(shortwave_radiation, Unit::WattsPerSquareMeter),
+];
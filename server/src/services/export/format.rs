@@ -0,0 +1,246 @@
+use crate::database::entity::hourly_weather;
+use crate::services::export::column::{COLUMNS, ColumnKind, UnitSystem, Value};
+use arrow_array::builder::{
+    Float64Builder, Int32Builder, StringBuilder, TimestampMicrosecondBuilder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Ndjson,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+
+    pub(super) fn encoder(self, units: UnitSystem) -> anyhow::Result<Box<dyn RowEncoder>> {
+        Ok(match self {
+            ExportFormat::Csv => Box::new(CsvEncoder { units }),
+            ExportFormat::Ndjson => Box::new(NdjsonEncoder { units }),
+            ExportFormat::Parquet => Box::new(ParquetEncoder::new(units)?),
+        })
+    }
+}
+
+/// Turns pages of rows into bytes, so exports can be streamed without holding all rows.
+pub(super) trait RowEncoder: Send {
+    fn header(&mut self) -> anyhow::Result<Vec<u8>>;
+    fn encode(&mut self, rows: &[hourly_weather::Model]) -> anyhow::Result<Vec<u8>>;
+    fn finish(self: Box<Self>) -> anyhow::Result<Vec<u8>>;
+}
+
+struct CsvEncoder {
+    units: UnitSystem,
+}
+
+impl CsvEncoder {
+    fn writer() -> csv::Writer<Vec<u8>> {
+        csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(Vec::new())
+    }
+}
+
+impl RowEncoder for CsvEncoder {
+    fn header(&mut self) -> anyhow::Result<Vec<u8>> {
+        let mut writer = Self::writer();
+        writer.write_record(
+            COLUMNS
+                .iter()
+                .map(|column| column.annotated_name(self.units)),
+        )?;
+        Ok(writer.into_inner()?)
+    }
+
+    fn encode(&mut self, rows: &[hourly_weather::Model]) -> anyhow::Result<Vec<u8>> {
+        let mut writer = Self::writer();
+        for row in rows {
+            writer.write_record(COLUMNS.iter().map(
+                |column| match column.value(row, self.units) {
+                    Value::Text(value) => value,
+                    Value::Time(value) => value.and_utc().to_rfc3339(),
+                    Value::Integer(value) => value.to_string(),
+                    Value::Float(value) => value.to_string(),
+                },
+            ))?;
+        }
+        Ok(writer.into_inner()?)
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+}
+
+struct NdjsonEncoder {
+    units: UnitSystem,
+}
+
+impl RowEncoder for NdjsonEncoder {
+    fn header(&mut self) -> anyhow::Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn encode(&mut self, rows: &[hourly_weather::Model]) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        for row in rows {
+            let object: serde_json::Map<String, serde_json::Value> = COLUMNS
+                .iter()
+                .map(|column| {
+                    let value = match column.value(row, self.units) {
+                        Value::Text(value) => value.into(),
+                        Value::Time(value) => value.and_utc().to_rfc3339().into(),
+                        Value::Integer(value) => value.into(),
+                        Value::Float(value) => value.into(),
+                    };
+                    (column.annotated_name(self.units), value)
+                })
+                .collect();
+            serde_json::to_writer(&mut buffer, &object)?;
+            buffer.push(b'\n');
+        }
+        Ok(buffer)
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Every page becomes a row group, units are stored as `unit` field metadata.
+struct ParquetEncoder {
+    units: UnitSystem,
+    schema: SchemaRef,
+    writer: ArrowWriter<Vec<u8>>,
+}
+
+impl ParquetEncoder {
+    fn new(units: UnitSystem) -> anyhow::Result<Self> {
+        let fields: Vec<Field> = COLUMNS
+            .iter()
+            .map(|column| {
+                let data_type = match column.kind {
+                    ColumnKind::Text => DataType::Utf8,
+                    ColumnKind::Time => {
+                        DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+                    }
+                    ColumnKind::Integer => DataType::Int32,
+                    ColumnKind::Float => DataType::Float64,
+                };
+                let field = Field::new(column.name, data_type, false);
+                match column.unit {
+                    Some(unit) => field.with_metadata(HashMap::from([(
+                        "unit".to_string(),
+                        unit.symbol(units).to_string(),
+                    )])),
+                    None => field,
+                }
+            })
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let writer = ArrowWriter::try_new(Vec::new(), schema.clone(), Some(properties))?;
+
+        Ok(Self {
+            units,
+            schema,
+            writer,
+        })
+    }
+
+    fn take_written(&mut self) -> Vec<u8> {
+        std::mem::take(self.writer.inner_mut())
+    }
+}
+
+impl RowEncoder for ParquetEncoder {
+    fn header(&mut self) -> anyhow::Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn encode(&mut self, rows: &[hourly_weather::Model]) -> anyhow::Result<Vec<u8>> {
+        let columns: Vec<ArrayRef> = COLUMNS
+            .iter()
+            .map(|column| {
+                let values = rows.iter().map(|row| column.value(row, self.units));
+                let array: ArrayRef = match column.kind {
+                    ColumnKind::Text => {
+                        let mut builder = StringBuilder::new();
+                        values.for_each(|value| {
+                            if let Value::Text(value) = value {
+                                builder.append_value(value);
+                            }
+                        });
+                        Arc::new(builder.finish())
+                    }
+                    ColumnKind::Time => {
+                        let mut builder = TimestampMicrosecondBuilder::new().with_timezone("UTC");
+                        values.for_each(|value| {
+                            if let Value::Time(value) = value {
+                                builder.append_value(value.and_utc().timestamp_micros());
+                            }
+                        });
+                        Arc::new(builder.finish())
+                    }
+                    ColumnKind::Integer => {
+                        let mut builder = Int32Builder::new();
+                        values.for_each(|value| {
+                            if let Value::Integer(value) = value {
+                                builder.append_value(value);
+                            }
+                        });
+                        Arc::new(builder.finish())
+                    }
+                    ColumnKind::Float => {
+                        let mut builder = Float64Builder::new();
+                        values.for_each(|value| {
+                            if let Value::Float(value) = value {
+                                builder.append_value(value);
+                            }
+                        });
+                        Arc::new(builder.finish())
+                    }
+                };
+                array
+            })
+            .collect();
+
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)?;
+        self.writer.write(&batch)?;
+        self.writer.flush()?;
+        Ok(self.take_written())
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<Vec<u8>> {
+        // Writes the footer and hands out everything not yet taken.
+        Ok(self.writer.into_inner()?)
+    }
+}
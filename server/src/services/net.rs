@@ -0,0 +1,447 @@
+use crate::config::SharedConfig;
+use crate::database::entity::hourly_weather;
+use crate::events::{EventBus, ServerEvent};
+use crate::services::ServiceInitContext;
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use omnistat_net::protocol::{
+    Alert, DataKind, GetHourlyWeather, GetHourlyWeatherChunks, HourlyWeather, HourlyWeatherChunk,
+    ListUsers, Subscribe, SubscriptionUpdate,
+};
+use omnistat_net::rpc::{ErrorCode, Handlers, RpcError, ServerFrame, encode_payload};
+use sea_orm::{ColumnTrait, DatabaseConnection, QueryFilter, QuerySelect};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tracing::{error, warn};
+
+/// Updates of a subscription buffered before the subscription falls behind the event bus.
+const SUBSCRIPTION_CAPACITY: usize = 16;
+
+/// Hours of a [`HourlyWeatherChunk`], a week.
+const CHUNK_HOURS: u64 = 168;
+
+/// Chunks loaded ahead of the client, loading waits until it reads them.
+const CHUNK_CAPACITY: usize = 2;
+
+/// What a connection may read, decided by the token it authenticated with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetAccess {
+    /// The token of the server, or any client while no tokens are configured
+    All,
+    /// The token of a user
+    User(String),
+}
+
+impl NetAccess {
+    pub fn allows(&self, user_id: &str) -> bool {
+        match self {
+            NetAccess::All => true,
+            NetAccess::User(own_id) => own_id == user_id,
+        }
+    }
+}
+
+/// Answers requests of the binary protocol, independent of the transport they arrived on.
+pub struct NetService {
+    config: SharedConfig,
+    db: Arc<DatabaseConnection>,
+    events: EventBus,
+}
+
+impl NetService {
+    pub fn initialize(context: &ServiceInitContext) -> Arc<Self> {
+        Arc::new(Self {
+            config: context.config.clone(),
+            db: context.db.clone(),
+            events: context.events.clone(),
+        })
+    }
+
+    /// The access a token grants, `None` if it is rejected.
+    pub fn authenticate(&self, token: Option<&str>) -> Option<NetAccess> {
+        let config = self.config.load();
+        let no_tokens = config.net_token.is_none()
+            && config.users.values().all(|user| user.net_token.is_none());
+        if no_tokens {
+            return Some(NetAccess::All);
+        }
+
+        let token = token?;
+        if config
+            .net_token
+            .as_deref()
+            .is_some_and(|expected| tokens_match(expected, token))
+        {
+            return Some(NetAccess::All);
+        }
+        config
+            .users
+            .iter()
+            .find(|(_, user)| {
+                user.net_token
+                    .as_deref()
+                    .is_some_and(|expected| tokens_match(expected, token))
+            })
+            .map(|(user_id, _)| NetAccess::User(user_id.clone()))
+    }
+
+    /// Whether the token is the one of the server. Unlike [`Self::authenticate`] nobody is an
+    /// admin while no server token is configured.
+    pub fn is_admin(&self, token: Option<&str>) -> bool {
+        let config = self.config.load();
+        config
+            .net_token
+            .as_deref()
+            .zip(token)
+            .is_some_and(|(expected, token)| tokens_match(expected, token))
+    }
+
+    /// Handlers of every method of the protocol for a connection with the given access.
+    pub fn handlers(self: &Arc<Self>, access: NetAccess) -> Handlers {
+        let mut handlers = Handlers::default();
+        let access = Arc::new(access);
+
+        let context = (self.clone(), access.clone());
+        handlers.unary(move |_: ListUsers| {
+            let (service, access) = context.clone();
+            async move { Ok(service.list_users(&access)) }
+        });
+        let context = (self.clone(), access.clone());
+        handlers.unary(move |request: GetHourlyWeather| {
+            let (service, access) = context.clone();
+            async move { service.hourly_weather(&access, request).await }
+        });
+        let context = (self.clone(), access.clone());
+        handlers.stream(move |request: GetHourlyWeatherChunks| {
+            let (service, access) = context.clone();
+            async move { service.hourly_weather_chunks(&access, request) }
+        });
+        let context = (self.clone(), access);
+        handlers.stream(move |request: Subscribe| {
+            let (service, access) = context.clone();
+            async move { service.subscribe(&access, request) }
+        });
+
+        handlers
+    }
+
+    /// Responses with a day of hourly weather each, as sent to clients, to train the dictionary
+    /// of [`omnistat_net::compression::WEATHER_DICTIONARY`] on.
+    pub async fn dictionary_samples(&self) -> anyhow::Result<Vec<Vec<u8>>> {
+        let mut samples = Vec::new();
+        for user_id in self.list_users(&NetAccess::All) {
+            let hours = hourly_weather::Entity::find_by_user_in_range(&user_id, None, None)
+                .all(self.db.as_ref())
+                .await?;
+            for (id, day) in hours.chunks(24).enumerate() {
+                let day: Vec<HourlyWeather> = day.iter().map(to_wire).collect();
+                let frame = ServerFrame::Response {
+                    id: id as u64,
+                    result: Ok(encode_payload(&day)?),
+                };
+                samples.push(encode_payload(&frame)?);
+            }
+        }
+        Ok(samples)
+    }
+
+    fn list_users(&self, access: &NetAccess) -> Vec<String> {
+        let mut users: Vec<String> = self
+            .config
+            .load()
+            .users
+            .keys()
+            .filter(|user_id| access.allows(user_id))
+            .cloned()
+            .collect();
+        users.sort();
+        users
+    }
+
+    async fn hourly_weather(
+        &self,
+        access: &NetAccess,
+        request: GetHourlyWeather,
+    ) -> Result<Vec<HourlyWeather>, RpcError> {
+        self.check_user(access, &request.user_id)?;
+        let from = request.from.map(naive_from_timestamp).transpose()?;
+        let to = request.to.map(naive_from_timestamp).transpose()?;
+        let hours = hourly_weather::Entity::find_by_user_in_range(&request.user_id, from, to)
+            .all(self.db.as_ref())
+            .await
+            .map_err(internal)?;
+        Ok(hours.iter().map(to_wire).collect())
+    }
+
+    /// Loads the hours of the users page by page while the client reads them, the pages are
+    /// never all held in memory. A failure ends the stream with an error.
+    fn hourly_weather_chunks(
+        &self,
+        access: &NetAccess,
+        request: GetHourlyWeatherChunks,
+    ) -> Result<mpsc::Receiver<Result<HourlyWeatherChunk, RpcError>>, RpcError> {
+        for user_id in &request.user_ids {
+            self.check_user(access, user_id)?;
+        }
+        let from = request.from.map(naive_from_timestamp).transpose()?;
+        let to = request.to.map(naive_from_timestamp).transpose()?;
+        let (sender, receiver) = mpsc::channel(CHUNK_CAPACITY);
+
+        let db = self.db.clone();
+        tokio::spawn(async move {
+            for user_id in request.user_ids {
+                let mut after = None;
+                loop {
+                    let mut select =
+                        hourly_weather::Entity::find_by_user_in_range(&user_id, from, to);
+                    if let Some(after) = after {
+                        select = select.filter(hourly_weather::Column::TimeUtc.gt(after));
+                    }
+                    let hours = match select.limit(CHUNK_HOURS).all(db.as_ref()).await {
+                        Ok(hours) => hours,
+                        Err(e) => {
+                            let _ = sender.send(Err(internal(e))).await;
+                            return;
+                        }
+                    };
+                    let Some(last) = hours.last() else {
+                        break;
+                    };
+                    after = Some(last.time_utc);
+
+                    let chunk = HourlyWeatherChunk {
+                        user_id: user_id.clone(),
+                        hours: hours.iter().map(to_wire).collect(),
+                    };
+                    if sender.send(Ok(chunk)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(receiver)
+    }
+
+    /// Forwards the updates a subscriber asked for until it goes away.
+    fn subscribe(
+        self: &Arc<Self>,
+        access: &NetAccess,
+        request: Subscribe,
+    ) -> Result<mpsc::Receiver<Result<SubscriptionUpdate, RpcError>>, RpcError> {
+        self.check_user(access, &request.user_id)?;
+        let mut events = self.events.subscribe();
+        let (sender, receiver) = mpsc::channel(SUBSCRIPTION_CAPACITY);
+        let _ = sender.try_send(Ok(SubscriptionUpdate::Subscribed));
+
+        let service = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    _ = sender.closed() => return,
+                    event = events.recv() => event,
+                };
+                let update = match event {
+                    Ok(event) => service.update_for(&event, &request).await,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(
+                            "Net subscription of '{}' lagged behind, skipped {skipped} event(s)",
+                            request.user_id
+                        );
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return,
+                };
+                if let Some(update) = update
+                    && sender.send(Ok(update)).await.is_err()
+                {
+                    return;
+                }
+            }
+        });
+
+        Ok(receiver)
+    }
+
+    /// The update pushed to a subscriber for an event, if it asked for it.
+    async fn update_for(
+        &self,
+        event: &ServerEvent,
+        subscription: &Subscribe,
+    ) -> Option<SubscriptionUpdate> {
+        let wants = |kind| subscription.kinds.contains(&kind);
+        match event {
+            ServerEvent::HourlyWeatherSynced { user_id }
+                if *user_id == subscription.user_id && wants(DataKind::HourlyWeather) =>
+            {
+                // The forecast ahead, past hours didn't change with the sync.
+                let now = Utc::now().duration_trunc(TimeDelta::hours(1)).ok()?;
+                let hours = hourly_weather::Entity::find_by_user_in_range(
+                    user_id,
+                    Some(now.naive_utc()),
+                    None,
+                )
+                .all(self.db.as_ref())
+                .await;
+                match hours {
+                    Ok(hours) => Some(SubscriptionUpdate::HourlyWeather {
+                        user_id: user_id.clone(),
+                        hours: hours.iter().map(to_wire).collect(),
+                    }),
+                    Err(e) => {
+                        error!("Failed to load hourly weather of '{user_id}' for subscribers: {e}");
+                        None
+                    }
+                }
+            }
+            ServerEvent::AlertFired(alert)
+                if alert.user_id == subscription.user_id && wants(DataKind::Alerts) =>
+            {
+                Some(SubscriptionUpdate::Alert(Alert {
+                    user_id: alert.user_id.clone(),
+                    rule_id: alert.rule_id.clone(),
+                    rule: alert.rule.clone(),
+                    message: alert.message.clone(),
+                    first_match: alert.first_match.timestamp(),
+                    fired_at: alert.fired_at.timestamp(),
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Other users are forbidden whether they exist or not, so tokens can't probe for them.
+    fn check_user(&self, access: &NetAccess, user_id: &str) -> Result<(), RpcError> {
+        if !access.allows(user_id) {
+            return Err(RpcError::new(
+                ErrorCode::Forbidden,
+                format!("No access to user '{user_id}'"),
+            ));
+        }
+        if self.config.load().users.contains_key(user_id) {
+            Ok(())
+        } else {
+            Err(RpcError::new(
+                ErrorCode::NotFound,
+                format!("User '{user_id}' does not exist"),
+            ))
+        }
+    }
+}
+
+/// Compares in constant time, so that the time of a rejection doesn't reveal a matching prefix.
+fn tokens_match(expected: &str, token: &str) -> bool {
+    expected.len() == token.len()
+        && expected
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// Details of internal failures stay in the log.
+fn internal(e: impl std::fmt::Display) -> RpcError {
+    error!("Net request failed: {e}");
+    RpcError::new(ErrorCode::Internal, "Internal error")
+}
+
+fn naive_from_timestamp(seconds: i64) -> Result<sea_orm::prelude::DateTime, RpcError> {
+    DateTime::from_timestamp(seconds, 0)
+        .map(|time| time.naive_utc())
+        .ok_or_else(|| {
+            RpcError::new(
+                ErrorCode::BadRequest,
+                format!("Timestamp {seconds} is out of range"),
+            )
+        })
+}
+
+fn to_wire(model: &hourly_weather::Model) -> HourlyWeather {
+    HourlyWeather {
+        time: model.time_utc.and_utc().timestamp(),
+        wmo_code: u8::try_from(model.wmo_code).unwrap_or(u8::MAX),
+        temperature: model.temperature_actual,
+        apparent_temperature: model.temperature_apparent,
+        relative_humidity: model.relative_humidity,
+        dew_point: model.dew_point,
+        surface_pressure: model.surface_pressure,
+        cloud_cover: model.cloud_cover,
+        cloud_cover_low: model.cloud_cover_low,
+        cloud_cover_mid: model.cloud_cover_mid,
+        cloud_cover_high: model.cloud_cover_high,
+        wind_speed: model.wind_speed,
+        max_wind_speed: model.max_wind_speed,
+        wind_direction: model.wind_direction,
+        total_precipitation: model.total_precipitation,
+        precipitation_probability: model.precipitation_probability,
+        rain: model.rain,
+        snowfall: model.snowfall,
+        snow_depth: model.snow_depth,
+        showers: model.showers,
+        visibility: model.visibility,
+        shortwave_radiation: model.shortwave_radiation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use arc_swap::ArcSwap;
+
+    fn service(source: &str) -> NetService {
+        let config: Config = toml::from_str(source).unwrap();
+        NetService {
+            config: Arc::new(ArcSwap::from_pointee(config)),
+            db: Arc::new(DatabaseConnection::default()),
+            events: EventBus::new(),
+        }
+    }
+
+    const TOKENS: &str = r#"
+net_token = "server"
+
+[users.a]
+latitude = 1.0
+longitude = 2.0
+net_token = "token-a"
+
+[users.b]
+latitude = 1.0
+longitude = 2.0
+"#;
+
+    #[test]
+    fn tokens_grant_their_access() {
+        let service = service(TOKENS);
+        assert_eq!(service.authenticate(Some("server")), Some(NetAccess::All));
+        assert_eq!(
+            service.authenticate(Some("token-a")),
+            Some(NetAccess::User("a".to_string()))
+        );
+        assert!(service.is_admin(Some("server")));
+        assert!(!service.is_admin(Some("token-a")));
+    }
+
+    #[test]
+    fn rejects_wrong_and_missing_tokens() {
+        let service = service(TOKENS);
+        for token in [
+            None,
+            Some(""),
+            Some("serve"),
+            Some("server "),
+            Some("token-b"),
+        ] {
+            assert_eq!(service.authenticate(token), None, "{token:?}");
+            assert!(!service.is_admin(token), "{token:?}");
+        }
+    }
+
+    #[test]
+    fn accepts_everyone_without_tokens_but_no_admin() {
+        let service = service("[users.a]\nlatitude = 1.0\nlongitude = 2.0\n");
+        assert_eq!(service.authenticate(None), Some(NetAccess::All));
+        assert!(!service.is_admin(None));
+    }
+}
@@ -0,0 +1,182 @@
+use crate::config::SharedConfig;
+use crate::database::entity::{daily_weather, hourly_weather, hourly_weather_rollup};
+use crate::services::ServiceInitContext;
+use crate::services::export::column::UnitSystem;
+use crate::services::export::encode_rows;
+use crate::services::export::format::ExportFormat;
+use crate::services::weather::hour::local_day_bounds;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tracing::{error, info};
+
+/// What a retention run did, or would do in a dry run, for one user.
+#[derive(Debug)]
+pub struct RetentionReport {
+    pub user_id: String,
+    /// Hours before this instant were expired
+    pub cutoff: DateTime<Utc>,
+    /// Local days rolled up
+    pub days: usize,
+    /// Hourly rows rolled up and removed
+    pub hours: usize,
+    /// Hourly rows of days rolled up by an earlier run, synced again and removed without
+    /// touching the rollup
+    pub resynced_hours: usize,
+    /// Files the removed hours were appended to
+    pub archived_to: Vec<PathBuf>,
+}
+
+pub struct RetentionService {
+    config: SharedConfig,
+    db: Arc<DatabaseConnection>,
+}
+
+impl RetentionService {
+    pub fn initialize(context: &ServiceInitContext) -> Arc<Self> {
+        Arc::new(Self {
+            config: context.config.clone(),
+            db: context.db.clone(),
+        })
+    }
+
+    /// Applies the retention policy to every user, does nothing if no retention is configured.
+    pub async fn run(&self, dry_run: bool) -> Vec<RetentionReport> {
+        let config = self.config.load_full();
+        if config.retention.hourly_days.is_none() {
+            return Vec::new();
+        }
+
+        let mut reports = Vec::new();
+        for user_id in config.users.keys() {
+            match self.run_user(user_id, dry_run).await {
+                Ok(report) => reports.push(report),
+                Err(e) => error!("Failed to apply retention to user '{user_id}': {e:#}"),
+            }
+        }
+        reports
+    }
+
+    /// Rolls the hours of complete local days older than the retention period up into daily
+    /// aggregates, archives them if configured and deletes them. A dry run only reports.
+    ///
+    /// Days are only rolled up once complete, so hours of a day that already has a rollup were
+    /// synced again after it. They are deleted without being rolled up or archived a second time,
+    /// replacing the rollup would lose the hours removed before.
+    pub async fn run_user(&self, user_id: &str, dry_run: bool) -> anyhow::Result<RetentionReport> {
+        let config = self.config.load_full();
+        let Some(hourly_days) = config.retention.hourly_days else {
+            anyhow::bail!("No retention is configured, set retention.hourly_days");
+        };
+
+        let timezone =
+            daily_weather::Entity::find_timezone_by_user(user_id, self.db.as_ref()).await?;
+        let today = Utc::now().with_timezone(&timezone).date_naive();
+        let cutoff_date = today
+            .checked_sub_days(Days::new(hourly_days as u64))
+            .unwrap_or(today);
+        let (cutoff, _) = local_day_bounds(cutoff_date, timezone);
+
+        let hours =
+            hourly_weather::Entity::find_by_user_in_range(user_id, None, Some(cutoff.naive_utc()))
+                .all(self.db.as_ref())
+                .await?;
+        let rolled_up: HashSet<NaiveDate> =
+            hourly_weather_rollup::Entity::find_by_user_in_range(user_id, None, Some(cutoff_date))
+                .all(self.db.as_ref())
+                .await?
+                .into_iter()
+                .map(|rollup| rollup.date)
+                .collect();
+        let (resynced, days): (Vec<_>, Vec<_>) = hours
+            .chunk_by(|a, b| local_date(a, timezone) == local_date(b, timezone))
+            .filter_map(|day| Some((local_date(day.first()?, timezone), day)))
+            .partition(|(date, _)| rolled_up.contains(date));
+
+        let mut report = RetentionReport {
+            user_id: user_id.to_string(),
+            cutoff,
+            days: days.len(),
+            hours: days.iter().map(|(_, day)| day.len()).sum(),
+            resynced_hours: resynced.iter().map(|(_, day)| day.len()).sum(),
+            archived_to: Vec::new(),
+        };
+        if dry_run || hours.is_empty() {
+            return Ok(report);
+        }
+
+        // Archived before deleting, a failed run can at worst archive hours twice.
+        if let Some(archive_dir) = &config.retention.archive_dir {
+            report.archived_to = archive(archive_dir, user_id, &days).await?;
+        }
+
+        let transaction = self.db.begin().await?;
+        for (date, day) in &days {
+            hourly_weather_rollup::Entity::upsert(
+                hourly_weather_rollup::ActiveModel::from_hours(user_id, *date, timezone, day),
+                &transaction,
+            )
+            .await?;
+        }
+        for (date, _) in days.iter().chain(&resynced) {
+            let (from, to) = local_day_bounds(*date, timezone);
+            hourly_weather::Entity::delete_by_user_in_range(
+                user_id,
+                from.naive_utc(),
+                to.naive_utc(),
+                &transaction,
+            )
+            .await?;
+        }
+        transaction.commit().await?;
+
+        info!(
+            "Rolled up {} hour(s) of user '{user_id}' into {} day(s), removed {} hour(s) of days \
+             rolled up before",
+            report.hours, report.days, report.resynced_hours
+        );
+        Ok(report)
+    }
+}
+
+fn local_date(hour: &hourly_weather::Model, timezone: Tz) -> NaiveDate {
+    hour.time_utc
+        .and_utc()
+        .with_timezone(&timezone)
+        .date_naive()
+}
+
+/// Appends the hours as NDJSON in metric units to `{archive_dir}/{user_id}/{YYYY-MM}.ndjson`.
+async fn archive(
+    archive_dir: &Path,
+    user_id: &str,
+    days: &[(NaiveDate, &[hourly_weather::Model])],
+) -> anyhow::Result<Vec<PathBuf>> {
+    let user_dir = archive_dir.join(user_id);
+    tokio::fs::create_dir_all(&user_dir).await?;
+
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for (date, day) in days {
+        let path = user_dir.join(format!(
+            "{}.{}",
+            date.format("%Y-%m"),
+            ExportFormat::Ndjson.extension()
+        ));
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        file.write_all(&encode_rows(day, ExportFormat::Ndjson, UnitSystem::Metric)?)
+            .await?;
+        file.flush().await?;
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
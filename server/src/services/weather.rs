@@ -1,19 +1,33 @@
-use crate::config::Config;
-use crate::database::entity::hourly_weather;
+use crate::config::SharedConfig;
+use crate::database::entity::{daily_weather, data_quality, hourly_weather};
+use crate::events::{EventBus, ServerEvent};
 use crate::services::ServiceInitContext;
-use omnistat_integrations::apis::open_meteo::hourly_forecast::OpenMeteoHourly;
+use crate::services::export::column::UnitSystem;
+use crate::services::export::encode_rows;
+use crate::services::export::format::ExportFormat;
+use crate::services::weather::quality::{History, QualityIssue};
+use chrono::{TimeDelta, Utc};
+use omnistat_core::types::latitude::Latitude;
+use omnistat_core::types::longitude::Longitude;
 use omnistat_integrations::apis::open_meteo::OpenMeteoApi;
-use sea_orm::DatabaseConnection;
+use omnistat_integrations::apis::open_meteo::daily_forecast::OpenMeteoDaily;
+use omnistat_integrations::apis::open_meteo::hourly_forecast::OpenMeteoHourly;
+use sea_orm::{DatabaseConnection, Set, TryIntoModel};
 use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-mod hour;
+pub mod hour;
+pub mod quality;
+
+/// Stored hours before a sync that new hours are compared against.
+const HISTORY: TimeDelta = TimeDelta::days(30);
 
 #[derive(Clone)]
 pub struct WeatherService {
-    config: Arc<Config>,
+    config: SharedConfig,
     db: Arc<DatabaseConnection>,
     open_meteo: Arc<OpenMeteoApi>,
+    events: EventBus,
 }
 
 impl WeatherService {
@@ -22,12 +36,14 @@ impl WeatherService {
             config: context.config.clone(),
             db: context.db.clone(),
             open_meteo: context.apis.open_meteo.clone(),
+            events: context.events.clone(),
         })
     }
 
     pub async fn sync_hourly_weather(&self) {
         info!("Syncing hourly weather...");
-        for user_id in self.config.users.keys() {
+        let config = self.config.load_full();
+        for user_id in config.users.keys() {
             info!("Processing user {}", user_id);
             let result = self.sync_hourly_weather_user(user_id).await;
             if let Err(e) = result {
@@ -42,24 +58,114 @@ impl WeatherService {
         info!("Finished syncing hourly weather");
     }
 
-    async fn sync_hourly_weather_user(&self, user_id: &str) -> anyhow::Result<()> {
-        let config_user = self.config.get_user_or_err(user_id)?;
+    pub async fn sync_hourly_weather_user(&self, user_id: &str) -> anyhow::Result<()> {
+        let (latitude, longitude) = self.user_location(user_id)?;
         let open_meteo_hourlies = self
             .open_meteo
-            .hourly_forecasts(config_user.latitude, config_user.longitude)
+            .hourly_forecasts(latitude, longitude)
+            .await?;
+        self.sync_open_meteo_hourlies(user_id, open_meteo_hourlies)
+            .await?;
+        self.events.publish(ServerEvent::HourlyWeatherSynced {
+            user_id: user_id.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Fills in the hourly weather of the past days, returns the amount of synced hours.
+    pub async fn backfill_hourly_weather_user(
+        &self,
+        user_id: &str,
+        past_days: u8,
+    ) -> anyhow::Result<usize> {
+        let (latitude, longitude) = self.user_location(user_id)?;
+        let open_meteo_hourlies = self
+            .open_meteo
+            .hourly_history(latitude, longitude, past_days)
+            .await?;
+        let count = self
+            .sync_open_meteo_hourlies(user_id, open_meteo_hourlies)
             .await?;
-        for hourly in open_meteo_hourlies {
-            self.sync_open_meteo_hourly(user_id, hourly).await?;
+        self.events.publish(ServerEvent::HourlyWeatherSynced {
+            user_id: user_id.to_string(),
+        });
+        Ok(count)
+    }
+
+    pub async fn sync_daily_weather(&self) {
+        info!("Syncing daily weather...");
+        let config = self.config.load_full();
+        for user_id in config.users.keys() {
+            let result = self.sync_daily_weather_user(user_id).await;
+            if let Err(e) = result {
+                error!("Failed to sync daily weather for user '{}': {}", user_id, e);
+            }
+        }
+        info!("Finished syncing daily weather");
+    }
+
+    pub async fn sync_daily_weather_user(&self, user_id: &str) -> anyhow::Result<()> {
+        let (latitude, longitude) = self.user_location(user_id)?;
+        let open_meteo_dailies = self.open_meteo.daily_forecasts(latitude, longitude).await?;
+        for daily in open_meteo_dailies {
+            self.sync_open_meteo_daily(user_id, daily).await?;
         }
+        self.events.publish(ServerEvent::DailyWeatherSynced {
+            user_id: user_id.to_string(),
+        });
         Ok(())
     }
 
+    fn user_location(&self, user_id: &str) -> anyhow::Result<(Latitude, Longitude)> {
+        let config = self.config.load();
+        let config_user = config.get_user_or_err(user_id)?;
+        Ok((config_user.latitude, config_user.longitude))
+    }
+
+    /// Stores the hours that pass the quality checks and flags the others in `data_quality`,
+    /// returns the amount of stored hours.
+    async fn sync_open_meteo_hourlies(
+        &self,
+        user_id: &str,
+        hourlies: Vec<OpenMeteoHourly>,
+    ) -> anyhow::Result<usize> {
+        let (Some(first), Some(last)) = (
+            hourlies.iter().map(|hourly| hourly.time).min(),
+            hourlies.iter().map(|hourly| hourly.time).max(),
+        ) else {
+            return Ok(0);
+        };
+        let stored = hourly_weather::Entity::find_by_user_in_range(
+            user_id,
+            Some((first - HISTORY).naive_utc()),
+            Some(last.naive_utc()),
+        )
+        .all(self.db.as_ref())
+        .await?;
+        let mut history = History::new(&stored, first);
+
+        let mut synced = 0;
+        for hourly in hourlies {
+            let mut issues = quality::check_bounds(&hourly);
+            issues.extend(history.check(&hourly));
+            if issues.is_empty() {
+                self.sync_open_meteo_hourly(user_id, &hourly).await?;
+                history.accept(&hourly);
+                synced += 1;
+            } else {
+                self.flag_open_meteo_hourly(user_id, &hourly, &issues)
+                    .await?;
+            }
+        }
+        Ok(synced)
+    }
+
     async fn sync_open_meteo_hourly(
         &self,
         user_id: &str,
-        hourly: OpenMeteoHourly,
+        hourly: &OpenMeteoHourly,
     ) -> anyhow::Result<()> {
-        let active_model = hourly_weather::ActiveModel::from_open_meteo(&hourly, user_id);
+        let active_model = hourly_weather::ActiveModel::from_open_meteo(hourly, user_id);
         hourly_weather::Entity::upsert(active_model, self.db.as_ref()).await?;
         info!(
             "Synced open meteo hourly of '{}' for user '{}'",
@@ -67,4 +173,67 @@ impl WeatherService {
         );
         Ok(())
     }
+
+    /// Records a suspicious hour with its values instead of storing it.
+    async fn flag_open_meteo_hourly(
+        &self,
+        user_id: &str,
+        hourly: &OpenMeteoHourly,
+        issues: &[QualityIssue],
+    ) -> anyhow::Result<()> {
+        let model =
+            hourly_weather::ActiveModel::from_open_meteo(hourly, user_id).try_into_model()?;
+        let payload = encode_rows(&[model], ExportFormat::Ndjson, UnitSystem::Metric)?;
+        let mut checks: Vec<&str> = issues.iter().map(|issue| issue.check.name()).collect();
+        checks.sort_unstable();
+        checks.dedup();
+        let message = issues
+            .iter()
+            .map(|issue| issue.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        warn!(
+            "Flagged open meteo hourly of '{}' for user '{user_id}': {message}",
+            hourly.time
+        );
+
+        let now = Utc::now().naive_utc();
+        data_quality::Entity::flag(
+            data_quality::ActiveModel {
+                user_id: Set(user_id.to_string()),
+                time_utc: Set(hourly.time.naive_utc()),
+                checks: Set(checks.join(",")),
+                message: Set(message),
+                payload: Set(String::from_utf8(payload)?.trim_end().to_string()),
+                occurrences: Set(1),
+                first_flagged_at_utc: Set(now),
+                last_flagged_at_utc: Set(now),
+            },
+            self.db.as_ref(),
+        )
+        .await
+    }
+
+    /// Flagged hours of a user, most recently flagged first.
+    pub async fn data_quality(
+        &self,
+        user_id: &str,
+        limit: u64,
+    ) -> anyhow::Result<Vec<data_quality::Model>> {
+        data_quality::Entity::find_by_user(user_id, limit, self.db.as_ref()).await
+    }
+
+    async fn sync_open_meteo_daily(
+        &self,
+        user_id: &str,
+        daily: OpenMeteoDaily,
+    ) -> anyhow::Result<()> {
+        let active_model = daily_weather::ActiveModel::from_open_meteo(&daily, user_id);
+        daily_weather::Entity::upsert(active_model, self.db.as_ref()).await?;
+        info!(
+            "Synced open meteo daily of '{}' for user '{}'",
+            daily.time, user_id
+        );
+        Ok(())
+    }
 }
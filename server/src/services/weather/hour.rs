@@ -1 +1,100 @@
-pub struct WeatherHour {}
+use crate::database::entity::hourly_weather;
+use chrono::{DateTime, NaiveDate, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+use omnistat_core::types::length::Length;
+use omnistat_core::types::percentage::Percentage;
+use omnistat_core::types::pressure::Pressure;
+use omnistat_core::types::speed::Speed;
+use omnistat_core::types::temperature::Temperature;
+use omnistat_core::types::wmo_code::WMOCode;
+
+/// A stored hour of weather of a user with its values in their core types.
+#[derive(Debug, Clone)]
+pub struct WeatherHour {
+    /// Instant of the values, precipitation and radiation are sums or averages of the preceding hour
+    pub time: DateTime<Utc>,
+    pub wmo_code: WMOCode,
+    pub temperature: Temperature,
+    pub apparent_temperature: Temperature,
+    pub relative_humidity: Percentage,
+    pub dew_point: Temperature,
+    pub surface_pressure: Pressure,
+    pub cloud_cover: Percentage,
+    pub wind_speed: Speed,
+    pub max_wind_speed: Speed,
+    pub total_precipitation: Length,
+    pub precipitation_probability: Percentage,
+    pub rain: Length,
+    pub snowfall: Length,
+    pub visibility: Length,
+}
+
+impl From<&hourly_weather::Model> for WeatherHour {
+    fn from(model: &hourly_weather::Model) -> Self {
+        Self {
+            time: model.time_utc.and_utc(),
+            wmo_code: WMOCode::from(u8::try_from(model.wmo_code).unwrap_or(u8::MAX)),
+            temperature: Temperature::from_celsius(model.temperature_actual),
+            apparent_temperature: Temperature::from_celsius(model.temperature_apparent),
+            relative_humidity: Percentage::from_0_1(model.relative_humidity),
+            dew_point: Temperature::from_celsius(model.dew_point),
+            surface_pressure: Pressure::from_hpa(model.surface_pressure),
+            cloud_cover: Percentage::from_0_1(model.cloud_cover),
+            wind_speed: Speed::from_km_h(model.wind_speed),
+            max_wind_speed: Speed::from_km_h(model.max_wind_speed),
+            total_precipitation: Length::from_millimeters(model.total_precipitation),
+            precipitation_probability: Percentage::from_0_1(model.precipitation_probability),
+            rain: Length::from_millimeters(model.rain),
+            snowfall: Length::from_millimeters(model.snowfall),
+            visibility: Length::from_meters(model.visibility),
+        }
+    }
+}
+
+/// Splits hours sorted by time into runs of consecutive hours that match, a gap in the data ends a run.
+pub fn consecutive_windows(
+    hours: &[WeatherHour],
+    matches: impl Fn(&WeatherHour) -> bool,
+) -> Vec<&[WeatherHour]> {
+    let mut windows = Vec::new();
+    let mut start = None;
+    for (index, hour) in hours.iter().enumerate() {
+        let continues = index > 0 && hour.time - hours[index - 1].time == TimeDelta::hours(1);
+        match (start, matches(hour)) {
+            (Some(first), true) if !continues => {
+                windows.push(&hours[first..index]);
+                start = Some(index);
+            }
+            (Some(first), false) => {
+                windows.push(&hours[first..index]);
+                start = None;
+            }
+            (None, true) => start = Some(index),
+            _ => {}
+        }
+    }
+    if let Some(first) = start {
+        windows.push(&hours[first..]);
+    }
+    windows
+}
+
+/// Start (inclusive) and end (exclusive) of a local day, which isn't always 24 hours long.
+pub fn local_day_bounds(date: NaiveDate, timezone: Tz) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start_of = |date: NaiveDate| {
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+        timezone
+            .from_local_datetime(&midnight)
+            .earliest()
+            // Midnight can be skipped by a DST change, an hour later always exists.
+            .or_else(|| {
+                timezone
+                    .from_local_datetime(&(midnight + TimeDelta::hours(1)))
+                    .earliest()
+            })
+            .map(|time| time.with_timezone(&Utc))
+            .unwrap_or_else(|| midnight.and_utc())
+    };
+    let next = date.succ_opt().unwrap_or(date);
+    (start_of(date), start_of(next))
+}
@@ -0,0 +1,265 @@
+use crate::database::entity::hourly_weather;
+use chrono::{DateTime, TimeDelta, Utc};
+use omnistat_core::types::wmo_code::WMOCode;
+use omnistat_integrations::apis::open_meteo::hourly_forecast::OpenMeteoHourly;
+use std::collections::BTreeMap;
+
+/// Dew point may exceed the temperature by this much, both are rounded upstream.
+const DEW_POINT_TOLERANCE_CELSIUS: f32 = 0.5;
+/// Changes from one hour to the next beyond these are implausible outside of instrument errors.
+const MAX_TEMPERATURE_JUMP_CELSIUS: f32 = 10.0;
+const MAX_PRESSURE_JUMP_HPA: f32 = 10.0;
+/// Temperatures this many standard deviations away from the recent history are outliers.
+const MAX_TEMPERATURE_DEVIATIONS: f32 = 5.0;
+/// The history must cover at least a week before outliers are detected.
+const MIN_HISTORY_HOURS: usize = 7 * 24;
+/// Keeps a very steady history from turning every small change into an outlier.
+const MIN_TEMPERATURE_DEVIATION_CELSIUS: f32 = 2.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum QualityCheck {
+    /// NaN or infinite values
+    NotFinite,
+    /// A value outside of what is physically possible
+    OutOfBounds,
+    DewPointAboveTemperature,
+    UnknownWeatherCode,
+    /// Sudden change compared to the previous hour
+    Jump,
+    /// Far off the recent history
+    Outlier,
+}
+
+impl QualityCheck {
+    pub fn name(self) -> &'static str {
+        match self {
+            QualityCheck::NotFinite => "not_finite",
+            QualityCheck::OutOfBounds => "out_of_bounds",
+            QualityCheck::DewPointAboveTemperature => "dew_point_above_temperature",
+            QualityCheck::UnknownWeatherCode => "unknown_weather_code",
+            QualityCheck::Jump => "jump",
+            QualityCheck::Outlier => "outlier",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct QualityIssue {
+    pub check: QualityCheck,
+    pub message: String,
+}
+
+/// Checks an hour against what is physically possible.
+pub fn check_bounds(hourly: &OpenMeteoHourly) -> Vec<QualityIssue> {
+    let mut issues = Vec::new();
+    let temperature = hourly.temperature_2m.as_celsius();
+    let dew_point = hourly.dew_point_2m.as_celsius();
+
+    let values = [
+        ("temperature", temperature as f64, -90.0, 60.0),
+        (
+            "apparent_temperature",
+            hourly.apparent_temperature.as_celsius() as f64,
+            -100.0,
+            70.0,
+        ),
+        ("dew_point", dew_point as f64, -100.0, 40.0),
+        (
+            "relative_humidity",
+            hourly.relative_humidity_2m.as_0_1() as f64,
+            0.0,
+            1.0,
+        ),
+        ("cloud_cover", hourly.cloud_cover.as_0_1() as f64, 0.0, 1.0),
+        (
+            "cloud_cover_low",
+            hourly.cloud_cover_low.as_0_1() as f64,
+            0.0,
+            1.0,
+        ),
+        (
+            "cloud_cover_mid",
+            hourly.cloud_cover_mid.as_0_1() as f64,
+            0.0,
+            1.0,
+        ),
+        (
+            "cloud_cover_high",
+            hourly.cloud_cover_high.as_0_1() as f64,
+            0.0,
+            1.0,
+        ),
+        (
+            "precipitation_probability",
+            hourly.precipitation_probability.as_0_1() as f64,
+            0.0,
+            1.0,
+        ),
+        (
+            "surface_pressure",
+            hourly.surface_pressure.as_hpa() as f64,
+            300.0,
+            1100.0,
+        ),
+        (
+            "wind_speed",
+            hourly.wind_speed_10m.as_km_h() as f64,
+            0.0,
+            400.0,
+        ),
+        (
+            "max_wind_speed",
+            hourly.max_wind_speed_10m.as_km_h() as f64,
+            0.0,
+            500.0,
+        ),
+        (
+            "total_precipitation",
+            hourly.total_precipitation.as_millimeters(),
+            0.0,
+            400.0,
+        ),
+        ("rain", hourly.rain.as_millimeters(), 0.0, 400.0),
+        ("showers", hourly.showers.as_millimeters(), 0.0, 400.0),
+        ("snowfall", hourly.snowfall.as_millimeters(), 0.0, 2000.0),
+        (
+            "snow_depth",
+            hourly.snow_depth.as_millimeters(),
+            0.0,
+            50_000.0,
+        ),
+        (
+            "visibility",
+            hourly.visibility.as_meters(),
+            0.0,
+            f64::INFINITY,
+        ),
+        (
+            "shortwave_radiation",
+            hourly.shortwave_radiation.as_w_m2() as f64,
+            0.0,
+            1500.0,
+        ),
+    ];
+    for (name, value, min, max) in values {
+        if !value.is_finite() {
+            issues.push(QualityIssue {
+                check: QualityCheck::NotFinite,
+                message: format!("{name} is {value}"),
+            });
+        } else if value < min || value > max {
+            issues.push(QualityIssue {
+                check: QualityCheck::OutOfBounds,
+                message: format!("{name} {value:.2} is outside of {min}..={max}"),
+            });
+        }
+    }
+
+    if dew_point > temperature + DEW_POINT_TOLERANCE_CELSIUS {
+        issues.push(QualityIssue {
+            check: QualityCheck::DewPointAboveTemperature,
+            message: format!(
+                "dew point {dew_point:.1} °C is above temperature {temperature:.1} °C"
+            ),
+        });
+    }
+    if matches!(hourly.wmo_code, WMOCode::Unknown) {
+        issues.push(QualityIssue {
+            check: QualityCheck::UnknownWeatherCode,
+            message: "weather code is unknown".to_string(),
+        });
+    }
+    issues
+}
+
+/// The stored hours around a sync, new hours are compared to it before they are stored.
+pub struct History {
+    /// Temperature and pressure by hour, accepted hours replace stored ones
+    hours: BTreeMap<DateTime<Utc>, (f32, f32)>,
+    /// Mean and standard deviation of the temperature before the synced hours
+    temperature: Option<(f32, f32)>,
+}
+
+impl History {
+    /// `stored` are the hours before and within the synced range, `first` is the first synced hour.
+    pub fn new(stored: &[hourly_weather::Model], first: DateTime<Utc>) -> Self {
+        let hours = stored
+            .iter()
+            .map(|hour| {
+                (
+                    hour.time_utc.and_utc(),
+                    (hour.temperature_actual, hour.surface_pressure),
+                )
+            })
+            .collect();
+
+        let before: Vec<f32> = stored
+            .iter()
+            .filter(|hour| hour.time_utc.and_utc() < first)
+            .map(|hour| hour.temperature_actual)
+            .collect();
+        let temperature = (before.len() >= MIN_HISTORY_HOURS).then(|| {
+            let mean = before.iter().sum::<f32>() / before.len() as f32;
+            let variance = before
+                .iter()
+                .map(|value| (value - mean).powi(2))
+                .sum::<f32>()
+                / before.len() as f32;
+            (mean, variance.sqrt())
+        });
+
+        Self { hours, temperature }
+    }
+
+    /// Checks an hour against the previous hour and the recent history.
+    pub fn check(&self, hourly: &OpenMeteoHourly) -> Vec<QualityIssue> {
+        let mut issues = Vec::new();
+        let temperature = hourly.temperature_2m.as_celsius();
+        let pressure = hourly.surface_pressure.as_hpa();
+
+        if let Some((previous_temperature, previous_pressure)) =
+            self.hours.get(&(hourly.time - TimeDelta::hours(1)))
+        {
+            if (temperature - previous_temperature).abs() > MAX_TEMPERATURE_JUMP_CELSIUS {
+                issues.push(QualityIssue {
+                    check: QualityCheck::Jump,
+                    message: format!(
+                        "temperature jumped from {previous_temperature:.1} °C to {temperature:.1} °C within an hour"
+                    ),
+                });
+            }
+            if (pressure - previous_pressure).abs() > MAX_PRESSURE_JUMP_HPA {
+                issues.push(QualityIssue {
+                    check: QualityCheck::Jump,
+                    message: format!(
+                        "surface pressure jumped from {previous_pressure:.1} hPa to {pressure:.1} hPa within an hour"
+                    ),
+                });
+            }
+        }
+
+        if let Some((mean, deviation)) = self.temperature {
+            let deviation = deviation.max(MIN_TEMPERATURE_DEVIATION_CELSIUS);
+            if (temperature - mean).abs() > MAX_TEMPERATURE_DEVIATIONS * deviation {
+                issues.push(QualityIssue {
+                    check: QualityCheck::Outlier,
+                    message: format!(
+                        "temperature {temperature:.1} °C is more than {MAX_TEMPERATURE_DEVIATIONS} standard deviations off the recent mean of {mean:.1} °C"
+                    ),
+                });
+            }
+        }
+        issues
+    }
+
+    /// Makes a stored hour the neighbour of the next one.
+    pub fn accept(&mut self, hourly: &OpenMeteoHourly) {
+        self.hours.insert(
+            hourly.time,
+            (
+                hourly.temperature_2m.as_celsius(),
+                hourly.surface_pressure.as_hpa(),
+            ),
+        );
+    }
+}
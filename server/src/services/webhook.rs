@@ -0,0 +1,287 @@
+use crate::config::SharedConfig;
+use crate::config::webhook::WebhookConfig;
+use crate::database::entity::webhook_delivery;
+use crate::database::entity_ext::webhook_delivery::DeliveryStatus;
+use crate::events::{EventBus, ServerEvent};
+use crate::services::ServiceInitContext;
+use chrono::{TimeDelta, Utc};
+use hmac::{Hmac, Mac};
+use omnistat_integrations::client::ApiClient;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, IntoActiveModel, Set};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::task::TaskTracker;
+use tracing::{error, info, warn};
+
+const BATCH_SIZE: u64 = 100;
+const FIRST_RETRY_DELAY: TimeDelta = TimeDelta::seconds(30);
+const MAX_RETRY_DELAY: TimeDelta = TimeDelta::hours(6);
+const DELIVERY_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Delivers server events to the configured webhooks through a persistent outbox.
+///
+/// Every event is stored per webhook before it is sent, failed deliveries are retried with
+/// exponential backoff and dead-lettered after the webhook's `max_attempts`.
+pub struct WebhookService {
+    config: SharedConfig,
+    db: Arc<DatabaseConnection>,
+    events: EventBus,
+    client: ApiClient,
+    /// Only one delivery run at a time, so a delivery is never sent twice concurrently
+    delivery_lock: Mutex<()>,
+    /// Set while a delivery run waits for the lock, events stored meanwhile are picked up by it
+    delivery_queued: AtomicBool,
+    deliveries: TaskTracker,
+}
+
+impl WebhookService {
+    pub fn initialize(context: &ServiceInitContext) -> Arc<Self> {
+        let http = reqwest::Client::builder()
+            .connect_timeout(DELIVERY_CONNECT_TIMEOUT)
+            .timeout(DELIVERY_TIMEOUT)
+            .build()
+            .expect("Webhook HTTP client should build");
+        Arc::new(Self {
+            config: context.config.clone(),
+            db: context.db.clone(),
+            events: context.events.clone(),
+            // Retries are scheduled by the outbox, so every attempt is a single request.
+            client: ApiClient::with_client(http, 60, 10, Duration::from_secs(1), 0),
+            delivery_lock: Mutex::new(()),
+            delivery_queued: AtomicBool::new(false),
+            deliveries: TaskTracker::new(),
+        })
+    }
+
+    /// Stores every published event in the outbox and delivers it right away, in the background
+    /// so that slow webhooks don't hold up storing the events that follow.
+    pub fn start_listening(self: &Arc<Self>) {
+        let service = self.clone();
+        let mut events = self.events.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        if service.handle(&event).await {
+                            service.schedule_delivery();
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => service.handle_missed(skipped),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Stores the event in the outbox without delivering it, returns whether it was stored.
+    pub async fn handle(&self, event: &ServerEvent) -> bool {
+        match self.enqueue(event).await {
+            Ok(_) => true,
+            Err(e) => {
+                error!(
+                    "Failed to store {} for webhooks: {e:#}",
+                    event.kind().name()
+                );
+                false
+            }
+        }
+    }
+
+    pub fn handle_missed(&self, skipped: u64) {
+        warn!("Missed {skipped} event(s), they won't reach any webhook");
+    }
+
+    /// Adds a delivery for every webhook that wants the event, returns the amount of deliveries.
+    pub async fn enqueue(&self, event: &ServerEvent) -> anyhow::Result<usize> {
+        let config = self.config.load_full();
+        let payload = serde_json::to_string(event)?;
+        let now = Utc::now().naive_utc();
+
+        let mut count = 0;
+        for (name, webhook) in &config.webhooks {
+            if !webhook.wants(event.kind()) {
+                continue;
+            }
+            webhook_delivery::ActiveModel {
+                webhook: Set(name.clone()),
+                event: Set(event.kind().name().to_string()),
+                payload: Set(payload.clone()),
+                status: Set(DeliveryStatus::Pending.as_str().to_string()),
+                attempts: Set(0),
+                next_attempt_at_utc: Set(now),
+                last_error: Set(None),
+                created_at_utc: Set(now),
+                delivered_at_utc: Set(None),
+                ..Default::default()
+            }
+            .insert(self.db.as_ref())
+            .await?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Runs [`WebhookService::deliver_due`] in the background, unless a run is already waiting.
+    fn schedule_delivery(self: &Arc<Self>) {
+        if self.delivery_queued.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let service = self.clone();
+        self.deliveries.spawn(async move {
+            let _guard = service.delivery_lock.lock().await;
+            service.delivery_queued.store(false, Ordering::Release);
+            service.deliver_due_locked().await;
+        });
+    }
+
+    /// Attempts every delivery that is due.
+    pub async fn deliver_due(&self) {
+        let _guard = self.delivery_lock.lock().await;
+        self.deliver_due_locked().await;
+    }
+
+    async fn deliver_due_locked(&self) {
+        loop {
+            let now = Utc::now().naive_utc();
+            let due =
+                match webhook_delivery::Entity::find_due(now, BATCH_SIZE, self.db.as_ref()).await {
+                    Ok(due) => due,
+                    Err(e) => {
+                        error!("Failed to load due webhook deliveries: {e:#}");
+                        return;
+                    }
+                };
+            let exhausted = (due.len() as u64) < BATCH_SIZE;
+            for delivery in due {
+                let id = delivery.id;
+                if let Err(e) = self.attempt(delivery).await {
+                    error!("Failed to update webhook delivery {id}: {e:#}");
+                }
+            }
+            if exhausted {
+                return;
+            }
+        }
+    }
+
+    /// Queues a delivery again with fresh attempts, even if it was delivered or dead-lettered.
+    pub async fn replay(&self, id: i32) -> anyhow::Result<Option<webhook_delivery::Model>> {
+        let Some(delivery) = webhook_delivery::Entity::find_by_id(id)
+            .one(self.db.as_ref())
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let mut delivery = delivery.into_active_model();
+        delivery.status = Set(DeliveryStatus::Pending.as_str().to_string());
+        delivery.attempts = Set(0);
+        delivery.next_attempt_at_utc = Set(Utc::now().naive_utc());
+        delivery.last_error = Set(None);
+        delivery.delivered_at_utc = Set(None);
+        delivery.update(self.db.as_ref()).await?;
+        info!("Replaying webhook delivery {id}");
+
+        self.deliver_due().await;
+        Ok(webhook_delivery::Entity::find_by_id(id)
+            .one(self.db.as_ref())
+            .await?)
+    }
+
+    pub async fn deliveries(
+        &self,
+        status: DeliveryStatus,
+        limit: u64,
+    ) -> anyhow::Result<Vec<webhook_delivery::Model>> {
+        webhook_delivery::Entity::find_by_status(status, limit, self.db.as_ref()).await
+    }
+
+    async fn attempt(&self, delivery: webhook_delivery::Model) -> anyhow::Result<()> {
+        let config = self.config.load_full();
+        let result = match config.webhooks.get(&delivery.webhook) {
+            Some(webhook) => self.send(webhook, &delivery).await,
+            None => Err(anyhow::anyhow!(
+                "Webhook '{}' is no longer configured",
+                delivery.webhook
+            )),
+        };
+        let max_attempts = config
+            .webhooks
+            .get(&delivery.webhook)
+            .map(|webhook| webhook.max_attempts)
+            .unwrap_or(0);
+
+        let (id, webhook_name) = (delivery.id, delivery.webhook.clone());
+        let attempts = delivery.attempts + 1;
+        let now = Utc::now().naive_utc();
+        let mut delivery = delivery.into_active_model();
+        delivery.attempts = Set(attempts);
+        match result {
+            Ok(()) => {
+                delivery.status = Set(DeliveryStatus::Delivered.as_str().to_string());
+                delivery.delivered_at_utc = Set(Some(now));
+                delivery.last_error = Set(None);
+                info!("Delivered webhook delivery {id} to '{webhook_name}'");
+            }
+            Err(e) if attempts as u32 >= max_attempts => {
+                delivery.status = Set(DeliveryStatus::Dead.as_str().to_string());
+                delivery.last_error = Set(Some(e.to_string()));
+                warn!(
+                    "Dead-lettered webhook delivery {id} to '{webhook_name}' after {attempts} attempt(s): {e}"
+                );
+            }
+            Err(e) => {
+                let next_attempt = now + retry_delay(attempts as u32);
+                delivery.next_attempt_at_utc = Set(next_attempt);
+                delivery.last_error = Set(Some(e.to_string()));
+                warn!(
+                    "Webhook delivery {id} to '{webhook_name}' failed, retrying at {next_attempt}: {e}"
+                );
+            }
+        }
+        delivery.update(self.db.as_ref()).await?;
+        Ok(())
+    }
+
+    async fn send(
+        &self,
+        webhook: &WebhookConfig,
+        delivery: &webhook_delivery::Model,
+    ) -> anyhow::Result<()> {
+        let timestamp = Utc::now().timestamp().to_string();
+        let signature = sign(&webhook.secret, &timestamp, &delivery.payload)?;
+        self.client
+            .request(&webhook.url)?
+            .header("Content-Type", "application/json")?
+            .header("X-Omnistat-Event", &delivery.event)?
+            .header("X-Omnistat-Delivery", delivery.id.to_string())?
+            .header("X-Omnistat-Timestamp", &timestamp)?
+            .header("X-Omnistat-Signature", format!("sha256={signature}"))?
+            .post(delivery.payload.clone())
+            .await?;
+        Ok(())
+    }
+}
+
+/// Hex encoded HMAC-SHA256 of `{timestamp}.{payload}`, the timestamp is signed to prevent replays.
+fn sign(secret: &str, timestamp: &str, payload: &str) -> anyhow::Result<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(payload.as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Doubles with every failed attempt, starting at [`FIRST_RETRY_DELAY`].
+fn retry_delay(attempts: u32) -> TimeDelta {
+    let factor = 2_i32.saturating_pow(attempts.saturating_sub(1));
+    FIRST_RETRY_DELAY
+        .checked_mul(factor)
+        .unwrap_or(MAX_RETRY_DELAY)
+        .min(MAX_RETRY_DELAY)
+}
@@ -1,5 +1,6 @@
 use crate::apis::Apis;
-use crate::config::Config;
+use crate::config::{Config, SharedConfig};
+use crate::events::EventBus;
 use crate::services::{ServiceInitContext, Services};
 use migration::{Migrator, MigratorTrait};
 use sea_orm::{ConnectOptions, DatabaseConnection};
@@ -7,7 +8,7 @@ use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct ServerState {
-    pub config: Arc<Config>,
+    pub config: SharedConfig,
     pub db: Arc<DatabaseConnection>,
     pub apis: Arc<Apis>,
     pub services: Arc<Services>,
@@ -23,6 +24,7 @@ impl ServerState {
             config: config.clone(),
             db: db.clone(),
             apis: apis.clone(),
+            events: EventBus::new(),
         };
         let services = Services::initialize(service_init_context);
 
@@ -34,10 +36,15 @@ impl ServerState {
         }))
     }
 
-    async fn initialize_db(config: &Arc<Config>) -> anyhow::Result<Arc<DatabaseConnection>> {
-        let options = ConnectOptions::new(&config.db_url);
-        let connection = sea_orm::Database::connect(options).await?;
+    async fn initialize_db(config: &SharedConfig) -> anyhow::Result<Arc<DatabaseConnection>> {
+        let connection = Self::connect_db(config).await?;
         Migrator::up(&connection, None).await?;
         Ok(Arc::new(connection))
     }
+
+    /// Connects without applying pending migrations.
+    pub async fn connect_db(config: &SharedConfig) -> anyhow::Result<DatabaseConnection> {
+        let options = ConnectOptions::new(&config.load().db_url);
+        Ok(sea_orm::Database::connect(options).await?)
+    }
 }